@@ -1,5 +1,26 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Without `std`, `core` stands in for it, and tests box their configuration
+// with `alloc`.
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+#[cfg(all(test, not(feature = "std")))]
+extern crate alloc;
+
+#[cfg(test)]
+extern crate proptest;
 extern crate linalg;
+#[cfg(feature = "std")]
 extern crate utilities;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
+#[cfg(feature = "std")]
 pub mod test_utilities;
+mod tests;
 pub mod types;
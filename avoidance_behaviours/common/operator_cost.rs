@@ -0,0 +1,140 @@
+#![feature(test)]
+
+extern crate linalg;
+use linalg::vector2d::Vec2D;
+
+extern crate test;
+use test::black_box;
+
+extern crate time;
+use time::PreciseTime;
+
+extern crate utilities;
+use utilities::rng_utilities::random_unity;
+
+// Number of inputs evaluated between two readings of the clock, so that each
+// sample is much longer than the overhead of measuring it.
+const BLOCK_SIZE: usize = 1_000;
+
+// Number of timed blocks of each form of each kernel.
+const NUM_BLOCKS: usize = 10_000;
+
+// Four vectors from which each kernel evaluates one result.
+type Input = (Vec2D, Vec2D, Vec2D, Vec2D);
+
+// Returns a vector with random components.
+fn random_vector() -> Vec2D {
+    Vec2D::new(1e3f64 * random_unity(), 1e3f64 * random_unity())
+}
+
+// Returns the separation of two vehicles, given their positions and
+// velocities, at their closest approach, as *avoid vehicle* finds it.
+fn approach_operators(&(p1, v1, p2, v2): &Input) -> Vec2D {
+    let relative_position = p1 - p2;
+    let relative_velocity = v2 - v1;
+    let time = relative_position.dot(relative_velocity)
+             / relative_velocity.mag_sq();
+    relative_position - relative_velocity * time
+}
+
+// As `approach_operators`, with the original methods.
+fn approach_methods(&(p1, v1, p2, v2): &Input) -> Vec2D {
+    let relative_position = p1.sub(p2);
+    let relative_velocity = v2.sub(v1);
+    let time = relative_position.dot(relative_velocity)
+             / relative_velocity.mag_sq();
+    relative_position.sub(relative_velocity.mul(time))
+}
+
+// Returns the potential of a repulsor, given the vehicle's velocity and the
+// radial and tangential weights, as the xetrov field function combines them.
+fn potential_operators(&(repulsor, velocity, weights, _): &Input) -> Vec2D {
+    let mut tangent = repulsor.perp();
+    if tangent.dot(velocity) < 0f64 { tangent = -tangent; }
+    repulsor * weights.x + tangent * weights.y
+}
+
+// As `potential_operators`, with the original methods.
+fn potential_methods(&(repulsor, velocity, weights, _): &Input) -> Vec2D {
+    let mut tangent = repulsor.perp();
+    if tangent.dot(velocity) < 0f64 { tangent = tangent.neg(); }
+    repulsor.mul(weights.x).add(tangent.mul(weights.y))
+}
+
+// Returns a weighted sum of the vectors, as potentials are accumulated.
+fn sum_operators(&(a, b, c, d): &Input) -> Vec2D {
+    let mut sum = Vec2D::zero();
+    sum += a;
+    sum -= b;
+    sum += c * 0.5f64;
+    sum += d * 0.25f64;
+    sum
+}
+
+// As `sum_operators`, with the original methods.
+fn sum_methods(&(a, b, c, d): &Input) -> Vec2D {
+    let mut sum = Vec2D::zero();
+    sum = sum.add(a);
+    sum = sum.sub(b);
+    sum = sum.add(c.mul(0.5f64));
+    sum = sum.add(d.mul(0.25f64));
+    sum
+}
+
+// Returns the per-input cost, in nanoseconds, of a block of `kernel` applied
+// to every input.
+fn time_block<F>(inputs: &[Input], kernel: &F) -> f64
+    where F: Fn(&Input) -> Vec2D
+{
+    let start = PreciseTime::now();
+    for input in inputs.iter() {
+        black_box(kernel(black_box(input)));
+    }
+    let elapsed = start.to(PreciseTime::now());
+    elapsed.num_nanoseconds().unwrap() as f64 / BLOCK_SIZE as f64
+}
+
+// Returns the median of the given costs.
+fn median(mut costs: Vec<f64>) -> f64 {
+    costs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    costs[costs.len() / 2]
+}
+
+// Checks that both forms of a kernel agree on every input, then times them
+// and prints the median cost of each and their ratio. Blocks of the two forms
+// alternate, each going first in turn, so that neither gains from the order.
+fn compare<F, G>(label: &str, inputs: &[Input], operators: F, methods: G)
+    where F: Fn(&Input) -> Vec2D, G: Fn(&Input) -> Vec2D
+{
+    assert!( inputs.iter().all(|x| operators(x) == methods(x))
+           , "forms of {} disagree", label );
+    let mut operator = Vec::with_capacity(NUM_BLOCKS);
+    let mut method = Vec::with_capacity(NUM_BLOCKS);
+    for i in 0..NUM_BLOCKS {
+        if i % 2 == 0 {
+            operator.push(time_block(inputs, &operators));
+            method.push(time_block(inputs, &methods));
+        } else {
+            method.push(time_block(inputs, &methods));
+            operator.push(time_block(inputs, &operators));
+        }
+    }
+    let (operator, method) = (median(operator), median(method));
+    println!( "{:>10} {:>10.2} {:>10.2} {:>10.3}"
+            , label, operator, method, operator / method );
+}
+
+// Times the kernels of the algorithms written with the `Vec2D` operators and
+// with the original methods, after checking that both forms produce the same
+// results. Costs are reported in nanoseconds per input.
+fn main() {
+    let inputs: Vec<Input> = (0..BLOCK_SIZE).map(|_| {
+        (random_vector(), random_vector(), random_vector(), random_vector())
+    }).collect();
+
+    println!( "{:>10} {:>10} {:>10} {:>10}"
+            , "kernel", "operators", "methods", "ratio" );
+    compare("approach", &inputs, approach_operators, approach_methods);
+    compare("potential", &inputs, potential_operators, potential_methods);
+    compare("sum", &inputs, sum_operators, sum_methods);
+}
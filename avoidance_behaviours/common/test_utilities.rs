@@ -1,29 +1,38 @@
+use super::utilities::test_utilities::Failure;
 use super::utilities::types::HasScenario;
 
 // Number of each test to execute.
 const NUM_RUNS: u32 = 10_000;
 
-// Verifies that the expected number of interactions occur.
-pub fn expected_interactions<F>(creator: F, expected: u32) -> bool
+// Verifies that the expected number of interactions occur, returning a report
+// of the first scenario in which they do not.
+pub fn expected_interactions<F>(creator: F, expected: u32)
+    -> Result<(), Failure>
     where F: Fn() -> Box<HasScenario>
 {
-    for _ in 0..NUM_RUNS {
-        if creator().interactions() != expected {
-            return false;
+    for i in 0..NUM_RUNS {
+        let mut scenario = creator();
+        if scenario.interactions() != expected {
+            return Err(Failure::new( "Unexpected number of interactions"
+                                   , i, Some(expected), None
+                                   , Some(&mut scenario) ));
         }
     }
-    true
+    Ok(())
 }
 
-// Verifies whether a avoidance force was produced.
-pub fn expected_avoidance<F>(creator: F, expected: bool) -> bool
+// Verifies whether a avoidance force was produced, returning a report of the
+// first scenario in which the result was not as expected.
+pub fn expected_avoidance<F>(creator: F, expected: bool) -> Result<(), Failure>
     where F: Fn() -> Box<HasScenario>
 {
-    for _ in 0..NUM_RUNS {
-        match creator().avoidance() {
-            Some(_) => if !expected { return false },
-            None => if expected { return false }
+    for i in 0..NUM_RUNS {
+        let mut scenario = creator();
+        if scenario.avoidance().is_some() != expected {
+            return Err(Failure::new( "Unexpected avoidance result"
+                                   , i, None, Some(expected)
+                                   , Some(&mut scenario) ));
         }
     }
-    true
+    Ok(())
 }
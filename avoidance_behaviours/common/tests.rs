@@ -0,0 +1,117 @@
+#![cfg(test)]
+
+use super::linalg::scalar::Scalar;
+use super::linalg::vector2d::Vec2D;
+use super::types::Frame;
+use super::linalg::origin::Origin;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+use std::f64::consts::PI;
+
+// Returns the configuration of the property tests, which save the seeds of
+// failing cases in `tests.proptest-regressions` beside this file.
+fn config() -> ProptestConfig {
+    let saved = FileFailurePersistence::WithSource("proptest-regressions");
+    ProptestConfig { failure_persistence: Some(Box::new(saved))
+                   , .. ProptestConfig::default() }
+}
+
+// Defines the tests for the scalar type `$t` in the module `$name`, comparing
+// transformed points to within the relative tolerance `$tolerance`.
+macro_rules! tests { ($name:ident, $t:ident, $tolerance:expr) => { mod $name {
+use super::*;
+
+type T = $t;
+
+// Relative tolerance of comparisons between transformed points.
+const TOLERANCE: f64 = $tolerance;
+
+// Returns a strategy producing angles over two full turns.
+fn angle() -> BoxedStrategy<T> {
+    (-2f64 * PI..2f64 * PI).prop_map(T::from_f64).boxed()
+}
+
+// Returns a strategy producing points and positions.
+fn point() -> BoxedStrategy<Vec2D<T>> {
+    (-1e3f64..1e3f64, -1e3f64..1e3f64).prop_map(|(x, y)| {
+        Vec2D::new(T::from_f64(x), T::from_f64(y))
+    }).boxed()
+}
+
+// Returns whether two points are equal to within the tolerance, relative to
+// the largest coordinates produced by `point`.
+fn close(a: Vec2D<T>, b: Vec2D<T>) -> bool {
+    (a - b).mag().to_f64() <= TOLERANCE * 1e3f64
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn frame_round_trips(position in point(), orientation in angle(),
+                         v in point())
+    {
+        let frame = Frame::new(position, orientation);
+        let world = frame.to_world.transform(v);
+        prop_assert!(close(frame.to_local.transform(world), v));
+        let local = frame.to_local.transform(v);
+        prop_assert!(close(frame.to_world.transform(local), v));
+    }
+
+    #[test]
+    fn updated_frame_matches_new(position in point(), orientation in angle(),
+                                 v in point())
+    {
+        let frame = Frame::new(position, orientation);
+        let mut updated = Frame::new(position, orientation);
+        updated.update_matrices();
+        prop_assert!(close( updated.to_world.transform(v)
+                          , frame.to_world.transform(v) ));
+        prop_assert!(close( updated.to_local.transform(v)
+                          , frame.to_local.transform(v) ));
+    }
+
+    #[test]
+    fn frame_origin_is_position(position in point(), orientation in angle())
+    {
+        let frame = Frame::new(position, orientation);
+        prop_assert!(close(frame.to_world.transform(Vec2D::zero()), position));
+        let heading = frame.to_world.transform(Vec2D::unitx()) - position;
+        prop_assert!(close(heading, Vec2D::polar(orientation, T::ONE)));
+    }
+
+    #[test]
+    fn relative_frame_matches_absolute(position in point(),
+                                       orientation in angle(), v in point())
+    {
+        let frame = Frame::new(position, orientation);
+        let mut relative = Frame::with_origin( position, orientation
+                                             , Origin::Relative );
+        prop_assert!(close(relative.local_point(v), frame.local_point(v)));
+        prop_assert!(close(relative.world_point(v), frame.world_point(v)));
+        relative.update_matrices();
+        prop_assert!(close(relative.local_point(v), frame.local_point(v)));
+    }
+}
+} } }
+
+tests!(double, f64, 1e-9);
+tests!(single, f32, 1e-3);
+
+// Frames are serialised as their position, orientation and origin, and
+// deserialised frames rebuild the same transforms.
+#[cfg(all(feature = "std", feature = "serde"))]
+#[test]
+fn frames_serialise_parameters() {
+    let frame = Frame::with_origin( Vec2D::new(3f64, -4f64), 0.5f64
+                                  , Origin::Relative );
+    let json = ::serde_json::to_string(&frame).unwrap();
+    let expected = r#"{"position":{"x":3.0,"y":-4.0},"orientation":0.5,"#;
+    assert_eq!(json, expected.to_string() + r#""origin":"relative"}"#);
+    let rebuilt: Frame = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.to_world, frame.to_world);
+    assert_eq!(rebuilt.to_local, frame.to_local);
+}
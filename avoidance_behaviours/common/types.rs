@@ -1,29 +1,123 @@
-use super::linalg::matrix2d::Mat2D;
+use super::linalg::scalar::Scalar;
+use super::linalg::transform2d::Transform2D;
 use super::linalg::vector2d::Vec2D;
+use super::linalg::origin::Origin;
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use super::serde::ser::SerializeStruct;
 
-// Local space with position and orientation.
-pub struct Frame { pub position:    Vec2D
-                 , pub orientation: f64
-                 , pub to_world:    Mat2D
-                 , pub to_local:    Mat2D
+// Local space with position and orientation. The rigid transforms of absolute
+// frames transform world coordinates directly. Those of relative frames only
+// rotate, and points are translated by the frame's position separately, so
+// that transforms far from the world origin do not lose precision.
+pub struct Frame<T = f64> { pub position:    Vec2D<T>
+                          , pub orientation: T
+                          , pub origin:      Origin
+                          , pub to_world:    Transform2D<T>
+                          , pub to_local:    Transform2D<T>
 }
 
-impl Frame {
+impl<T: Scalar> Frame<T> {
     // Creates a vehicle with the given position and orientation.
-    pub fn new(pos: Vec2D, ori: f64) -> Frame {
-        let to_local = Mat2D::translation(pos.neg()).turn(-ori);
-        let to_world = Mat2D::rotation(ori).shift(pos);
+    pub fn new(pos: Vec2D<T>, ori: T) -> Frame<T> {
+        Frame::with_origin(pos, ori, Origin::Absolute)
+    }
+
+    // Creates a frame with the given position and orientation that
+    // transforms about the given origin.
+    pub fn with_origin(pos: Vec2D<T>, ori: T, origin: Origin) -> Frame<T> {
+        let to_world = Frame::to_world(pos, ori, origin);
         Frame { position:    pos
               , orientation: ori
-              , to_local:    to_local
+              , origin:      origin
+              , to_local:    to_world.inverse()
               , to_world:    to_world }
     }
 
-    // Updates the vehicle's matrices.
+    // Returns the transform from local to world space, which only rotates for
+    // relative frames.
+    fn to_world(pos: Vec2D<T>, ori: T, origin: Origin) -> Transform2D<T> {
+        match origin {
+            Origin::Absolute => Transform2D::new(ori, pos),
+            Origin::Relative => Transform2D::rotation(ori)
+        }
+    }
+
+    // Updates the vehicle's transforms.
     pub fn update_matrices(&mut self) {
-        self.to_local = Mat2D::identity().shift(self.position.neg())
-                                         .turn(-self.orientation);
-        self.to_world = Mat2D::identity().turn(self.orientation)
-                                         .shift(self.position);
+        self.to_world = Frame::to_world( self.position
+                                       , self.orientation
+                                       , self.origin );
+        self.to_local = self.to_world.inverse();
+    }
+
+    // Returns the given world point in the space the transforms map
+    // from: unchanged for absolute frames, and relative to the frame's
+    // position for relative frames.
+    pub fn relative_point(&self, v: Vec2D<T>) -> Vec2D<T> {
+        match self.origin {
+            Origin::Absolute => v,
+            Origin::Relative => v - self.position
+        }
+    }
+
+    // Returns the given point, in the space the transforms map from, in
+    // world space.
+    pub fn absolute_point(&self, v: Vec2D<T>) -> Vec2D<T> {
+        match self.origin {
+            Origin::Absolute => v,
+            Origin::Relative => v + self.position
+        }
+    }
+
+    // Returns the given world point in local space.
+    pub fn local_point(&self, v: Vec2D<T>) -> Vec2D<T> {
+        self.to_local.transform(self.relative_point(v))
+    }
+
+    // Returns the given local point in world space.
+    pub fn world_point(&self, v: Vec2D<T>) -> Vec2D<T> {
+        let point = self.to_world.transform(v);
+        match self.origin {
+            Origin::Absolute => point,
+            Origin::Relative => point + self.position
+        }
+    }
+
+    // Returns the given local vector, such as a force, in world space.
+    pub fn world_vector(&self, v: Vec2D<T>) -> Vec2D<T> {
+        self.to_world.transform_vector(v)
+    }
+}
+
+// Defining parameters of a frame, as which it is serialised. Its transforms
+// follow from them.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct FrameParameters<T> { position:    Vec2D<T>
+                          , orientation: T
+                          , origin:      Origin }
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Frame<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        let mut state = serializer.serialize_struct("Frame", 3)?;
+        state.serialize_field("position", &self.position)?;
+        state.serialize_field("orientation", &self.orientation)?;
+        state.serialize_field("origin", &self.origin)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Scalar + Deserialize<'de>> Deserialize<'de> for Frame<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Frame<T>, D::Error>
+    {
+        let p = FrameParameters::deserialize(deserializer)?;
+        Ok(Frame::with_origin(p.position, p.orientation, p.origin))
     }
 }
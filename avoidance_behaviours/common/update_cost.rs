@@ -0,0 +1,81 @@
+#![feature(test)]
+
+extern crate common;
+use common::types::Frame;
+
+extern crate linalg;
+use linalg::matrix2d::Mat2D;
+use linalg::vector2d::Vec2D;
+
+extern crate test;
+use test::black_box;
+
+extern crate time;
+use time::PreciseTime;
+
+extern crate utilities;
+use utilities::rng_utilities::{random_tau, random_unity};
+
+// Number of frames updated between two readings of the clock, so that each
+// sample is much longer than the overhead of measuring it.
+const BLOCK_SIZE: usize = 1_000;
+
+// Number of timed blocks for each way of updating frames.
+const NUM_BLOCKS: usize = 10_000;
+
+// Returns a frame at a random position and orientation.
+fn random_frame() -> Frame {
+    let position = Vec2D::new(1e3f64 * random_unity(), 1e3f64 * random_unity());
+    Frame::new(position, random_tau())
+}
+
+// Builds the matrices of a frame as `Frame::update_matrices` did before
+// frames kept rigid transforms.
+fn matrices(frame: &Frame) -> (Mat2D, Mat2D) {
+    let to_local = Mat2D::identity().shift(-frame.position)
+                                    .turn(-frame.orientation);
+    let to_world = Mat2D::identity().turn(frame.orientation)
+                                    .shift(frame.position);
+    (to_local, to_world)
+}
+
+// Returns the per-frame cost, in nanoseconds, of each timed block of `update`
+// applied to every frame in the block.
+fn time_blocks<F>(frames: &mut [Frame], update: F) -> Vec<f64>
+    where F: Fn(&mut Frame)
+{
+    (0..NUM_BLOCKS).map(|_| {
+        let start = PreciseTime::now();
+        for frame in frames.iter_mut() {
+            update(frame);
+            black_box(&frame);
+        }
+        let elapsed = start.to(PreciseTime::now());
+        elapsed.num_nanoseconds().unwrap() as f64 / BLOCK_SIZE as f64
+    }).collect()
+}
+
+// Prints the median, mean and minimum of the given per-frame costs.
+fn print_costs(label: &str, mut costs: Vec<f64>) {
+    costs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mean = costs.iter().sum::<f64>() / costs.len() as f64;
+    println!( "{:>8} {:>10.2} {:>10.2} {:>10.2}"
+            , label, costs[costs.len() / 2], mean, costs[0] );
+}
+
+// Times the cost of updating a frame's transforms with 3x3 matrices and with
+// rigid transforms, and of not updating them at all, as on the `no-matrix`
+// branch. Costs are reported in nanoseconds per frame.
+fn main() {
+    let mut frames: Vec<Frame> = (0..BLOCK_SIZE).map(|_| random_frame())
+                                                .collect();
+
+    let none = time_blocks(&mut frames, |_| {});
+    let matrix = time_blocks(&mut frames, |x| { black_box(matrices(x)); });
+    let rigid = time_blocks(&mut frames, |x| x.update_matrices());
+
+    println!("{:>8} {:>10} {:>10} {:>10}", "update", "median", "mean", "min");
+    print_costs("none", none);
+    print_costs("matrix", matrix);
+    print_costs("rigid", rigid);
+}
@@ -2,9 +2,15 @@ extern crate disk_avoidance;
 use disk_avoidance::scenarios::*;
 
 extern crate utilities;
-use utilities::bench_utilities::time_batch;
-use utilities::types::{HasScenario, Obstacles};
-use utilities::utilities::{time_execution_seconds, write_results};
+use utilities::bench_utilities::{run, run_batched, run_fixed, run_single};
+use utilities::bench_utilities::run_worlds;
+use utilities::bench_utilities::BATCH_VARIANT;
+use utilities::record::Record;
+use utilities::results::DEFAULT_VARIANT;
+use utilities::types::{HasScenario, Obstacles, Origin};
+use utilities::precision::{FIXED_VARIANT, Q16, SINGLE_VARIANT};
+use utilities::utilities::get_variant;
+use utilities::world::{Kind, World, WORLD_VARIANT};
 
 // Length of the feeler.
 const FEELER_LENGTH: f64 = 10f64;
@@ -12,21 +18,77 @@ const FEELER_LENGTH: f64 = 10f64;
 // Width of the feeler.
 const FEELER_WIDTH: f64 = 2f64;
 
-// Starts benchmarks and writes results to file.
+// Label of the benchmarked algorithm in results.
+const ALGORITHM: &'static str = "avoidance_behaviours/disk_avoidance";
+
+// Starts benchmarks and writes results to file, or explains a scenario. The
+// `world` variant draws scenarios from shared worlds, the `relative` variant
+// transforms about the vehicle's position, the `f32` variant evaluates in
+// single precision, the `q16.16` variant evaluates in fixed point and the
+// `batch` variant evaluates disks in batches.
 fn main() {
-    let run = || {
-        let creator = |o: &Obstacles| -> Box<HasScenario> {
-            scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH).unwrap()
-        };
+    let variant = get_variant();
+    let single = |r: &Record| {
+        scenario_from_record_as::<f32>(r, Origin::Absolute)
+    };
+    let fixed = |r: &Record| {
+        scenario_from_record_as::<Q16>(r, Origin::Absolute)
+    };
+    let origin = match variant {
+        Some(ref name) if name == WORLD_VARIANT => {
+            let from_world = |w: &World| -> Box<HasScenario> {
+                scenario_from_world(w, FEELER_LENGTH, FEELER_WIDTH)
+            };
+            run_worlds( ALGORITHM, Kind::Disks, from_world
+                      , scenario_from_record, single, fixed );
+            return;
+        },
+        Some(ref name) if name == SINGLE_VARIANT => {
+            let creator = |o: &Obstacles| -> Box<HasScenario> {
+                scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+                    .unwrap()
+            };
+            run_single(ALGORITHM, creator, single);
+            return;
+        },
+        Some(ref name) if name == FIXED_VARIANT => {
+            let creator = |o: &Obstacles| -> Box<HasScenario> {
+                scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+                    .unwrap()
+            };
+            run_fixed(ALGORITHM, creator, fixed);
+            return;
+        },
+        Some(ref name) if name == BATCH_VARIANT => {
+            let creator = |o: &Obstacles| -> Box<HasScenario> {
+                scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+                    .unwrap()
+            };
+            let batched = |r: &Record| {
+                scenario_from_record_batched(r, Origin::Absolute)
+            };
+            run_batched(ALGORITHM, creator, batched, single, fixed);
+            return;
+        },
+        Some(ref name) if name == Origin::Relative.name() => Origin::Relative,
+        Some(_) => {
+            println!("Variants are world, relative, f32, q16.16 or batch.");
+            return;
+        },
+        None => Origin::Absolute
+    };
+    let label = match variant {
+        Some(_) => origin.name(),
+        None => DEFAULT_VARIANT
+    };
 
-        let mut results = vec!();
-        for i in 1..6 {
-            let interaction1 = Obstacles::new(i, 0u32);
-            results.push(time_batch(&interaction1, |x| creator(x)));
-            let interaction2 = Obstacles::new(0u32, i);
-            results.push(time_batch(&interaction2, |x| creator(x)));
+    let rebuild = |r: &Record| scenario_from_record_with_origin(r, origin);
+    let creator = |o: &Obstacles| -> Box<HasScenario> {
+        let scenario = scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH);
+        match origin {
+            Origin::Absolute => scenario.unwrap(),
+            Origin::Relative => rebuild(&scenario.unwrap().record()).unwrap()
         }
-        write_results(&results);
     };
-    println!("Total time: {} seconds", time_execution_seconds(run));
+    run(ALGORITHM, label, creator, rebuild, single, fixed);
 }
@@ -1,18 +1,25 @@
 use types::*;
 
 use super::common::types::Frame;
-use super::linalg::matrix2d::Mat2D;
+use super::linalg::batch::Lanes;
+use super::linalg::scalar::Scalar;
+use super::linalg::transform2d::Transform2D;
 use super::linalg::vector2d::Vec2D;
+use super::utilities::explain::Trace;
+use super::utilities::invariants::Evaluation;
+use super::utilities::oracle::{Geometry, Risk};
+use super::utilities::record::Record;
 use super::utilities::rng_utilities::*;
-use super::utilities::types::{HasScenario, Obstacles};
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+use super::utilities::world::World;
 
 use std::f64::consts::PI;
 
 // Arrangement of vehicle and disks to be used in benchmarks.
-pub struct Scenario { pub vehicle: Vehicle
-                    , pub disks:   Vec<Disk> }
+pub struct Scenario<T = f64> { pub vehicle: Vehicle<T>
+                             , pub disks:   Vec<Disk<T>> }
 
-impl HasScenario for Scenario {
+impl<T: Scalar> HasScenario for Scenario<T> {
     // Returns the interactions between the vehicle and obstacles in the
     // scenario.
     fn interactions(&self) -> u32 {
@@ -27,17 +34,108 @@ impl HasScenario for Scenario {
     // the steering scenario.
     fn avoidance(&mut self) -> Option<Vec2D> {
         self.vehicle.update();
-        self.vehicle.disk_avoidance(&self.disks)
+        self.vehicle.disk_avoidance(&self.disks).map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.vehicle.update();
+        self.vehicle.explain_avoidance(&self.disks)
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.vehicle.update();
+        self.vehicle.evaluate_avoidance(&self.disks)
+    }
+
+    // Returns the parameters defining the vehicle and disks.
+    fn record(&self) -> Record {
+        let mut record = self.vehicle.record();
+        for disk in self.disks.iter() {
+            record.obstacle(&disk.geometry().parameters());
+        }
+        record
+    }
+
+    // Returns whether the feeler interacts with each disk, and whether the
+    // disk lies in the path of the vehicle.
+    fn risks(&self) -> Vec<Risk> {
+        let body = self.vehicle.swept_body();
+        self.disks.iter().map(|disk| {
+            let predicted = self.vehicle.interaction(disk).is_some();
+            Risk::new(predicted, &body, &disk.geometry())
+        }).collect()
+    }
+
+    // Returns the translation of the vehicle's frame, which the avoidance
+    // force is transformed to world space with as a point.
+    fn force_origin(&self) -> Vec2D {
+        self.vehicle.frame.to_world.translation.cast()
     }
 }
 
-impl Scenario {
+impl<T: Scalar> Scenario<T> {
     // Convenience function for creating scenarios.
-    fn new(vehicle: Vehicle, disks: Vec<Disk>) -> Scenario {
+    fn new(vehicle: Vehicle<T>, disks: Vec<Disk<T>>) -> Scenario<T> {
         Scenario { vehicle: vehicle, disks: disks }
     }
 }
 
+// Scenario whose disks are also held as a batch, with which the interactions
+// and avoidance force are evaluated. Other evaluations use the disks one at a
+// time.
+pub struct BatchScenario<T = f64> { pub scenario: Scenario<T>
+                                  , pub batch:    DiskBatch<T> }
+
+impl<T: Lanes> HasScenario for BatchScenario<T> {
+    // Returns the interactions between the vehicle and obstacles in the
+    // scenario.
+    fn interactions(&self) -> u32 {
+        let mut count = 0;
+        self.scenario.vehicle.batch_interactions(&self.batch, |_, _| {
+            count += 1;
+        });
+        count
+    }
+
+    // Returns the avoidance force to be applied to the vehicle according to
+    // the steering scenario.
+    fn avoidance(&mut self) -> Option<Vec2D> {
+        let vehicle = &mut self.scenario.vehicle;
+        vehicle.update();
+        vehicle.batch_avoidance(&self.batch).map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.scenario.explain()
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.scenario.evaluation()
+    }
+
+    // Returns the parameters defining the vehicle and disks.
+    fn record(&self) -> Record {
+        self.scenario.record()
+    }
+
+    // Returns whether the feeler interacts with each disk, and whether the
+    // disk lies in the path of the vehicle.
+    fn risks(&self) -> Vec<Risk> {
+        self.scenario.risks()
+    }
+
+    // Returns the translation of the vehicle's frame.
+    fn force_origin(&self) -> Vec2D {
+        self.scenario.force_origin()
+    }
+}
+
 // Returns a vehicle with a semi-random position and orientation with the
 // given length and width.
 fn random_vehicle(length: f64, width: f64) -> Vehicle {
@@ -49,8 +147,10 @@ fn random_vehicle(length: f64, width: f64) -> Vehicle {
 
 // Returns a disk with a semi-random centre determined by `x_scale`, `y_scale`
 // and `y_offset`, which is then transformed by `transform`.
-fn near_disk(x_scale: f64, y_scale: f64, significant: bool, transform: &Mat2D)
-    -> Disk
+fn near_disk( x_scale:     f64
+            , y_scale:     f64
+            , significant: bool
+            , transform:   &Transform2D ) -> Disk
 {
     let radius = y_scale * random_margin();
     let mut offset = random_margin();
@@ -71,12 +171,63 @@ fn scenario(n: u32, x_scale: f64, y_scale: f64, significant: bool)
     -> Box<Scenario>
 {
     let vehicle = random_vehicle(x_scale, y_scale);
-    let to_world = vehicle.frame.to_world.clone();
+    let to_world = vehicle.frame.to_world;
     let f = |_| near_disk(x_scale, y_scale, significant, &to_world);
     let disks: Vec<Disk> = (0..n).map(f).collect();
     Box::new(Scenario::new(vehicle, disks))
 }
 
+// Returns a disk exactly on, or just outside, the boundary of the feeler
+// volume of a vehicle at `position` facing along the x-axis. The disk is
+// either tangent to the side of the volume or centred on its end.
+fn boundary_disk(length: f64, width: f64, significant: bool, position: Vec2D)
+    -> Disk
+{
+    let radius = random_integer(1, 3);
+    let margin = if significant { 0f64 } else { BOUNDARY_MARGIN };
+    let side = if random_unity() < 0.5f64 { 1f64 } else { -1f64 };
+
+    let local_centre = if random_unity() < 0.5f64 {
+        let x = random_integer(0, length as i32);
+        Vec2D::new(x, side * (radius + width + margin))
+    } else {
+        let y = random_integer(0, width as i32);
+        Vec2D::new(length + margin, side * y)
+    };
+    Disk::new(position + local_centre, radius)
+}
+
+// Returns a scenario involving `n` disks on the boundary of the feeler volume
+// of a vehicle facing along the x-axis from an integer position, so that the
+// geometry is exactly representable.
+fn boundary_scenario(n: u32, length: f64, width: f64, significant: bool)
+    -> Box<Scenario>
+{
+    let position = random_grid_point();
+    let vehicle = Vehicle::new(Frame::new(position, 0f64), length, width);
+    let f = |_| boundary_disk(length, width, significant, position);
+    let disks: Vec<Disk> = (0..n).map(f).collect();
+    Box::new(Scenario::new(vehicle, disks))
+}
+
+// Returns a scenario with the given configuration of obstacles placed on the
+// boundary of the feeler volume. Returns none if it is not possible to create
+// the given scenario.
+pub fn boundary_scenario_with_obstacles( obstacles:     &Obstacles
+                                       , feeler_length: f64
+                                       , feeler_width:  f64 )
+    -> Option<Box<HasScenario>>
+{
+    let (length, width) = (feeler_length, feeler_width);
+    match obstacles.details() {
+        (num_obs, 0u32) =>
+            Some(boundary_scenario(num_obs, length, width, false)),
+        (0u32, num_obs) =>
+            Some(boundary_scenario(num_obs, length, width, true)),
+        _ => None
+    }
+}
+
 // Returns a scenario with the given configuration of obstacles. Returns none
 // if it is not possible to create the given scenario.
 pub fn scenario_with_obstacles( obstacles:     &Obstacles
@@ -92,3 +243,67 @@ pub fn scenario_with_obstacles( obstacles:     &Obstacles
         _ => None
     }
 }
+
+// Returns the scenario described by the given record, or none if the record
+// is incomplete.
+pub fn scenario_from_record(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_with_origin(record, Origin::Absolute)
+}
+
+// As `scenario_from_record`, but the vehicle's frame transforms about the
+// given origin.
+pub fn scenario_from_record_with_origin(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    scenario_from_record_as::<f64>(record, origin)
+}
+
+// As `scenario_from_record_with_origin`, but the scenario is evaluated in the
+// scalar type `T`.
+pub fn scenario_from_record_as<T: Scalar>(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    Some(Box::new(build::<T>(record, origin)?))
+}
+
+// As `scenario_from_record_with_origin`, but the scenario evaluates its disks
+// in batches.
+pub fn scenario_from_record_batched(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    let scenario = build::<f64>(record, origin)?;
+    let batch = DiskBatch::new(&scenario.disks);
+    Some(Box::new(BatchScenario { scenario: scenario, batch: batch }))
+}
+
+// Returns the scenario described by the given record, evaluated in the scalar
+// type `T`, or none if the record is incomplete.
+fn build<T: Scalar>(record: &Record, origin: Origin) -> Option<Scenario<T>> {
+    let v = &record.vehicle;
+    let frame = Frame::with_origin( v.vec_as("x", "y")?
+                                  , v.get_as("orientation")?
+                                  , origin );
+    let vehicle = Vehicle::new(frame, v.get_as("length")?, v.get_as("width")?);
+
+    let mut disks = vec!();
+    for obstacle in record.obstacles.iter() {
+        let centre = obstacle.vec_as("x", "y")?;
+        disks.push(Disk::<T>::new(centre, obstacle.get_as("radius")?));
+    }
+    Some(Scenario::new(vehicle, disks))
+}
+
+// Returns the scenario of the given shared world, for a vehicle whose feeler
+// has the given dimensions. Obstacles other than disks are ignored.
+pub fn scenario_from_world( world:         &World
+                          , feeler_length: f64
+                          , feeler_width:  f64 ) -> Box<HasScenario>
+{
+    let frame = Frame::new(world.position, world.orientation());
+    let vehicle = Vehicle::new(frame, feeler_length, feeler_width);
+    let disks = world.obstacles.iter().filter_map(|x| match *x {
+        Geometry::Disk { centre, radius } => Some(Disk::new(centre, radius)),
+        _ => None
+    }).collect();
+    Box::new(Scenario::new(vehicle, disks))
+}
@@ -1,8 +1,40 @@
 #![cfg(test)]
 
+use super::common::types::Frame;
+use super::linalg::fixed::ArrayVec;
+use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "std")]
+use super::scenarios::boundary_scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record, scenario_from_world};
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_origin;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_as;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_batched;
+#[cfg(feature = "std")]
 use super::scenarios::scenario_with_obstacles;
-use super::utilities::types::{HasScenario, Obstacles};
-use super::utilities::test_utilities::test_scenarios;
+use super::types::{Disk, Vehicle};
+#[cfg(feature = "std")]
+use super::utilities::golden::check_corpus;
+#[cfg(all(feature = "std", feature = "deterministic"))]
+use super::utilities::golden::digest_corpus;
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+#[cfg(feature = "std")]
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+#[cfg(feature = "std")]
+use super::utilities::world::Kind;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::{test_batches, test_drift};
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_precisions;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_worlds;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 // Length of the feeler.
 const FEELER_LENGTH: f64 = 10f64;
@@ -10,6 +42,18 @@ const FEELER_LENGTH: f64 = 10f64;
 // Width of the feeler.
 const FEELER_WIDTH: f64 = 2f64;
 
+// Rebuilds scenarios from their records in single precision.
+#[cfg(feature = "std")]
+fn single(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<f32>(record, Origin::Absolute)
+}
+
+// Distance along each axis by which scenarios are translated when testing
+// drift, far enough from the origin that absolute evaluation drifts.
+#[cfg(feature = "std")]
+const OFFSET: f64 = 1e12;
+
+#[cfg(feature = "std")]
 #[test]
 fn test() {
     let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
@@ -18,8 +62,118 @@ fn test() {
 
     for i in 1..6 {
         let obstacles1 = Obstacles::new(i, 0u32);
-        assert!(test_scenarios(&obstacles1, |x| creator(x)));
+        test_precisions(&obstacles1, &creator, &single).unwrap();
         let obstacles2 = Obstacles::new(0u32, i);
-        assert!(test_scenarios(&obstacles2, |x| creator(x)));
+        test_precisions(&obstacles2, &creator, &single).unwrap();
     }
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn boundary() {
+    let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
+        boundary_scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+    };
+
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_precisions(&obstacles1, &creator, &single).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, &creator, &single).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn golden() {
+    check_corpus(include_str!("golden.json"), scenario_from_record).unwrap();
+}
+
+// Digest of the golden corpus as evaluated in deterministic mode, which every
+// platform must reproduce.
+#[cfg(all(feature = "std", feature = "deterministic"))]
+const DIGEST: u64 = 0x7194232027230892;
+
+#[cfg(all(feature = "std", feature = "deterministic"))]
+#[test]
+fn deterministic() {
+    let corpus = include_str!("golden.json");
+    let digest = digest_corpus(corpus, scenario_from_record);
+    assert!(digest == DIGEST, "corpus digest {:#018x}", digest);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn world() {
+    test_worlds(Kind::Disks, |w| {
+        scenario_from_world(w, FEELER_LENGTH, FEELER_WIDTH)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drift() {
+    let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
+        scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+    };
+    test_drift(OFFSET, creator, |r| {
+        scenario_from_record_with_origin(r, Origin::Relative)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn batch() {
+    let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
+        scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+    };
+    let boundary = |o: &Obstacles| -> Option<Box<HasScenario>> {
+        boundary_scenario_with_obstacles(o, FEELER_LENGTH, FEELER_WIDTH)
+    };
+    for &origin in [Origin::Absolute, Origin::Relative].iter() {
+        let rebuild = |r: &Record| scenario_from_record_with_origin(r, origin);
+        let batched = |r: &Record| scenario_from_record_batched(r, origin);
+        test_batches(&creator, rebuild, batched).unwrap();
+        test_batches(&boundary, rebuild, batched).unwrap();
+    }
+}
+
+// Disks held in a fixed-capacity collection produce the same force as disks
+// in a vector, and the collection refuses disks beyond its capacity.
+#[test]
+fn fixed_capacity() {
+    let frame = Frame::new(Vec2D::zero(), 0f64);
+    let vehicle = Vehicle::new(frame, FEELER_LENGTH, FEELER_WIDTH);
+    let centres = [ (3f64, 1f64), (6f64, -1.5f64)
+                  , (8f64, 0.5f64), (20f64, 0f64) ];
+    let disk = |&(x, y): &(f64, f64)| Disk::new(Vec2D::new(x, y), 1f64);
+    let disks: Vec<Disk> = centres.iter().map(&disk).collect();
+    let mut fixed: ArrayVec<Disk, 4> = ArrayVec::new();
+    for centre in centres.iter() { fixed.push(disk(centre)); }
+    assert!(fixed.try_push(disk(&(0f64, 0f64))).is_err());
+    let force = vehicle.disk_avoidance(&fixed);
+    assert!(force.is_some());
+    assert_eq!(force, vehicle.disk_avoidance(&disks));
+}
+
+// Disks serialise their centre and radius, and vehicles their frame's
+// parameters and feeler, from which deserialised vehicles rebuild the same
+// transforms.
+#[cfg(feature = "serde")]
+#[test]
+fn serialisation() {
+    let disk = Disk::new(Vec2D::new(1f64, 2f64), 0.5f64);
+    let json = ::serde_json::to_string(&disk).unwrap();
+    assert_eq!(json, r#"{"centre":{"x":1.0,"y":2.0},"radius":0.5}"#);
+    let rebuilt: Disk = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.to_circle(), disk.to_circle());
+
+    let frame = Frame::new(Vec2D::new(3f64, -4f64), 0.5f64);
+    let vehicle = Vehicle::new(frame, FEELER_LENGTH, FEELER_WIDTH);
+    let json = ::serde_json::to_string(&vehicle).unwrap();
+    assert!(json.ends_with(r#""length":10.0,"width":2.0}"#));
+    let rebuilt: Vehicle = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(::serde_json::to_string(&rebuilt).unwrap(), json);
+    assert_eq!(rebuilt.frame.to_world, vehicle.frame.to_world);
+    assert_eq!(rebuilt.frame.to_local, vehicle.frame.to_local);
+}
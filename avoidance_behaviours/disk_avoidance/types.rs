@@ -1,49 +1,104 @@
 use super::common::types::Frame;
+use super::linalg::batch::{self, Lanes, Vec2DBatch, BLOCK};
+use super::linalg::geometry::Circle;
+use super::linalg::scalar::Scalar;
+use super::linalg::origin::Origin;
 use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "serde")]
+use super::serde::Deserialize;
+#[cfg(feature = "std")]
+use super::utilities::explain::Trace;
+#[cfg(feature = "std")]
+use super::utilities::invariants::Evaluation;
+#[cfg(feature = "std")]
+use super::utilities::oracle::{Geometry, SweptBody};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use std::cmp;
 
 // Weighting factor for obstacle avoidance steering force.
 const BRAKING_WEIGHT: f64 = 2f64;
 
 // Defines a disk.
-pub struct Disk { pub centre: Vec2D
-                , pub radius: f64 }
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Disk<T = f64> { pub centre: Vec2D<T>
+                         , pub radius: T }
 
-impl Disk {
+impl<T: Scalar> Disk<T> {
     // Creates a disk from the given centre and radius.
-    pub fn new(centre: Vec2D, radius: f64) -> Disk {
+    pub fn new(centre: Vec2D<T>, radius: T) -> Disk<T> {
         Disk { centre: centre, radius: radius }
     }
+
+    // Creates a disk covering the given circle.
+    pub fn from_circle(circle: &Circle<T>) -> Disk<T> {
+        Disk::new(circle.centre, circle.radius)
+    }
+
+    // Returns the circle the disk covers.
+    pub fn to_circle(&self) -> Circle<T> {
+        Circle::new(self.centre, self.radius)
+    }
+
+    // Returns the centre and radius of the disk.
+    #[cfg(feature = "std")]
+    pub fn geometry(&self) -> Geometry {
+        Geometry::Disk { centre: self.centre.cast()
+                       , radius: self.radius.to_f64() }
+    }
+}
+
+// Disks held as a batch of centres and their radii, so that their
+// interactions with a feeler are evaluated several at a time.
+pub struct DiskBatch<T = f64> { pub centres: Vec2DBatch<T>
+                              , pub radii:   Vec<T> }
+
+impl<T: Lanes> DiskBatch<T> {
+    // Creates a batch of the given disks.
+    pub fn new(disks: &[Disk<T>]) -> DiskBatch<T> {
+        let mut centres = Vec2DBatch::new();
+        for disk in disks.iter() { centres.push(disk.centre); }
+        DiskBatch { centres: centres
+                  , radii:   disks.iter().map(|x| x.radius).collect() }
+    }
 }
 
 // Result of interaction between feeler and disk.
-pub enum FeelerResult {
+pub enum FeelerResult<T = f64> {
     Case1,
-    Case2(Interaction),
-    Case3(Interaction)
+    Case2(Interaction<T>),
+    Case3(Interaction<T>)
 }
 
 // Pairs a disk with a distance. Used to capture interactions between vehicle
 // feeler and disks.
 #[derive(Copy, Clone)]
-pub struct Interaction { dist:   f64
-                       , centre: Vec2D
-                       , radius: f64 }
+pub struct Interaction<T = f64> { dist:   T
+                                , centre: Vec2D<T>
+                                , radius: T }
 
-impl Interaction {
+impl<T: Scalar> Interaction<T> {
     // Creates an interaction containing the provided values.
-    fn new(distance: f64, centre: Vec2D, radius: f64) -> Interaction {
+    fn new(distance: T, centre: Vec2D<T>, radius: T) -> Interaction<T> {
         Interaction { dist: distance, centre: centre, radius: radius }
     }
 }
 
-// Defines a vehicle with a single feeler volume.
-pub struct Vehicle { pub frame:  Frame
-                   ,     length: f64
-                   ,     width:  f64 }
+// Defines a vehicle with a single feeler volume. Deserialised vehicles
+// rebuild the transforms of their frames.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr( feature = "serde"
+          , serde(bound(deserialize = "T: Scalar + Deserialize<'de>")) )]
+pub struct Vehicle<T = f64> { pub frame:  Frame<T>
+                            ,     length: T
+                            ,     width:  T }
 
-impl Vehicle {
+impl<T: Scalar> Vehicle<T> {
     // Creates a new vehicle using the given values.
-    pub fn new(frame: Frame, length: f64, width: f64) -> Vehicle {
+    pub fn new(frame: Frame<T>, length: T, width: T) -> Vehicle<T> {
         Vehicle { frame: frame, length: length, width: width }
     }
 
@@ -52,13 +107,41 @@ impl Vehicle {
         self.frame.update_matrices();
     }
 
+    // Returns a record of the vehicle's parameters.
+    #[cfg(feature = "std")]
+    pub fn record(&self) -> Record {
+        Record::new(&[ ("x", self.frame.position.x.to_f64())
+                     , ("y", self.frame.position.y.to_f64())
+                     , ("orientation", self.frame.orientation.to_f64())
+                     , ("length", self.length.to_f64())
+                     , ("width", self.width.to_f64()) ])
+    }
+
+    // Returns a body as wide as the feeler that travels its length.
+    #[cfg(feature = "std")]
+    pub fn swept_body(&self) -> SweptBody {
+        let heading = Vec2D::polar(self.frame.orientation, self.length);
+        SweptBody { position: self.frame.position.cast()
+                  , velocity: heading.cast()
+                  , radius:   self.width.to_f64()
+                  , horizon:  1f64 }
+    }
+
     // Returns the interaction between the vehicle's feeler and the given
     // disk.
-    pub fn interaction(&self, disk: &Disk) -> Option<Interaction> {
-        let local_centre = self.frame.to_local.transform(disk.centre);
+    pub fn interaction(&self, disk: &Disk<T>) -> Option<Interaction<T>> {
+        let local_centre = self.frame.local_point(disk.centre);
+        self.local_interaction(local_centre, disk.radius)
+    }
+
+    // Returns the interaction between the vehicle's feeler and a disk with
+    // the given centre, in the vehicle's local space, and radius.
+    fn local_interaction(&self, local_centre: Vec2D<T>, radius: T)
+        -> Option<Interaction<T>>
+    {
         if local_centre.x > self.length { return None; }
 
-        let expanded_radius = disk.radius + self.width;
+        let expanded_radius = radius + self.width;
         if local_centre.y.abs() > expanded_radius { return None; }
 
         let r2 = expanded_radius * expanded_radius;
@@ -66,16 +149,16 @@ impl Vehicle {
         let sqrt_part = (r2 - y2).sqrt();
 
         let mut x = local_centre.x - sqrt_part;
-        if x < 0f64 { x = local_centre.x + sqrt_part; }
-        Some(Interaction::new(x, local_centre, disk.radius))
+        if x < T::ZERO { x = local_centre.x + sqrt_part; }
+        Some(Interaction::new(x, local_centre, radius))
     }
 
     // Returns a force intended to prevent collision between the vehicle and a
     // collection of disks.
-    pub fn disk_avoidance(&self, disks: &Vec<Disk>) -> Option<Vec2D> {
+    pub fn disk_avoidance(&self, disks: &[Disk<T>]) -> Option<Vec2D<T>> {
 
         // Collect interactions between vehicle's feeler and disks.
-        let mut nearest: Option<Interaction> = None;
+        let mut nearest: Option<Interaction<T>> = None;
         for disk in disks.iter() {
 
             // Check if interaction is closer than known nearest.
@@ -89,12 +172,114 @@ impl Vehicle {
             }
         }
         if nearest.is_none() { return None; }
-        let near: Interaction = nearest.unwrap();
+        Some(self.avoidance_force(nearest.unwrap()))
+    }
 
-        // Determine steering force.
-        let multiplier = 1f64 + (self.length - near.dist) / self.length;
-        let force_x = (near.radius - near.centre.x) * BRAKING_WEIGHT;
+    // Returns the steering force that avoids the nearest interaction.
+    fn avoidance_force(&self, near: Interaction<T>) -> Vec2D<T> {
+        let multiplier = T::ONE + (self.length - near.dist) / self.length;
+        let braking_weight = T::from_f64(BRAKING_WEIGHT);
+        let force_x = (near.radius - near.centre.x) * braking_weight;
         let force_y = (near.radius - near.centre.y) * multiplier;
-        Some(self.frame.to_world.transform(Vec2D::new(force_x, force_y)))
+        self.frame.to_world.transform(Vec2D::new(force_x, force_y))
+    }
+
+    // Returns a trace of the evaluation of `disk_avoidance` for the given
+    // disks.
+    #[cfg(feature = "std")]
+    pub fn explain_avoidance(&self, disks: &[Disk<T>]) -> Trace {
+        let mut trace = Trace::new();
+        let mut nearest: Option<(usize, Interaction<T>)> = None;
+        for (i, disk) in disks.iter().enumerate() {
+            let local_centre: Vec2D = self.frame.local_point(disk.centre)
+                                               .cast();
+            let expanded_radius = (disk.radius + self.width).to_f64();
+            trace.record( Some(i), "feeler"
+                        , &[ ("local_x", local_centre.x)
+                           , ("local_y", local_centre.y)
+                           , ("expanded_radius", expanded_radius) ] );
+            if let Some(int) = self.interaction(disk) {
+                trace.record( Some(i), "interaction"
+                            , &[("distance", int.dist.to_f64())] );
+                if nearest.map_or(true, |(_, x)| int.dist < x.dist) {
+                    nearest = Some((i, int));
+                }
+            }
+        }
+        let force = self.disk_avoidance(disks).map(|x| x.cast());
+        trace.conclude(nearest.map(|x| x.0), force);
+        trace
+    }
+
+    // Returns the quantities of the evaluation of `disk_avoidance` that are
+    // subject to invariants. For disks centred within the feeler's length
+    // ahead of the vehicle, the braking component of the force is at most
+    // `BRAKING_WEIGHT * (length + radius)` and the lateral component at most
+    // `2 * (2 * radius + width)`, where `radius` is that of the largest disk.
+    #[cfg(feature = "std")]
+    pub fn evaluate_avoidance(&self, disks: &[Disk<T>]) -> Evaluation {
+        let mut nearest: Option<(&Disk<T>, T)> = None;
+        let mut radius = T::ZERO;
+        for disk in disks.iter() {
+            if disk.radius > radius { radius = disk.radius; }
+            if let Some(int) = self.interaction(disk) {
+                if nearest.map_or(true, |(_, x)| int.dist < x) {
+                    nearest = Some((disk, int.dist));
+                }
+            }
+        }
+        let braking = BRAKING_WEIGHT * (self.length + radius).to_f64();
+        let lateral = 2f64 * (2f64 * radius.to_f64() + self.width.to_f64());
+        let away = nearest.map(|(x, _)| {
+            (self.frame.position - x.centre).cast()
+        });
+        Evaluation { force:  self.disk_avoidance(disks).map(|x| x.cast())
+                   , away:   away
+                   , bound:  (braking * braking + lateral * lateral).sqrt()
+                   , budget: None }
+    }
+}
+
+impl<T: Lanes> Vehicle<T> {
+    // Calls `found` with the index of each disk of the batch with which the
+    // vehicle's feeler interacts, and the interaction, in order. Disks are
+    // transformed into the vehicle's local space `BLOCK` at a time.
+    pub fn batch_interactions<F>(&self, disks: &DiskBatch<T>, mut found: F)
+        where F: FnMut(usize, Interaction<T>)
+    {
+        let centres = &disks.centres;
+        let mut relative = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let mut local = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        for start in (0..centres.len()).step_by(BLOCK) {
+            let end = cmp::min(start + BLOCK, centres.len());
+            let n = end - start;
+            let mut xs = &centres.xs[start..end];
+            let mut ys = &centres.ys[start..end];
+            if self.frame.origin == Origin::Relative {
+                batch::sub( xs, ys, self.frame.position
+                          , &mut relative.0[..n], &mut relative.1[..n] );
+                xs = &relative.0[..n];
+                ys = &relative.1[..n];
+            }
+            batch::transform( &self.frame.to_local, xs, ys
+                            , &mut local.0[..n], &mut local.1[..n] );
+            for i in 0..n {
+                let local_centre = Vec2D::new(local.0[i], local.1[i]);
+                let radius = disks.radii[start + i];
+                let interaction = self.local_interaction(local_centre, radius);
+                if let Some(int) = interaction { found(start + i, int); }
+            }
+        }
+    }
+
+    // As `disk_avoidance`, but evaluates a batch of disks.
+    pub fn batch_avoidance(&self, disks: &DiskBatch<T>) -> Option<Vec2D<T>> {
+        let mut nearest: Option<Interaction<T>> = None;
+        self.batch_interactions(disks, |_, int| {
+            if nearest.map_or(true, |near| int.dist < near.dist) {
+                nearest = Some(int);
+            }
+        });
+        nearest.map(|near| self.avoidance_force(near))
     }
 }
@@ -0,0 +1,47 @@
+#![no_main]
+#[macro_use]
+extern crate arbitrary;
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate common;
+extern crate disk_avoidance;
+extern crate utilities;
+
+use common::types::Frame;
+use disk_avoidance::types::{Disk, Vehicle};
+use utilities::fuzz::{check_finite, coordinate, positive, Point};
+
+// Arbitrary vehicle with a feeler of positive length and width, and disks of
+// positive radius.
+#[derive(Arbitrary, Debug)]
+struct Input { position:    Point
+             , orientation: f64
+             , length:      f64
+             , width:       f64
+             , disks:       Vec<(Point, f64)> }
+
+fuzz_target!(|input: Input| {
+    let position = match input.position.vec() { Some(x) => x, None => return };
+    let orientation = match coordinate(input.orientation) {
+        Some(x) => x,
+        None => return
+    };
+    let feeler = (positive(input.length), positive(input.width));
+    let (length, width) = match feeler {
+        (Some(l), Some(w)) => (l, w),
+        _ => return
+    };
+
+    let mut disks = vec!();
+    for &(centre, radius) in input.disks.iter() {
+        match (centre.vec(), positive(radius)) {
+            (Some(c), Some(r)) => disks.push(Disk::new(c, r)),
+            _ => return
+        }
+    }
+
+    let frame = Frame::new(position, orientation);
+    let mut vehicle = Vehicle::new(frame, length, width);
+    vehicle.update();
+    check_finite(vehicle.disk_avoidance(&disks), &input);
+});
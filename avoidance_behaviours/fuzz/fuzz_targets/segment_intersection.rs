@@ -0,0 +1,20 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate utilities;
+extern crate wall_avoidance;
+
+use utilities::fuzz::{check_finite, Point};
+use wall_avoidance::types::Segment;
+
+// Intersects two arbitrary segments with both orientation predicates.
+// Degenerate segments are rejected by `Segment::new`, so are skipped.
+fuzz_target!(|input: (Point, Point, Point, Point)| {
+    let points = (input.0.vec(), input.1.vec(), input.2.vec(), input.3.vec());
+    if let (Some(p1), Some(p2), Some(p3), Some(p4)) = points {
+        if let (Ok(a), Ok(b)) = (Segment::new(p1, p2), Segment::new(p3, p4)) {
+            check_finite(a.segment_intersection(&b), &input);
+            check_finite(a.exact_segment_intersection(&b), &input);
+        }
+    }
+});
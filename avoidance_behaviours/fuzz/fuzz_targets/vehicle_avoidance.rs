@@ -0,0 +1,40 @@
+#![no_main]
+#[macro_use]
+extern crate arbitrary;
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate utilities;
+extern crate vehicle_avoidance;
+
+use utilities::fuzz::{check_finite, positive, Point};
+use vehicle_avoidance::types::Vehicle;
+
+// Arbitrary vehicles of positive radius, the first of which avoids the
+// others with a positive maximum acceleration.
+#[derive(Arbitrary, Debug)]
+struct Input { vehicle:          (Point, Point)
+             , radius:           f64
+             , max_acceleration: f64
+             , others:           Vec<(Point, Point)> }
+
+fuzz_target!(|input: Input| {
+    let radius = match positive(input.radius) { Some(x) => x, None => return };
+    let max_acc = match positive(input.max_acceleration) {
+        Some(x) => x,
+        None => return
+    };
+
+    let create = |&(position, velocity): &(Point, Point)| {
+        match (position.vec(), velocity.vec()) {
+            (Some(p), Some(v)) => Some(Vehicle::new(p, v, radius, max_acc)),
+            _ => None
+        }
+    };
+    let vehicle = match create(&input.vehicle) { Some(x) => x, None => return };
+    let mut others = vec!();
+    for other in input.others.iter() {
+        match create(other) { Some(x) => others.push(x), None => return }
+    }
+
+    check_finite(vehicle.vehicle_avoidance(&others), &input);
+});
@@ -1,7 +1,22 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Without `std`, `core` stands in for it.
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+
 extern crate common;
 extern crate linalg;
+#[cfg(feature = "std")]
 extern crate utilities;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
+#[cfg(feature = "std")]
 pub mod scenarios;
 mod tests;
 pub mod types;
@@ -2,25 +2,54 @@ extern crate vehicle_avoidance;
 use vehicle_avoidance::scenarios::*;
 
 extern crate utilities;
-use utilities::bench_utilities::time_batch;
+use utilities::bench_utilities::{run, run_batched, run_fixed, run_single};
+use utilities::bench_utilities::run_worlds;
+use utilities::bench_utilities::BATCH_VARIANT;
+use utilities::results::DEFAULT_VARIANT;
 use utilities::types::{HasScenario, Obstacles};
-use utilities::utilities::{time_execution_seconds, write_results};
+use utilities::precision::{FIXED_VARIANT, Q16, SINGLE_VARIANT};
+use utilities::utilities::get_variant;
+use utilities::world::{Kind, WORLD_VARIANT};
 
-// Starts benchmarks and writes results to file.
-fn main() {
-    let run = || {
-        let creator = |o: &Obstacles| -> Box<HasScenario> {
-            scenario_with_obstacles(o).unwrap()
-        };
+// Label of the benchmarked algorithm in results.
+const ALGORITHM: &'static str = "avoidance_behaviours/vehicle_avoidance";
 
-        let mut results = vec!();
-        for i in 1..6 {
-            let interaction1 = Obstacles::new(i, 0u32);
-            results.push(time_batch(&interaction1, |x| creator(x)));
-            let interaction2 = Obstacles::new(0u32, i);
-            results.push(time_batch(&interaction2, |x| creator(x)));
-        }
-        write_results(&results);
+// Starts benchmarks and writes results to file, or explains a scenario. The
+// `world` variant draws scenarios from shared worlds, the `f32` variant
+// evaluates in single precision, the `q16.16` variant evaluates in fixed
+// point and the `batch` variant evaluates other vehicles in batches.
+fn main() {
+    let single = scenario_from_record_as::<f32>;
+    let fixed = scenario_from_record_as::<Q16>;
+    let creator = |o: &Obstacles| -> Box<HasScenario> {
+        scenario_with_obstacles(o).unwrap()
     };
-    println!("Total time: {} seconds", time_execution_seconds(run));
+    match get_variant() {
+        Some(ref name) if name == WORLD_VARIANT => {
+            run_worlds( ALGORITHM, Kind::Vehicles, scenario_from_world
+                      , scenario_from_record, single, fixed );
+            return;
+        },
+        Some(ref name) if name == SINGLE_VARIANT => {
+            run_single(ALGORITHM, creator, single);
+            return;
+        },
+        Some(ref name) if name == FIXED_VARIANT => {
+            run_fixed(ALGORITHM, creator, fixed);
+            return;
+        },
+        Some(ref name) if name == BATCH_VARIANT => {
+            let batched = scenario_from_record_batched;
+            run_batched(ALGORITHM, creator, batched, single, fixed);
+            return;
+        },
+        Some(_) => {
+            println!("Variants are world, f32, q16.16 or batch.");
+            return;
+        },
+        None => {}
+    }
+
+    run( ALGORITHM, DEFAULT_VARIANT, creator, scenario_from_record, single
+       , fixed );
 }
@@ -1,8 +1,18 @@
 use types::*;
 
+use super::linalg::batch::Lanes;
+use super::linalg::scalar::Scalar;
 use super::linalg::vector2d::Vec2D;
+use super::utilities::explain::Trace;
+use super::utilities::invariants::Evaluation;
+use super::utilities::oracle::{Geometry, Risk};
+use super::utilities::record::Record;
+use super::utilities::rng_utilities::{random_axis, random_grid_point};
+use super::utilities::rng_utilities::{random_index, random_integer};
 use super::utilities::rng_utilities::{random_tau, random_unity};
+use super::utilities::rng_utilities::BOUNDARY_MARGIN;
 use super::utilities::types::{HasScenario, Obstacles};
+use super::utilities::world::World;
 
 use std::f64::consts::PI;
 
@@ -12,11 +22,14 @@ const VEHICLE_RADIUS: f64 = 5f64;
 // Radius of virtual circle used to position scenario.
 const MAX_ACCELERATION: f64 = 25f64;
 
+// Time over which vehicles are checked for collisions.
+const HORIZON: f64 = 10f64;
+
 // Arrangement of vehicles.
-pub struct Scenario { pub vehicle: Vehicle
-                    , pub other_vehicles: Vec<Vehicle> }
+pub struct Scenario<T = f64> { pub vehicle: Vehicle<T>
+                             , pub other_vehicles: Vec<Vehicle<T>> }
 
-impl HasScenario for Scenario {
+impl<T: Scalar> HasScenario for Scenario<T> {
     // Returns the interactions between the vehicle and obstacles in the
     // scenario.
     fn interactions(&self) -> u32 {
@@ -31,12 +44,91 @@ impl HasScenario for Scenario {
     // the steering scenario.
     fn avoidance(&mut self) -> Option<Vec2D> {
         self.vehicle.vehicle_avoidance(&self.other_vehicles)
+                    .map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.vehicle.explain_avoidance(&self.other_vehicles)
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.vehicle.evaluate_avoidance(&self.other_vehicles)
+    }
+
+    // Returns the parameters defining the vehicles.
+    fn record(&self) -> Record {
+        let mut record = self.vehicle.record();
+        for vehicle in self.other_vehicles.iter() {
+            record.obstacle(&vehicle.geometry().parameters());
+        }
+        record
+    }
+
+    // Returns whether the vehicle interacts with each other vehicle, and
+    // whether the vehicles collide within the horizon.
+    fn risks(&self) -> Vec<Risk> {
+        let body = self.vehicle.swept_body(HORIZON);
+        self.other_vehicles.iter().map(|vehicle| {
+            let predicted = self.vehicle.interaction(vehicle).is_some();
+            Risk::new(predicted, &body, &vehicle.geometry())
+        }).collect()
+    }
+}
+
+// Scenario whose other vehicles are also held as a batch, with which the
+// interactions and avoidance force are evaluated. Other evaluations use the
+// vehicles one at a time.
+pub struct BatchScenario<T = f64> { pub scenario: Scenario<T>
+                                  , pub batch:    VehicleBatch<T> }
+
+impl<T: Lanes> HasScenario for BatchScenario<T> {
+    // Returns the interactions between the vehicle and obstacles in the
+    // scenario.
+    fn interactions(&self) -> u32 {
+        let mut count = 0;
+        self.scenario.vehicle.batch_interactions(&self.batch, |_, _| {
+            count += 1;
+        });
+        count
+    }
+
+    // Returns the avoidance force to be applied to the vehicle according to
+    // the steering scenario.
+    fn avoidance(&mut self) -> Option<Vec2D> {
+        self.scenario.vehicle.batch_avoidance(&self.batch).map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.scenario.explain()
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.scenario.evaluation()
+    }
+
+    // Returns the parameters defining the vehicles.
+    fn record(&self) -> Record {
+        self.scenario.record()
+    }
+
+    // Returns whether the vehicle interacts with each other vehicle, and
+    // whether the vehicles collide within the horizon.
+    fn risks(&self) -> Vec<Risk> {
+        self.scenario.risks()
     }
 }
 
-impl Scenario {
+impl<T: Scalar> Scenario<T> {
     // Creates a scenario involving multiple vehicles.
-    pub fn new(vehicle: Vehicle, other_vehicles: Vec<Vehicle>) -> Scenario {
+    pub fn new(vehicle: Vehicle<T>, other_vehicles: Vec<Vehicle<T>>)
+        -> Scenario<T>
+    {
         Scenario { vehicle: vehicle, other_vehicles: other_vehicles }
     }
 }
@@ -59,18 +151,18 @@ fn scenario(num_vehicles: u32, colliding: bool) -> Box<Scenario> {
     let mut other_vehicles = vec!();
     for _ in 0..num_vehicles {
         let time = 1f64 + 4f64 * random_unity();
-        let offset = focus_velocity.mul(time);
-        let intersection = focus_position.add(offset);
+        let offset = focus_velocity * time;
+        let intersection = focus_position + offset;
 
         let angle_offset = PI * (0.05f64 + 0.35f64 * random_unity());
         let other_angle = focus_angle + angle_offset;
         let other_speed = 10f64;
         let mut other_velocity = Vec2D::polar(other_angle, other_speed);
-        if random_unity() < 0.5 { other_velocity = other_velocity.neg(); }
+        if random_unity() < 0.5 { other_velocity = -other_velocity; }
 
-        let other_travel = other_velocity.neg().mul(time);
-        if !colliding { other_velocity = other_velocity.neg(); }
-        let other_position = intersection.add(other_travel);
+        let other_travel = -other_velocity * time;
+        if !colliding { other_velocity = -other_velocity; }
+        let other_position = intersection + other_travel;
 
         let other_vehicle = Vehicle::new( other_position
                                         , other_velocity
@@ -81,6 +173,76 @@ fn scenario(num_vehicles: u32, colliding: bool) -> Box<Scenario> {
     Box::new(Scenario::new(focus_vehicle, other_vehicles))
 }
 
+// Relative speed of vehicles approaching in boundary scenarios. A power of
+// two, so that times to collision are exact.
+const BOUNDARY_SPEED: f64 = 8f64;
+
+// Returns the position and velocity, relative to a focus vehicle, of a
+// vehicle on the boundary of its interaction check. Vehicles approaching to
+// pass at exactly twice the radius are significant. Vehicles passing just
+// further away, receding, or moving at the same velocity (including those
+// already overlapping) are not.
+fn boundary_offset(significant: bool) -> (Vec2D, Vec2D) {
+    let along = random_axis();
+    let across = if random_unity() < 0.5f64 {
+        along.perp()
+    } else {
+        -along.perp()
+    };
+
+    // Offset of 3-4-5 proportions whose minimum separation, as calculated by
+    // `interaction`, is twice its scale.
+    let passing = |scale: f64| {
+        along * (3f64 * scale) + across * (4f64 * scale)
+    };
+    let approaching = along * -BOUNDARY_SPEED;
+
+    match (significant, random_index(3)) {
+        (true, _) => (passing(VEHICLE_RADIUS), approaching),
+        (false, 0) => (passing(VEHICLE_RADIUS + BOUNDARY_MARGIN), approaching),
+        (false, 1) => (passing(VEHICLE_RADIUS), -approaching),
+        (false, _) => {
+            let distance = random_integer(0, 2 * VEHICLE_RADIUS as i32);
+            (along * distance, Vec2D::zero())
+        }
+    }
+}
+
+// Returns a scenario involving a vehicle at an integer position moving along
+// an axis, and vehicles on the boundary of its interaction check.
+fn boundary_scenario(num_vehicles: u32, colliding: bool) -> Box<Scenario> {
+    let focus_position = random_grid_point();
+    let focus_velocity = random_axis() * 10f64;
+    let focus_vehicle = Vehicle::new( focus_position
+                                    , focus_velocity
+                                    , VEHICLE_RADIUS
+                                    , MAX_ACCELERATION );
+
+    let mut other_vehicles = vec!();
+    for _ in 0..num_vehicles {
+        let (offset, relative_velocity) = boundary_offset(colliding);
+        let other_vehicle = Vehicle::new( focus_position + offset
+                                        , focus_velocity + relative_velocity
+                                        , VEHICLE_RADIUS
+                                        , MAX_ACCELERATION );
+        other_vehicles.push(other_vehicle);
+    }
+    Box::new(Scenario::new(focus_vehicle, other_vehicles))
+}
+
+// Returns a scenario with the given configuration of obstacles placed on the
+// boundary of the interaction check. Returns none if it is not possible to
+// create the given scenario.
+pub fn boundary_scenario_with_obstacles(obstacles: &Obstacles)
+    -> Option<Box<HasScenario>>
+{
+    match obstacles.details() {
+        (num_obs, 0u32) => Some(boundary_scenario(num_obs, false)),
+        (0u32, num_obs) => Some(boundary_scenario(num_obs, true)),
+        _ => None
+    }
+}
+
 // Returns a scenario with the given configuration of obstacles. Returns none
 // if it is not possible to create the given scenario.
 pub fn scenario_with_obstacles(obstacles: &Obstacles)
@@ -92,3 +254,62 @@ pub fn scenario_with_obstacles(obstacles: &Obstacles)
         _ => None
     }
 }
+
+// Returns the scenario described by the given record, or none if the record
+// is incomplete. Other vehicles share the vehicle's maximum acceleration.
+pub fn scenario_from_record(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<f64>(record)
+}
+
+// As `scenario_from_record`, but the scenario is evaluated in the scalar type
+// `T`.
+pub fn scenario_from_record_as<T: Scalar>(record: &Record)
+    -> Option<Box<HasScenario>>
+{
+    Some(Box::new(build::<T>(record)?))
+}
+
+// As `scenario_from_record`, but the scenario evaluates the other vehicles in
+// batches.
+pub fn scenario_from_record_batched(record: &Record)
+    -> Option<Box<HasScenario>>
+{
+    let scenario = build::<f64>(record)?;
+    let batch = VehicleBatch::new(&scenario.other_vehicles);
+    Some(Box::new(BatchScenario { scenario: scenario, batch: batch }))
+}
+
+// Returns the scenario described by the given record, evaluated in the scalar
+// type `T`, or none if the record is incomplete.
+fn build<T: Scalar>(record: &Record) -> Option<Scenario<T>> {
+    let v = &record.vehicle;
+    let max_acceleration = v.get_as("max_acceleration")?;
+    let vehicle = Vehicle::<T>::new( v.vec_as("x", "y")?
+                                   , v.vec_as("vx", "vy")?
+                                   , v.get_as("radius")?
+                                   , max_acceleration );
+
+    let mut other_vehicles = vec!();
+    for obstacle in record.obstacles.iter() {
+        other_vehicles.push(Vehicle::new( obstacle.vec_as("x", "y")?
+                                        , obstacle.vec_as("vx", "vy")?
+                                        , obstacle.get_as("radius")?
+                                        , max_acceleration ));
+    }
+    Some(Scenario::new(vehicle, other_vehicles))
+}
+
+// Returns the scenario of the given shared world. All vehicles share the
+// maximum acceleration. Obstacles other than vehicles are ignored.
+pub fn scenario_from_world(world: &World) -> Box<HasScenario> {
+    let vehicle = Vehicle::new( world.position
+                              , world.velocity
+                              , world.radius
+                              , MAX_ACCELERATION );
+    let other_vehicles = world.obstacles.iter().filter_map(|x| match *x {
+        Geometry::Vehicle { position, velocity, radius } =>
+            Some(Vehicle::new(position, velocity, radius, MAX_ACCELERATION)),
+        _ => None
+    }).collect();
+    Box::new(Scenario::new(vehicle, other_vehicles))
+}
@@ -1,15 +1,132 @@
 #![cfg(test)]
 
-use super::scenarios::scenario_with_obstacles;
-use super::utilities::test_utilities::test_scenarios;
+use super::linalg::fixed::ArrayVec;
+use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "std")]
+use super::scenarios::boundary_scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record, scenario_from_world};
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record_as, scenario_with_obstacles};
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_batched;
+use super::types::Vehicle;
+#[cfg(feature = "std")]
+use super::utilities::golden::check_corpus;
+#[cfg(all(feature = "std", feature = "deterministic"))]
+use super::utilities::golden::digest_corpus;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::{test_batches, test_drift};
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_precisions;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_worlds;
+#[cfg(feature = "std")]
 use super::utilities::types::Obstacles;
+#[cfg(feature = "std")]
+use super::utilities::world::Kind;
 
+// Distance along each axis by which scenarios are translated when testing
+// drift. Vehicles are compared by their relative positions, so do not drift.
+#[cfg(feature = "std")]
+const OFFSET: f64 = 1e12;
+
+#[cfg(feature = "std")]
 #[test]
 fn test() {
+    let single = scenario_from_record_as::<f32>;
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_precisions(&obstacles1, scenario_with_obstacles, single).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, scenario_with_obstacles, single).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn boundary() {
+    let creator = boundary_scenario_with_obstacles;
+    let single = scenario_from_record_as::<f32>;
     for i in 1..6 {
         let obstacles1 = Obstacles::new(i, 0u32);
-        assert!(test_scenarios(&obstacles1, scenario_with_obstacles));
+        test_precisions(&obstacles1, creator, single).unwrap();
         let obstacles2 = Obstacles::new(0u32, i);
-        assert!(test_scenarios(&obstacles2, scenario_with_obstacles));
+        test_precisions(&obstacles2, creator, single).unwrap();
     }
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn golden() {
+    check_corpus(include_str!("golden.json"), scenario_from_record).unwrap();
+}
+
+// Digest of the golden corpus as evaluated in deterministic mode, which every
+// platform must reproduce.
+#[cfg(all(feature = "std", feature = "deterministic"))]
+const DIGEST: u64 = 0x8ffcbf288bc053dd;
+
+#[cfg(all(feature = "std", feature = "deterministic"))]
+#[test]
+fn deterministic() {
+    let corpus = include_str!("golden.json");
+    let digest = digest_corpus(corpus, scenario_from_record);
+    assert!(digest == DIGEST, "corpus digest {:#018x}", digest);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn world() {
+    test_worlds(Kind::Vehicles, scenario_from_world).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drift() {
+    test_drift(OFFSET, scenario_with_obstacles, scenario_from_record).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn batch() {
+    let rebuild = scenario_from_record;
+    let batched = scenario_from_record_batched;
+    test_batches(scenario_with_obstacles, rebuild, batched).unwrap();
+    test_batches(boundary_scenario_with_obstacles, rebuild, batched).unwrap();
+}
+
+// Vehicles held in a fixed-capacity collection are avoided as they are in an
+// array.
+#[test]
+fn fixed_capacity() {
+    let velocity = Vec2D::new(1f64, 0f64);
+    let vehicle = Vehicle::new(Vec2D::zero(), velocity, 1f64, 2f64);
+    let others = [ (10f64, 0.5f64, -1f64), (6f64, -1f64, -2f64)
+                 , (-5f64, 0f64, -1f64) ];
+    let other = |&(x, y, vx): &(f64, f64, f64)| {
+        Vehicle::new(Vec2D::new(x, y), Vec2D::new(vx, 0f64), 1f64, 2f64)
+    };
+    let vehicles = [other(&others[0]), other(&others[1]), other(&others[2])];
+    let mut fixed: ArrayVec<Vehicle, 3> = ArrayVec::new();
+    for x in others.iter() { fixed.push(other(x)); }
+    assert!(fixed.try_push(other(&others[0])).is_err());
+    let force = vehicle.vehicle_avoidance(&fixed);
+    assert!(force.is_some());
+    assert_eq!(force, vehicle.vehicle_avoidance(&vehicles));
+}
+
+// Vehicles serialise their position, velocity, radius and maximum
+// acceleration.
+#[cfg(all(feature = "std", feature = "serde"))]
+#[test]
+fn serialisation() {
+    let vehicle = Vehicle::new( Vec2D::new(1f64, 2f64), Vec2D::new(-3f64, 0f64)
+                              , 0.5f64, 4f64 );
+    let json = ::serde_json::to_string(&vehicle).unwrap();
+    let expected = r#"{"position":{"x":1.0,"y":2.0},"velocity":{"x":-3.0,"#;
+    let rest = r#""y":0.0},"radius":0.5,"max_acceleration":4.0}"#;
+    assert_eq!(json, expected.to_string() + rest);
+    let rebuilt: Vehicle = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(::serde_json::to_string(&rebuilt).unwrap(), json);
+}
@@ -1,52 +1,128 @@
-use super::linalg::vector2d::{EPSILON, Vec2D};
+use super::linalg::batch::{self, Lanes, Vec2DBatch, BLOCK};
+use super::linalg::scalar::Scalar;
+use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "std")]
+use super::utilities::explain::Trace;
+#[cfg(feature = "std")]
+use super::utilities::invariants::Evaluation;
+#[cfg(feature = "std")]
+use super::utilities::oracle::{Geometry, SweptBody};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+
+use std::cmp;
 
 // Data describing approach between two vehicles.
 #[derive(Copy, Clone)]
-pub struct Interaction { vehicle_position:  Vec2D
-                       , relative_position: Vec2D
-                       , relative_velocity: Vec2D
-                       , time_to_collision: f64
-                       , min_separation:    f64
-                       , distance:          f64 }
+pub struct Interaction<T = f64> { vehicle_position:  Vec2D<T>
+                                , relative_position: Vec2D<T>
+                                , relative_velocity: Vec2D<T>
+                                , time_to_collision: T
+                                , min_separation:    T
+                                , distance:          T }
+
+// Vehicles held as a batch of positions and velocities, so that their
+// approach to a vehicle is evaluated several at a time.
+pub struct VehicleBatch<T = f64> { pub positions:  Vec2DBatch<T>
+                                 , pub velocities: Vec2DBatch<T> }
+
+impl<T: Lanes> VehicleBatch<T> {
+    // Creates a batch of the given vehicles.
+    pub fn new(vehicles: &[Vehicle<T>]) -> VehicleBatch<T> {
+        let mut batch = VehicleBatch { positions:  Vec2DBatch::new()
+                                     , velocities: Vec2DBatch::new() };
+        for vehicle in vehicles.iter() {
+            batch.positions.push(vehicle.position);
+            batch.velocities.push(vehicle.velocity);
+        }
+        batch
+    }
+}
 
 // Defines a vehicle capable of avoiding other vehicles.
-pub struct Vehicle { position:         Vec2D
-                   , velocity:         Vec2D
-                   , radius:           f64
-                   , max_acceleration: f64 }
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Vehicle<T = f64> { position:         Vec2D<T>
+                            , velocity:         Vec2D<T>
+                            , radius:           T
+                            , max_acceleration: T }
 
-impl Vehicle {
+impl<T: Scalar> Vehicle<T> {
     // Creates a vehicle from the given values.
-    pub fn new(pos: Vec2D, vel: Vec2D, radius: f64, max_acc: f64 ) -> Vehicle {
+    pub fn new(pos: Vec2D<T>, vel: Vec2D<T>, radius: T, max_acc: T)
+        -> Vehicle<T>
+    {
         Vehicle { position:         pos
                 , velocity:         vel
                 , radius:           radius
                 , max_acceleration: max_acc }
     }
 
+    // Returns the body of the vehicle and its velocity.
+    #[cfg(feature = "std")]
+    pub fn geometry(&self) -> Geometry {
+        Geometry::Vehicle { position: self.position.cast()
+                          , velocity: self.velocity.cast()
+                          , radius:   self.radius.to_f64() }
+    }
+
+    // Returns a record of the vehicle's parameters.
+    #[cfg(feature = "std")]
+    pub fn record(&self) -> Record {
+        Record::new(&[ ("x", self.position.x.to_f64())
+                     , ("y", self.position.y.to_f64())
+                     , ("vx", self.velocity.x.to_f64())
+                     , ("vy", self.velocity.y.to_f64())
+                     , ("radius", self.radius.to_f64())
+                     , ("max_acceleration", self.max_acceleration.to_f64()) ])
+    }
+
+    // Returns the body of the vehicle swept over the given time.
+    #[cfg(feature = "std")]
+    pub fn swept_body(&self, horizon: f64) -> SweptBody {
+        SweptBody { position: self.position.cast()
+                  , velocity: self.velocity.cast()
+                  , radius:   self.radius.to_f64()
+                  , horizon:  horizon }
+    }
+
     // Returns the interaction between this vehicle and the given vehicle.
-    pub fn interaction(&self, vehicle: &Vehicle) -> Option<Interaction> {
+    pub fn interaction(&self, vehicle: &Vehicle<T>) -> Option<Interaction<T>> {
         // Determine relative position.
-        let relative_position = self.position.sub(vehicle.position);
+        let relative_position = self.position - vehicle.position;
         let distance = relative_position.mag();
 
         // Determine time to collision, skipping this vehicle if
         // velocities are equal.
-        let relative_velocity = vehicle.velocity.sub(self.velocity);
+        let relative_velocity = vehicle.velocity - self.velocity;
         let relative_speed = relative_velocity.mag();
 
         let numerator = relative_position.dot(relative_velocity);
+        self.approach( vehicle.position, relative_position, relative_velocity
+                     , distance, relative_speed, numerator )
+    }
+
+    // Returns the interaction with a vehicle at the given position, given
+    // their relative position and velocity, their magnitudes and their dot
+    // product.
+    fn approach( &self
+               , vehicle_position:  Vec2D<T>
+               , relative_position: Vec2D<T>
+               , relative_velocity: Vec2D<T>
+               , distance:          T
+               , relative_speed:    T
+               , numerator:         T ) -> Option<Interaction<T>>
+    {
         let denominator = relative_speed * relative_speed;
-        if denominator < EPSILON { return None; }
+        if denominator < T::EPSILON { return None; }
         let time_to_collision = numerator / denominator;
 
         // Check if collision will occur.
-        if time_to_collision < EPSILON { return None; }
+        if time_to_collision < T::EPSILON { return None; }
         let min_separation = distance - relative_speed * time_to_collision;
-        if min_separation > 2f64 * self.radius { return None; }
+        if min_separation > self.radius + self.radius { return None; }
 
         // Return result.
-        let interaction = Interaction { vehicle_position:  vehicle.position
+        let interaction = Interaction { vehicle_position:  vehicle_position
                                       , relative_position: relative_position
                                       , relative_velocity: relative_velocity
                                       , time_to_collision: time_to_collision
@@ -57,10 +133,12 @@ impl Vehicle {
 
     // Returns a force intended to prevent collision between the vehicle and a
     // collection of other vehicles.
-    pub fn vehicle_avoidance(&self, vehicles: &Vec<Vehicle>) -> Option<Vec2D> {
+    pub fn vehicle_avoidance(&self, vehicles: &[Vehicle<T>])
+        -> Option<Vec2D<T>>
+    {
 
         // Collect interactions between this and other vehicles.
-        let mut soonest: Option<Interaction> = None;
+        let mut soonest: Option<Interaction<T>> = None;
         for vehicle in vehicles.iter() {
 
             // Check if interaction is sooner than known soonest.
@@ -78,20 +156,137 @@ impl Vehicle {
 
         // Determine collision avoidance from soonest interaction.
         if soonest.is_none() { return None; }
-        let interaction = soonest.unwrap();
+        Some(self.avoidance_force(soonest.unwrap()))
+    }
 
-        let colliding = interaction.distance < 2f64 * self.radius;
-        let exact = interaction.min_separation <= EPSILON;
+    // Returns the force that avoids the soonest interaction.
+    fn avoidance_force(&self, interaction: Interaction<T>) -> Vec2D<T> {
+        let colliding = interaction.distance < self.radius + self.radius;
+        let exact = interaction.min_separation <= T::EPSILON;
         let relative_position = if colliding || exact {
-            interaction.vehicle_position.sub(self.position)
+            interaction.vehicle_position - self.position
         } else {
             let rel_pos = interaction.relative_position;
             let rel_vel = interaction.relative_velocity;
-            rel_pos.add(rel_vel.mul(interaction.time_to_collision))
+            rel_pos + rel_vel * interaction.time_to_collision
         };
 
         // Determine avoidance force.
         let factor = self.max_acceleration / interaction.min_separation;
-        Some(relative_position.mul(factor))
+        relative_position * factor
+    }
+
+    // Returns a trace of the evaluation of `vehicle_avoidance` for the given
+    // vehicles.
+    #[cfg(feature = "std")]
+    pub fn explain_avoidance(&self, vehicles: &[Vehicle<T>]) -> Trace {
+        let mut trace = Trace::new();
+        let mut soonest: Option<(usize, Interaction<T>)> = None;
+        for (i, vehicle) in vehicles.iter().enumerate() {
+            let distance = (self.position - vehicle.position).mag().to_f64();
+            match self.interaction(vehicle) {
+                Some(int) => {
+                    let time = int.time_to_collision.to_f64();
+                    let values = [ ("distance", distance)
+                                 , ("time_to_collision", time)
+                                 , ( "min_separation"
+                                   , int.min_separation.to_f64() ) ];
+                    trace.record(Some(i), "interaction", &values);
+                    let sooner = soonest.map_or(true, |(_, x)| {
+                        int.time_to_collision < x.time_to_collision
+                    });
+                    if sooner { soonest = Some((i, int)); }
+                },
+                None => trace.record( Some(i), "no interaction"
+                                    , &[("distance", distance)] )
+            }
+        }
+        let force = self.vehicle_avoidance(vehicles).map(|x| x.cast());
+        trace.conclude(soonest.map(|x| x.0), force);
+        trace
+    }
+
+    // Returns the quantities of the evaluation of `vehicle_avoidance` that
+    // are subject to invariants. The relative position at closest approach is
+    // as long as the minimum separation, so the force is bounded by the
+    // maximum acceleration.
+    #[cfg(feature = "std")]
+    pub fn evaluate_avoidance(&self, vehicles: &[Vehicle<T>]) -> Evaluation {
+        let mut soonest: Option<(&Vehicle<T>, T)> = None;
+        for vehicle in vehicles.iter() {
+            if let Some(int) = self.interaction(vehicle) {
+                let time = int.time_to_collision;
+                if soonest.map_or(true, |(_, x)| time < x) {
+                    soonest = Some((vehicle, time));
+                }
+            }
+        }
+        let away = soonest.map(|(x, _)| (self.position - x.position).cast());
+        Evaluation { force:  self.vehicle_avoidance(vehicles).map(|x| x.cast())
+                   , away:   away
+                   , bound:  self.max_acceleration.to_f64()
+                   , budget: None }
+    }
+}
+
+impl<T: Lanes> Vehicle<T> {
+    // Calls `found` with the index of each vehicle of the batch with which
+    // this vehicle interacts, and the interaction, in order. Relative
+    // positions and velocities, their magnitudes and dot products are found
+    // `BLOCK` vehicles at a time.
+    pub fn batch_interactions<F>( &self
+                                , vehicles:  &VehicleBatch<T>
+                                , mut found: F )
+        where F: FnMut(usize, Interaction<T>)
+    {
+        let positions = &vehicles.positions;
+        let velocities = &vehicles.velocities;
+        let mut relative_positions = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let mut relative_velocities = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let mut distances = [T::ZERO; BLOCK];
+        let mut speeds = [T::ZERO; BLOCK];
+        let mut numerators = [T::ZERO; BLOCK];
+        for start in (0..positions.len()).step_by(BLOCK) {
+            let end = cmp::min(start + BLOCK, positions.len());
+            let n = end - start;
+            let (pxs, pys) = ( &mut relative_positions.0[..n]
+                             , &mut relative_positions.1[..n] );
+            let (vxs, vys) = ( &mut relative_velocities.0[..n]
+                             , &mut relative_velocities.1[..n] );
+            batch::sub_from( self.position
+                           , &positions.xs[start..end]
+                           , &positions.ys[start..end]
+                           , pxs, pys );
+            batch::mag(pxs, pys, &mut distances[..n]);
+            batch::sub( &velocities.xs[start..end]
+                      , &velocities.ys[start..end]
+                      , self.velocity
+                      , vxs, vys );
+            batch::mag(vxs, vys, &mut speeds[..n]);
+            batch::dot_lanes(pxs, pys, vxs, vys, &mut numerators[..n]);
+            for i in 0..n {
+                let interaction = self.approach( positions.get(start + i)
+                                               , Vec2D::new(pxs[i], pys[i])
+                                               , Vec2D::new(vxs[i], vys[i])
+                                               , distances[i]
+                                               , speeds[i]
+                                               , numerators[i] );
+                if let Some(int) = interaction { found(start + i, int); }
+            }
+        }
+    }
+
+    // As `vehicle_avoidance`, but evaluates a batch of vehicles.
+    pub fn batch_avoidance(&self, vehicles: &VehicleBatch<T>)
+        -> Option<Vec2D<T>>
+    {
+        let mut soonest: Option<Interaction<T>> = None;
+        self.batch_interactions(vehicles, |_, int| {
+            let sooner = soonest.map_or(true, |soon| {
+                int.time_to_collision < soon.time_to_collision
+            });
+            if sooner { soonest = Some(int); }
+        });
+        soonest.map(|int| self.avoidance_force(int))
     }
 }
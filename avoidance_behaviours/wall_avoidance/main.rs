@@ -1,26 +1,84 @@
 extern crate wall_avoidance;
+use wall_avoidance::predicates::Predicate;
 use wall_avoidance::scenarios::*;
 
 extern crate utilities;
-use utilities::bench_utilities::time_batch;
-use utilities::types::{HasScenario, Obstacles};
-use utilities::utilities::{time_execution_seconds, write_results};
+use utilities::bench_utilities::{run, run_fixed, run_single, run_worlds};
+use utilities::record::Record;
+use utilities::results::DEFAULT_VARIANT;
+use utilities::types::{HasScenario, Obstacles, Origin};
+use utilities::precision::{FIXED_VARIANT, Q16, SINGLE_VARIANT};
+use utilities::utilities::get_variant;
+use utilities::world::{Kind, World, WORLD_VARIANT};
 
-// Starts benchmarks and writes results to file.
+// Label of the benchmarked algorithm in results.
+const ALGORITHM: &'static str = "avoidance_behaviours/wall_avoidance";
+
+// Starts benchmarks and writes results to file, or explains a scenario. The
+// `float` (default) and `exact` variants select the orientation predicate,
+// the `relative` variant transforms about the vehicle's position, the
+// `world` variant draws scenarios from shared worlds, the `f32` variant
+// evaluates in single precision and the `q16.16` variant evaluates in fixed
+// point.
 fn main() {
-    let run = || {
+    let variant = get_variant();
+    let single = |r: &Record| {
+        scenario_from_record_as::<f32>(r, Origin::Absolute)
+    };
+    let fixed = |r: &Record| {
+        scenario_from_record_as::<Q16>(r, Origin::Absolute)
+    };
+    if variant.as_ref().map_or(false, |x| x == WORLD_VARIANT) {
+        let from_world = |w: &World| -> Box<HasScenario> {
+            scenario_from_world(w, false, Predicate::Float)
+        };
+        run_worlds( ALGORITHM, Kind::Segments, from_world
+                  , scenario_from_record, single, fixed );
+        return;
+    }
+    if variant.as_ref().map_or(false, |x| x == SINGLE_VARIANT) {
+        let creator = |o: &Obstacles| -> Box<HasScenario> {
+            scenario_with_obstacles(o, false, Predicate::Float).unwrap()
+        };
+        run_single(ALGORITHM, creator, single);
+        return;
+    }
+    if variant.as_ref().map_or(false, |x| x == FIXED_VARIANT) {
         let creator = |o: &Obstacles| -> Box<HasScenario> {
-            scenario_with_obstacles(o, false).unwrap()
+            scenario_with_obstacles(o, false, Predicate::Float).unwrap()
         };
+        run_fixed(ALGORITHM, creator, fixed);
+        return;
+    }
 
-        let mut results = vec!();
-        for i in 1..6 {
-            let obstacles1 = Obstacles::new(i, 0u32);
-            results.push(time_batch(&obstacles1, |x| creator(x)));
-            let obstacles2 = Obstacles::new(0u32, i);
-            results.push(time_batch(&obstacles2, |x| creator(x)));
+    let (origin, predicate) = match variant {
+        Some(ref name) if name == Origin::Relative.name() =>
+            (Origin::Relative, Predicate::Float),
+        Some(ref name) => match Predicate::from_name(name) {
+            Some(predicate) => (Origin::Absolute, predicate),
+            None => {
+                println!("Variants are float, exact, relative, world, f32 or \
+                          q16.16.");
+                return;
+            }
+        },
+        None => (Origin::Absolute, Predicate::Float)
+    };
+    let label = match variant {
+        Some(_) if origin == Origin::Relative => origin.name(),
+        Some(_) => predicate.name(),
+        None => DEFAULT_VARIANT
+    };
+
+    let rebuild = |r: &Record| {
+        scenario_from_record_with_origin(r, origin)
+    };
+    let creator = |o: &Obstacles| -> Box<HasScenario> {
+        let scenario = scenario_with_obstacles(o, false, predicate).unwrap();
+        match origin {
+            Origin::Absolute => scenario,
+            Origin::Relative => rebuild(&scenario.record()).unwrap()
         }
-        write_results(&results);
     };
-    println!("Total time: {} seconds", time_execution_seconds(run));
+    run(ALGORITHM, label, creator, rebuild, single, fixed);
 }
@@ -0,0 +1,181 @@
+use super::linalg::vector2d::Vec2D;
+
+// Half the distance between 1 and the next representable float.
+const EPSILON: f64 = 1.1102230246251565e-16;
+
+// Relative error bound of the floating-point orientation, beyond which its
+// sign is certain (Shewchuk's `ccwerrboundA`).
+const ERROR_BOUND: f64 = (3f64 + 16f64 * EPSILON) * EPSILON;
+
+// Used to split a float into two non-overlapping halves of 26 bits.
+const SPLITTER: f64 = 134_217_729f64;
+
+// Most components needed to represent an orientation exactly.
+const MAX_COMPONENTS: usize = 16;
+
+// Ways in which segment intersections decide orientation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Predicate { Float
+                   , Exact }
+
+impl Predicate {
+    // Returns the predicate with the given name, if any.
+    pub fn from_name(name: &str) -> Option<Predicate> {
+        match name {
+            "float" => Some(Predicate::Float),
+            "exact" => Some(Predicate::Exact),
+            _ => None
+        }
+    }
+
+    // Returns the name of the predicate.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Predicate::Float => "float",
+            Predicate::Exact => "exact"
+        }
+    }
+}
+
+// Returns the sum of two floats and the rounding error of that sum.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+// As `two_sum`, but requires that `a` is no smaller in magnitude than `b`.
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+// Returns the high and low halves of a float.
+fn split(a: f64) -> (f64, f64) {
+    let c = SPLITTER * a;
+    let high = c - (c - a);
+    (high, a - high)
+}
+
+// Returns the product of two floats and the rounding error of that product.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let (a_high, a_low) = split(a);
+    let (b_high, b_low) = split(b);
+    let error = ((x - a_high * b_high) - a_low * b_high) - a_high * b_low;
+    (x, a_low * b_low - error)
+}
+
+// Exact sum of non-overlapping floats, stored in order of increasing
+// magnitude. The last component has the sign of the sum.
+#[derive(Copy, Clone)]
+struct Expansion { components: [f64; MAX_COMPONENTS]
+                 , len:        usize }
+
+impl Expansion {
+    // Returns an expansion without components, representing zero.
+    fn zero() -> Expansion {
+        Expansion { components: [0f64; MAX_COMPONENTS], len: 0 }
+    }
+
+    // Returns the exact difference of two floats.
+    fn difference(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_sum(a, -b);
+        let mut result = Expansion::zero();
+        result.push(y);
+        result.push(x);
+        result
+    }
+
+    // Appends a component, unless it is zero.
+    fn push(&mut self, component: f64) {
+        if component != 0f64 {
+            self.components[self.len] = component;
+            self.len += 1;
+        }
+    }
+
+    // Returns the sum of the expansion and a float.
+    fn grow(&self, b: f64) -> Expansion {
+        let mut result = Expansion::zero();
+        let mut q = b;
+        for &component in self.components[..self.len].iter() {
+            let (sum, error) = two_sum(q, component);
+            result.push(error);
+            q = sum;
+        }
+        result.push(q);
+        result
+    }
+
+    // Returns the sum of two expansions.
+    fn add(&self, other: &Expansion) -> Expansion {
+        other.components[..other.len].iter().fold(*self, |a, &x| a.grow(x))
+    }
+
+    // Returns the negation of the expansion.
+    fn neg(&self) -> Expansion {
+        let mut result = *self;
+        for x in result.components[..result.len].iter_mut() { *x = -*x; }
+        result
+    }
+
+    // Returns the product of the expansion and a float.
+    fn scale(&self, b: f64) -> Expansion {
+        let mut result = Expansion::zero();
+        if self.len == 0 { return result; }
+
+        let (mut q, error) = two_product(self.components[0], b);
+        result.push(error);
+        for &component in self.components[1..self.len].iter() {
+            let (product, product_error) = two_product(component, b);
+            let (sum, error) = two_sum(q, product_error);
+            result.push(error);
+            let (total, error) = fast_two_sum(product, sum);
+            result.push(error);
+            q = total;
+        }
+        result.push(q);
+        result
+    }
+
+    // Returns the product of two expansions.
+    fn mul(&self, other: &Expansion) -> Expansion {
+        other.components[..other.len].iter().fold(Expansion::zero(), |a, &x| {
+            a.add(&self.scale(x))
+        })
+    }
+
+    // Returns an approximation of the expansion with the correct sign.
+    fn estimate(&self) -> f64 {
+        self.components[..self.len].iter().fold(0f64, |a, &x| a + x)
+    }
+}
+
+// Returns twice the signed area of the triangle `a`, `b`, `c`: positive if
+// the points turn anticlockwise, negative if clockwise and zero if they are
+// collinear. The result is computed in floating point.
+pub fn float_orientation(a: Vec2D, b: Vec2D, c: Vec2D) -> f64 {
+    (a.x - c.x) * (b.y - c.y) - (a.y - c.y) * (b.x - c.x)
+}
+
+// As `float_orientation`, but the sign of the result is exact. The floating-
+// point result is returned when its error bound shows its sign is correct,
+// otherwise the orientation is computed exactly.
+pub fn exact_orientation(a: Vec2D, b: Vec2D, c: Vec2D) -> f64 {
+    let left = (a.x - c.x) * (b.y - c.y);
+    let right = (a.y - c.y) * (b.x - c.x);
+    let det = left - right;
+    if det.abs() >= ERROR_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+
+    let left = Expansion::difference(a.x, c.x)
+        .mul(&Expansion::difference(b.y, c.y));
+    let right = Expansion::difference(a.y, c.y)
+        .mul(&Expansion::difference(b.x, c.x));
+    left.add(&right.neg()).estimate()
+}
@@ -1,18 +1,30 @@
 use types::*;
 
 use super::common::types::Frame;
-use super::linalg::matrix2d::Mat2D;
+use super::predicates::Predicate;
+use super::linalg::scalar::Scalar;
+use super::linalg::transform2d::Transform2D;
 use super::linalg::vector2d::Vec2D;
-use super::utilities::rng_utilities::{random_margin, random_unity};
-use super::utilities::types::{HasScenario, Obstacles};
+use super::utilities::explain::Trace;
+use super::utilities::invariants::Evaluation;
+use super::utilities::oracle::{Geometry, Risk};
+use super::utilities::record::Record;
+use super::utilities::rng_utilities::{random_grid_point, random_index};
+use super::utilities::rng_utilities::{random_integer, random_margin};
+use super::utilities::rng_utilities::{random_unity, BOUNDARY_MARGIN};
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+use super::utilities::world::World;
 
 use std::f64::consts::PI;
 
+// Radius of the body of vehicles when checking for collisions.
+const BODY_RADIUS: f64 = 2f64;
+
 // Arrangement of vehicle and line segment obstacles.
-pub struct Scenario { pub vehicle: Vehicle
-                    , pub walls: Vec<Segment> }
+pub struct Scenario<T = f64> { pub vehicle: Vehicle<T>
+                             , pub walls: Vec<Segment<T>> }
 
-impl HasScenario for Scenario {
+impl<T: Scalar> HasScenario for Scenario<T> {
     // Returns the interactions between the vehicle and obstacles in the
     // scenario.
     fn interactions(&self) -> u32 {
@@ -31,13 +43,48 @@ impl HasScenario for Scenario {
     // the steering scenario.
     fn avoidance(&mut self) -> Option<Vec2D> {
         self.vehicle.update();
-        self.vehicle.wall_avoidance(&self.walls)
+        self.vehicle.wall_avoidance(&self.walls).map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.vehicle.update();
+        self.vehicle.explain_avoidance(&self.walls)
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.vehicle.update();
+        self.vehicle.evaluate_avoidance(&self.walls)
+    }
+
+    // Returns the parameters defining the vehicle and walls.
+    fn record(&self) -> Record {
+        let mut record = self.vehicle.record();
+        for wall in self.walls.iter() {
+            record.obstacle(&wall.geometry().parameters());
+        }
+        record
+    }
+
+    // Returns whether any feeler intersects each wall, and whether the wall
+    // lies in the path of the vehicle.
+    fn risks(&self) -> Vec<Risk> {
+        let body = self.vehicle.swept_body(BODY_RADIUS);
+        self.walls.iter().map(|wall| {
+            let feelers = self.vehicle.local_feelers.iter();
+            let predicted = feelers.fold(false, |a, feeler| {
+                a || self.vehicle.interaction(feeler, wall).is_some()
+            });
+            Risk::new(predicted, &body, &wall.geometry())
+        }).collect()
     }
 }
 
-impl Scenario {
+impl<T: Scalar> Scenario<T> {
     // Creates a scenario involving a vehicle with feelers and wall segments.
-    pub fn new(vehicle: Vehicle, walls: Vec<Segment>) -> Scenario {
+    pub fn new(vehicle: Vehicle<T>, walls: Vec<Segment<T>>) -> Scenario<T> {
         Scenario { vehicle: vehicle, walls: walls }
     }
 }
@@ -49,17 +96,23 @@ fn random_frame() -> Frame {
     Frame::new(position, 2f64 * PI * random_unity())
 }
 
+// Returns the segment between the given points, in the scalar type `T`.
+fn segment<T: Scalar>(x1: f64, y1: f64, x2: f64, y2: f64) -> Segment<T> {
+    Segment::new( Vec2D::new(x1, y1).cast()
+                , Vec2D::new(x2, y2).cast() ).unwrap()
+}
+
 // Returns a feeler that extends ahead of a vehicle.
-fn feeler() -> Segment {
-    Segment::new( Vec2D::new(2f64, 0f64), Vec2D::new(12f64, 0f64) ).unwrap()
+fn feeler<T: Scalar>() -> Segment<T> {
+    segment(2f64, 0f64, 12f64, 0f64)
 }
 
 // Returns a vector of whiskers - feelers that extend to the left and right of
 // a hypothetical vehicle.
-fn whiskers() -> Vec<Segment> {
-    let l = Segment::new( Vec2D::new(0f64,  2f64), Vec2D::new(5f64,  7f64) );
-    let r = Segment::new( Vec2D::new(0f64, -2f64), Vec2D::new(5f64, -7f64) );
-    vec!(l.unwrap(), r.unwrap())
+fn whiskers<T: Scalar>() -> Vec<Segment<T>> {
+    let l = segment(0f64,  2f64, 5f64,  7f64);
+    let r = segment(0f64, -2f64, 5f64, -7f64);
+    vec!(l, r)
 }
 
 // Returns a wall segment that is near the given feeler.
@@ -67,15 +120,15 @@ fn wall_near_feeler(feeler: &Segment, significant: bool) -> Segment {
     // Determine feeler transform.
     let position = feeler.point1;
     let orientation = feeler.unit.angle();
-    let to_world = Mat2D::rotation(orientation).shift(position);
+    let to_world = Transform2D::new(orientation, position);
 
     // Create intersecting segment.
     let mut offset = random_margin();
     if !significant { offset += 1f64; }
-    let intersection = Vec2D::unitx().mul(feeler.length * offset);
+    let intersection = Vec2D::unitx() * (feeler.length * offset);
     let angle = (0.25f64 + 0.5f64 * random_unity()) * PI;
-    let local_point1 = intersection.add(Vec2D::polar(angle, 0.5f64));
-    let local_point2 = intersection.add(Vec2D::polar(angle + PI, 0.5f64));
+    let local_point1 = intersection + Vec2D::polar(angle, 0.5f64);
+    let local_point2 = intersection + Vec2D::polar(angle + PI, 0.5f64);
 
     let point1 = to_world.transform(local_point1);
     let point2 = to_world.transform(local_point2);
@@ -83,29 +136,192 @@ fn wall_near_feeler(feeler: &Segment, significant: bool) -> Segment {
 }
 
 // Constructs a scenario for a given arragement of feelers.
-fn scenario(num_obstacles: u32, significant: bool, has_whiskers: bool)
-    -> Box<Scenario>
+fn scenario( num_obstacles: u32
+           , significant:   bool
+           , has_whiskers:  bool
+           , predicate:     Predicate ) -> Box<Scenario>
 {
     let feeler = feeler();
     let frame = random_frame();
-    let to_world = frame.to_world.clone();
+    let to_world = frame.to_world;
 
     let f = |_| wall_near_feeler(&feeler, significant).transform(&to_world);
     let walls = (0..num_obstacles).map(f).collect();
 
     let mut feelers = vec!(feeler.clone());
     if has_whiskers { feelers.extend(whiskers()); }
-    Box::new(Scenario::new(Vehicle::new(frame, feelers), walls))
+    let mut vehicle = Vehicle::new(frame, feelers);
+    vehicle.predicate = predicate;
+    Box::new(Scenario::new(vehicle, walls))
+}
+
+// Distance ahead of the vehicle beyond which whiskers do not reach.
+const WHISKER_REACH: i32 = 5;
+
+// Returns a wall, in the space of a vehicle facing along the x-axis, exactly
+// on the boundary of the feeler's intersection check and beyond the reach of
+// whiskers. Walls touching the feeler with an endpoint, or touched by its tip,
+// are significant. Walls parallel or coincident to the feeler, or just beyond
+// its tip, are not.
+fn boundary_wall(feeler: &Segment, significant: bool) -> Segment {
+    let tip = feeler.point2.x;
+    let x = random_integer(WHISKER_REACH + 1, tip as i32);
+    let height = random_integer(1, 3);
+    let side = if random_unity() < 0.5f64 { 1f64 } else { -1f64 };
+
+    let (point1, point2) = match (significant, random_index(3)) {
+        (true, 0) => (Vec2D::new(x, 0f64), Vec2D::new(x, side * height)),
+        (true, _) => (Vec2D::new(tip, -height), Vec2D::new(tip, height)),
+        (false, 0) => ( Vec2D::new(x - 1f64, side * height)
+                      , Vec2D::new(tip, side * height) ),
+        (false, 1) => (Vec2D::new(x - 1f64, 0f64), Vec2D::new(tip, 0f64)),
+        (false, _) => ( Vec2D::new(tip + BOUNDARY_MARGIN, -height)
+                      , Vec2D::new(tip + BOUNDARY_MARGIN, height) )
+    };
+    Segment::new(point1, point2).unwrap()
+}
+
+// Constructs a scenario with walls on the boundary of the feeler's
+// intersection check, for a vehicle facing along the x-axis from an integer
+// position so that the geometry is exactly representable.
+fn boundary_scenario( num_obstacles: u32
+                    , significant:   bool
+                    , has_whiskers:  bool
+                    , predicate:     Predicate ) -> Box<Scenario>
+{
+    let feeler = feeler();
+    let frame = Frame::new(random_grid_point(), 0f64);
+    let to_world = frame.to_world;
+
+    let f = |_| boundary_wall(&feeler, significant).transform(&to_world);
+    let walls = (0..num_obstacles).map(f).collect();
+
+    let mut feelers = vec!(feeler.clone());
+    if has_whiskers { feelers.extend(whiskers()); }
+    let mut vehicle = Vehicle::new(frame, feelers);
+    vehicle.predicate = predicate;
+    Box::new(Scenario::new(vehicle, walls))
 }
 
-// Returns a scenario with the given configuration of obstacles. Returns none
-// if it is not possible to create the given scenario.
-pub fn scenario_with_obstacles(obstacles: &Obstacles, has_whiskers: bool)
+// Returns a scenario with the given configuration of obstacles placed on the
+// boundary of the feeler's intersection check, whose vehicle decides
+// orientation with the given predicate. Returns none if it is not possible to
+// create the given scenario.
+pub fn boundary_scenario_with_obstacles( obstacles:    &Obstacles
+                                       , has_whiskers: bool
+                                       , predicate:    Predicate )
     -> Option<Box<HasScenario>>
 {
+    let (whiskers, p) = (has_whiskers, predicate);
     match obstacles.details() {
-        (num_obs, 0u32) => Some(scenario(num_obs, false, has_whiskers)),
-        (0u32, num_obs) => Some(scenario(num_obs, true, has_whiskers)),
+        (num_obs, 0u32) => Some(boundary_scenario(num_obs, false, whiskers, p)),
+        (0u32, num_obs) => Some(boundary_scenario(num_obs, true, whiskers, p)),
         _ => None
     }
 }
+
+// Returns a scenario with the given configuration of obstacles, whose vehicle
+// decides orientation with the given predicate. Returns none if it is not
+// possible to create the given scenario.
+pub fn scenario_with_obstacles( obstacles:    &Obstacles
+                              , has_whiskers: bool
+                              , predicate:    Predicate )
+    -> Option<Box<HasScenario>>
+{
+    let (whiskers, p) = (has_whiskers, predicate);
+    match obstacles.details() {
+        (num_obs, 0u32) => Some(scenario(num_obs, false, whiskers, p)),
+        (0u32, num_obs) => Some(scenario(num_obs, true, whiskers, p)),
+        _ => None
+    }
+}
+
+// Returns the wall between the given world points, with unit and normal
+// vectors computed in the frame's space, or none if it is degenerate. Walls
+// of absolute frames are transformed back to world space, whilst relative
+// frames keep the exact world points.
+fn frame_wall<T: Scalar>( frame:  &Frame<T>
+                        , point1: Vec2D<T>
+                        , point2: Vec2D<T> ) -> Option<Segment<T>>
+{
+    let local = Segment::new( frame.local_point(point1)
+                            , frame.local_point(point2) ).ok()?;
+    match frame.origin {
+        Origin::Absolute => Some(local.transform(&frame.to_world)),
+        Origin::Relative => Some(Segment { point1: point1
+                                         , point2: point2
+                                         , .. local })
+    }
+}
+
+// Returns the scenario described by the given record, or none if the record
+// is incomplete. Walls are rebuilt in the vehicle's space, where their unit
+// and normal vectors were computed when they were generated. The vehicle has
+// the recorded feelers and predicate.
+pub fn scenario_from_record(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_with_origin(record, Origin::Absolute)
+}
+
+// As `scenario_from_record`, but the vehicle's frame transforms about the
+// given origin.
+pub fn scenario_from_record_with_origin(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    scenario_from_record_as::<f64>(record, origin)
+}
+
+// As `scenario_from_record_with_origin`, but the scenario is evaluated in the
+// scalar type `T`.
+pub fn scenario_from_record_as<T: Scalar>(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    let v = &record.vehicle;
+    let frame = Frame::<T>::with_origin( v.vec_as("x", "y")?
+                                       , v.get_as("orientation")?
+                                       , origin );
+    let mut feelers = vec!();
+    for i in 0..v.get("feelers")? as usize {
+        let name = |x: &str| format!("feeler{}_{}", i, x);
+        let point1 = v.vec_as(&name("x1"), &name("y1"))?;
+        let point2 = v.vec_as(&name("x2"), &name("y2"))?;
+        feelers.push(Segment::new(point1, point2).ok()?);
+    }
+
+    let mut walls = vec!();
+    for obstacle in record.obstacles.iter() {
+        walls.push(frame_wall( &frame
+                             , obstacle.vec_as("x1", "y1")?
+                             , obstacle.vec_as("x2", "y2")? )?);
+    }
+    let mut vehicle = Vehicle::new(frame, feelers);
+    if v.get("exact")? != 0f64 { vehicle.predicate = Predicate::Exact; }
+    Some(Box::new(Scenario::new(vehicle, walls)))
+}
+
+// Returns the scenario of the given shared world, whose vehicle decides
+// orientation with the given predicate. The unit and normal vectors of walls
+// are computed in the vehicle's space, as for generated walls. Obstacles
+// other than segments, and degenerate segments, are ignored.
+pub fn scenario_from_world( world:        &World
+                          , has_whiskers: bool
+                          , predicate:    Predicate ) -> Box<HasScenario>
+{
+    let frame = Frame::new(world.position, world.orientation());
+    let mut feelers = vec!(feeler());
+    if has_whiskers { feelers.extend(whiskers()); }
+
+    let walls = world.obstacles.iter().filter_map(|x| match *x {
+        Geometry::Segment { point1, point2 } => {
+            let local = Segment::new( frame.to_local.transform(point1)
+                                    , frame.to_local.transform(point2) );
+            local.ok().map(|x| Segment { point1: point1
+                                       , point2: point2
+                                       , .. x })
+        },
+        _ => None
+    }).collect();
+
+    let mut vehicle = Vehicle::new(frame, feelers);
+    vehicle.predicate = predicate;
+    Box::new(Scenario::new(vehicle, walls))
+}
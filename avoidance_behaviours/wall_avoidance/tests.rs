@@ -1,19 +1,323 @@
 #![cfg(test)]
 
+use super::common::types::Frame;
+use super::linalg::fixed::ArrayVec;
+use super::linalg::geometry;
+use super::linalg::vector2d::Vec2D;
+use super::predicates::{exact_orientation, float_orientation, Predicate};
+#[cfg(feature = "std")]
+use super::scenarios::boundary_scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record, scenario_from_world};
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_origin;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_as;
+#[cfg(feature = "std")]
 use super::scenarios::scenario_with_obstacles;
-use super::utilities::types::{HasScenario, Obstacles};
-use super::utilities::test_utilities::test_scenarios;
+use super::types::{Segment, Vehicle};
+#[cfg(feature = "std")]
+use super::utilities::golden::check_corpus;
+#[cfg(all(feature = "std", feature = "deterministic"))]
+use super::utilities::golden::digest_corpus;
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+#[cfg(feature = "std")]
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+#[cfg(feature = "std")]
+use super::utilities::world::Kind;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::{test_drift, test_precisions};
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_worlds;
 
+// Range of powers of two by which near-degenerate configurations are scaled.
+// `Segment::new` rejects segments shorter than about 10^-4.
+const MIN_SCALE: i32 = -12;
+const MAX_SCALE: i32 = 21;
+
+// Predicates with which scenarios are tested.
+const PREDICATES: [Predicate; 2] = [Predicate::Float, Predicate::Exact];
+
+// Distance along each axis by which scenarios are translated when testing
+// drift, far enough from the origin that absolute evaluation drifts.
+#[cfg(feature = "std")]
+const OFFSET: f64 = 1e12;
+
+// Returns a segment between the given points, scaled by `scale`.
+fn segment(x1: f64, y1: f64, x2: f64, y2: f64, scale: f64) -> Segment {
+    let point1 = Vec2D::new(x1 * scale, y1 * scale);
+    let point2 = Vec2D::new(x2 * scale, y2 * scale);
+    Segment::new(point1, point2).unwrap()
+}
+
+// Returns the sign of the orientation of the given points, computed exactly
+// using integer arithmetic. The coordinates must be multiples of 2^-53.
+fn reference_orientation(a: Vec2D, b: Vec2D, c: Vec2D) -> i32 {
+    let unit = 9_007_199_254_740_992f64;
+    let int = |v: Vec2D| ((v.x * unit) as i128, (v.y * unit) as i128);
+    let (a, b, c) = (int(a), int(b), int(c));
+    let det = (a.0 - c.0) * (b.1 - c.1) - (a.1 - c.1) * (b.0 - c.0);
+    if det > 0 { 1 } else if det < 0 { -1 } else { 0 }
+}
+
+// Returns 2 to the power of `k`, which `powi` only computes with `std`.
+fn power_of_two(k: i32) -> f64 {
+    f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+// Returns the sign of an orientation.
+fn sign(o: f64) -> i32 {
+    if o > 0f64 { 1 } else if o < 0f64 { -1 } else { 0 }
+}
+
+#[cfg(feature = "std")]
 #[test]
 fn test() {
-    let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
-        scenario_with_obstacles(o, false)
+    for &predicate in PREDICATES.iter() {
+        let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
+            scenario_with_obstacles(o, false, predicate)
+        };
+        let single = |r: &Record| {
+            scenario_from_record_as::<f32>(r, Origin::Absolute)
+        };
+
+        for i in 1..6 {
+            let obstacles1 = Obstacles::new(i, 0u32);
+            test_precisions(&obstacles1, &creator, &single).unwrap();
+            let obstacles2 = Obstacles::new(0u32, i);
+            test_precisions(&obstacles2, &creator, &single).unwrap();
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn boundary() {
+    for &predicate in PREDICATES.iter() {
+        let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
+            boundary_scenario_with_obstacles(o, true, predicate)
+        };
+        let single = |r: &Record| {
+            scenario_from_record_as::<f32>(r, Origin::Absolute)
+        };
+
+        for i in 1..6 {
+            let obstacles1 = Obstacles::new(i, 0u32);
+            test_precisions(&obstacles1, &creator, &single).unwrap();
+            let obstacles2 = Obstacles::new(0u32, i);
+            test_precisions(&obstacles2, &creator, &single).unwrap();
+        }
+    }
+}
+
+// Points on a fine grid near (0.5, 0.5) are almost collinear with (12, 12)
+// and (24, 24). The floating-point orientation misjudges some of them, but
+// the exact orientation agrees with integer arithmetic for all of them.
+#[test]
+fn near_collinear_orientation() {
+    let (b, c) = (Vec2D::new(12f64, 12f64), Vec2D::new(24f64, 24f64));
+    let ulp = 1f64 / 9_007_199_254_740_992f64;
+    let mut float_errors = 0;
+    for i in 0..256 {
+        for j in 0..256 {
+            let a = Vec2D::new( 0.5f64 + i as f64 * ulp
+                              , 0.5f64 + j as f64 * ulp );
+            let reference = reference_orientation(a, b, c);
+            assert_eq!(sign(exact_orientation(a, b, c)), reference);
+            if sign(float_orientation(a, b, c)) != reference {
+                float_errors += 1;
+            }
+        }
+    }
+    assert!(float_errors > 0);
+}
+
+// Shared segments convert to walls and back unchanged, and degenerate ones
+// are rejected.
+#[test]
+fn shared_segments() {
+    let shared = geometry::Segment::new( Vec2D::new(1f64, 2f64)
+                                       , Vec2D::new(-3f64, 5f64) );
+    let wall = Segment::from_segment(&shared).unwrap();
+    assert_eq!(wall.to_segment(), shared);
+    assert_eq!(wall.length, 5f64);
+    let point = geometry::Segment::new(shared.point1, shared.point1);
+    assert!(Segment::from_segment(&point).is_err());
+}
+
+// Whether the float predicate judges walls crossing the feeler at a shallow
+// angle to be parallel depends on their scale. The exact predicate finds the
+// crossing at every scale, as it does for perpendicular walls.
+#[test]
+fn shallow_crossings() {
+    let shallow = 1f64 / 1_099_511_627_776f64;
+    for k in MIN_SCALE..MAX_SCALE {
+        let scale = power_of_two(k);
+        let feeler = segment(0f64, 0f64, 1f64, 0f64, scale);
+        let walls = [ segment(0f64, -shallow, 1f64, shallow, scale)
+                    , segment(0.5f64, -1f64, 0.5f64, 1f64, scale) ];
+        for wall in walls.iter() {
+            let point = feeler.exact_segment_intersection(wall).unwrap();
+            assert!((point - Vec2D::new(0.5f64 * scale, 0f64)).mag()
+                    <= 1e-9f64 * scale);
+        }
+    }
+
+    let float = |scale: f64| {
+        let feeler = segment(0f64, 0f64, 1f64, 0f64, scale);
+        let wall = segment(0f64, -shallow, 1f64, shallow, scale);
+        feeler.segment_intersection(&wall)
     };
+    assert!(float(1f64).is_none());
+    assert!(float(8192f64).is_some());
+}
+
+// Walls that touch the feeler with an endpoint, or are touched by the
+// feeler's endpoint, intersect it exactly at that point at every scale.
+#[test]
+fn touching_endpoints() {
+    for k in MIN_SCALE..MAX_SCALE {
+        let scale = power_of_two(k);
+        let feeler = segment(1f64, 1f64, 7f64, 9f64, scale);
+        let walls = [ segment(4f64, 5f64, 9f64, 1f64, scale)
+                    , segment(3f64, 11f64, 11f64, 7f64, scale)
+                    , segment(-3f64, 4f64, 5f64, -2f64, scale) ];
+        let points = [(4f64, 5f64), (7f64, 9f64), (1f64, 1f64)];
+        for (wall, &(x, y)) in walls.iter().zip(points.iter()) {
+            let point = feeler.exact_segment_intersection(wall).unwrap();
+            assert!((point - Vec2D::new(x * scale, y * scale)).mag()
+                    <= 1e-9f64 * scale);
+        }
+    }
+}
+
+// Walls on the feeler's line never intersect it, whether they overlap it or
+// not, and walls parallel to it never intersect it however close they are.
+#[test]
+fn collinear_and_parallel_walls() {
+    for k in MIN_SCALE..MAX_SCALE {
+        let scale = power_of_two(k);
+        let feeler = segment(1f64, 1f64, 7f64, 9f64, scale);
+        let walls = [ segment(4f64, 5f64, 10f64, 13f64, scale)
+                    , segment(-2f64, -3f64, 13f64, 17f64, scale)
+                    , segment(10f64, 13f64, 13f64, 17f64, scale) ];
+        for wall in walls.iter() {
+            assert!(feeler.exact_segment_intersection(wall).is_none());
+        }
+
+        let offset = power_of_two(k - 40);
+        let shift = Vec2D::new(0f64, offset);
+        let parallel = Segment::new( feeler.point1 + shift
+                                   , feeler.point2 + shift ).unwrap();
+        assert!(feeler.exact_segment_intersection(&parallel).is_none());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn golden() {
+    check_corpus(include_str!("golden.json"), scenario_from_record).unwrap();
+}
+
+// Digest of the golden corpus as evaluated in deterministic mode, which every
+// platform must reproduce.
+#[cfg(all(feature = "std", feature = "deterministic"))]
+const DIGEST: u64 = 0xf06130235d6faa7b;
+
+#[cfg(all(feature = "std", feature = "deterministic"))]
+#[test]
+fn deterministic() {
+    let corpus = include_str!("golden.json");
+    let digest = digest_corpus(corpus, scenario_from_record);
+    assert!(digest == DIGEST, "corpus digest {:#018x}", digest);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn world() {
+    for &predicate in [Predicate::Float, Predicate::Exact].iter() {
+        test_worlds(Kind::Segments, |w| {
+            scenario_from_world(w, true, predicate)
+        }).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drift() {
+    for &predicate in PREDICATES.iter() {
+        let creator = |o: &Obstacles| -> Option<Box<HasScenario>> {
+            scenario_with_obstacles(o, true, predicate)
+        };
+        test_drift(OFFSET, creator, |r| {
+            scenario_from_record_with_origin(r, Origin::Relative)
+        }).unwrap();
+    }
+}
+
+// Records hold the vehicle's feelers and predicate, so the vehicles of
+// scenarios with whiskers are rebuilt exactly, with either predicate.
+#[cfg(feature = "std")]
+#[test]
+fn records() {
+    let obstacles = Obstacles::new(0u32, 2u32);
+    for &predicate in PREDICATES.iter() {
+        let scenario = scenario_with_obstacles(&obstacles, true, predicate);
+        let record = scenario.unwrap().record();
+        assert_eq!(record.vehicle.get("feeler2_y2"), Some(-7f64));
+        let rebuilt = scenario_from_record(&record).unwrap().record();
+        assert!(rebuilt.vehicle.close(&record.vehicle, 0f64));
+        assert!(record.vehicle.close(&rebuilt.vehicle, 0f64));
+    }
+}
 
-    for i in 1..6 {
-        let obstacles1 = Obstacles::new(i, 0u32);
-        assert!(test_scenarios(&obstacles1, |x| creator(x)));
-        let obstacles2 = Obstacles::new(0u32, i);
-        assert!(test_scenarios(&obstacles2, |x| creator(x)));
+// Walls held in a fixed-capacity collection are avoided as they are in an
+// array, with either predicate.
+#[test]
+fn fixed_capacity() {
+    let frame = Frame::new(Vec2D::zero(), 0f64);
+    let feeler = segment(0f64, 0f64, 10f64, 0f64, 1f64);
+    let mut vehicle = Vehicle::new(frame, [feeler].to_vec());
+    let ends = [(4f64, -1f64), (7f64, 1f64), (20f64, 0f64)];
+    let wall = |&(x, y): &(f64, f64)| segment(x, y, x + 1f64, -y, 1f64);
+    let walls = [wall(&ends[0]), wall(&ends[1]), wall(&ends[2])];
+    let mut fixed: ArrayVec<Segment, 3> = ArrayVec::new();
+    for end in ends.iter() { fixed.push(wall(end)); }
+    assert!(fixed.try_push(wall(&ends[0])).is_err());
+    for &predicate in PREDICATES.iter() {
+        vehicle.predicate = predicate;
+        let force = vehicle.wall_avoidance(&fixed);
+        assert!(force.is_some());
+        assert_eq!(force, vehicle.wall_avoidance(&walls));
     }
 }
+
+// Segments serialise their endpoints and vehicles their frame's parameters,
+// local feelers and predicate, from which deserialised vehicles rebuild the
+// same feelers in world space. Degenerate segments do not deserialise.
+#[cfg(all(feature = "std", feature = "serde"))]
+#[test]
+fn serialisation() {
+    let wall = segment(1f64, 2f64, 3f64, 2f64, 1f64);
+    let json = ::serde_json::to_string(&wall).unwrap();
+    let expected = r#"{"point1":{"x":1.0,"y":2.0},"point2":{"x":3.0,"y":2.0}}"#;
+    assert_eq!(json, expected);
+    let rebuilt: Segment = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.to_segment(), wall.to_segment());
+    assert_eq!(rebuilt.normal, wall.normal);
+    let point = r#"{"x":1.0,"y":2.0}"#;
+    let degenerate = format!(r#"{{"point1":{},"point2":{}}}"#, point, point);
+    assert!(::serde_json::from_str::<Segment>(&degenerate).is_err());
+
+    let frame = Frame::new(Vec2D::new(3f64, -4f64), 0.5f64);
+    let mut vehicle = Vehicle::new(frame, vec![wall.clone()]);
+    vehicle.predicate = Predicate::Exact;
+    let json = ::serde_json::to_string(&vehicle).unwrap();
+    assert!(json.ends_with(r#""predicate":"exact"}"#));
+    let rebuilt: Vehicle = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(::serde_json::to_string(&rebuilt).unwrap(), json);
+    assert_eq!(rebuilt.predicate, Predicate::Exact);
+    assert_eq!( rebuilt.local_feelers[0].to_segment()
+              , vehicle.local_feelers[0].to_segment() );
+}
@@ -1,52 +1,97 @@
-use super::linalg::vector2d::{EPSILON, Vec2D};
-use super::linalg::matrix2d::Mat2D;
+use super::linalg::geometry;
+use super::linalg::scalar::Scalar;
+use super::linalg::vector2d::Vec2D;
+use super::linalg::transform2d::Transform2D;
 use super::common::types::Frame;
+use super::linalg::origin::Origin;
+use super::predicates::{exact_orientation, Predicate};
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use super::serde::de::Error;
+#[cfg(feature = "serde")]
+use super::serde::ser::SerializeStruct;
+#[cfg(feature = "std")]
+use super::utilities::explain::Trace;
+#[cfg(feature = "std")]
+use super::utilities::invariants::Evaluation;
+#[cfg(feature = "std")]
+use super::utilities::oracle::{Geometry, SweptBody};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+
+#[cfg(not(feature = "std"))]
+use alloc::borrow::Cow;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+// Returns whether two orientations are both strictly positive or both
+// strictly negative.
+fn same_side<T: Scalar>(o1: T, o2: T) -> bool {
+    (o1 > T::ZERO && o2 > T::ZERO) || (o1 < T::ZERO && o2 < T::ZERO)
+}
 
 // Used to indicate degenerate segment geometry.
 #[derive(Debug)]
 pub struct Degenerate;
 
 // Captures the intersection between two lines.
-enum LineLine { Coincident
-              , Parallel
-              , Intersect(f64,f64) }
+enum LineLine<T> { Coincident
+                 , Parallel
+                 , Intersect(T,T) }
 
 // Used to describe intersection between a feeler and wall.
 #[derive(Copy, Clone)]
-pub struct Interaction { force: Vec2D
-                       , dist:  f64 }
+pub struct Interaction<T = f64> { force: Vec2D<T>
+                                , dist:  T }
 
-impl Interaction {
+impl<T: Scalar> Interaction<T> {
     // Creates an interaction from the given values.
-    fn new(force: Vec2D, dist: f64) -> Interaction {
+    fn new(force: Vec2D<T>, dist: T) -> Interaction<T> {
         Interaction { force: force, dist: dist }
     }
 }
 
 // Defines a line segment.
 #[derive(Clone)]
-pub struct Segment { pub point1: Vec2D
-                   , pub point2: Vec2D
-                   , pub length: f64
-                   , pub unit:   Vec2D
-                   , pub normal: Vec2D }
+pub struct Segment<T = f64> { pub point1: Vec2D<T>
+                            , pub point2: Vec2D<T>
+                            , pub length: T
+                            , pub unit:   Vec2D<T>
+                            , pub normal: Vec2D<T> }
 
-impl Segment {
+impl<T: Scalar> Segment<T> {
     // Creates a line segment from the given points.
-    pub fn new(point1: Vec2D, point2: Vec2D) -> Result<Segment, Degenerate> {
-        let diff = point2.sub(point1);
+    pub fn new(point1: Vec2D<T>, point2: Vec2D<T>)
+        -> Result<Segment<T>, Degenerate>
+    {
+        let diff = point2 - point1;
         let length = diff.mag();
-        let unit = diff.mul(1f64 / length);
+        let unit = diff * (T::ONE / length);
         let result = Segment { point1: point1
                              , point2: point2
                              , length: length
                              , unit:   unit
                              , normal: unit.perp() };
-        if length < EPSILON { Err(Degenerate) } else { Ok(result) }
+        if length < T::EPSILON { Err(Degenerate) } else { Ok(result) }
+    }
+
+    // Creates a line segment with the endpoints of the given segment.
+    pub fn from_segment(segment: &geometry::Segment<T>)
+        -> Result<Segment<T>, Degenerate>
+    {
+        Segment::new(segment.point1, segment.point2)
+    }
+
+    // Returns the endpoints of the segment as a shared segment.
+    pub fn to_segment(&self) -> geometry::Segment<T> {
+        geometry::Segment::new(self.point1, self.point2)
     }
 
-    // Transforms the two points of the segment using the given matrix.
-    pub fn transform(&self, m: &Mat2D) -> Segment {
+    // Transforms the two points of the segment using the given transform.
+    pub fn transform(&self, m: &Transform2D<T>) -> Segment<T> {
         Segment { point1: m.transform(self.point1)
                 , point2: m.transform(self.point2)
                 , length: self.length
@@ -54,11 +99,27 @@ impl Segment {
                 , normal: self.normal }
     }
 
+    // Translates the two points of the segment by the given offset.
+    pub fn shift(&self, offset: Vec2D<T>) -> Segment<T> {
+        Segment { point1: self.point1 + offset
+                , point2: self.point2 + offset
+                , length: self.length
+                , unit:   self.unit
+                , normal: self.normal }
+    }
+
+    // Returns the endpoints of the segment.
+    #[cfg(feature = "std")]
+    pub fn geometry(&self) -> Geometry {
+        Geometry::Segment { point1: self.point1.cast()
+                          , point2: self.point2.cast() }
+    }
+
     // Returns the intersection between this segment and another, considering
     // both as lines. Assumes the given geometry is not degenerate, which
     // should hold as long as the points inside the segments have not been
     // altered after the creation of the segment.
-    fn line_intersection(&self, other: &Segment) -> LineLine {
+    fn line_intersection(&self, other: &Segment<T>) -> LineLine<T> {
         let pt1 = self.point1;
         let pt2 = self.point2;
         let pt3 = other.point1;
@@ -73,8 +134,8 @@ impl Segment {
         let mut d = (pt4.y - pt3.y) * (pt2.x - pt1.x);
         d -= (pt4.x - pt3.x) * (pt2.y - pt1.y);
 
-        if d.abs() < EPSILON {
-            if a.abs() < EPSILON && b.abs() < EPSILON {
+        if d.abs() < T::EPSILON {
+            if a.abs() < T::EPSILON && b.abs() < T::EPSILON {
                 LineLine::Coincident
             } else {
                 LineLine::Parallel
@@ -87,36 +148,83 @@ impl Segment {
     // Returns the intersection of this segment and another, if the segments
     // are not parallel nor coincident. If the provided geometry is degenerate
     // this function will panic (should not occur, as verified by the tests).
-    pub fn segment_intersection(&self, other: &Segment) -> Option<Vec2D> {
+    pub fn segment_intersection(&self, other: &Segment<T>)
+        -> Option<Vec2D<T>>
+    {
         match self.line_intersection(&other) {
             LineLine::Intersect(u1, u2) => {
-                let u1_xsect = u1 >= 0f64 && u1 <= 1f64;
-                let u2_xsect = u2 >= 0f64 && u2 <= 1f64;
+                let u1_xsect = u1 >= T::ZERO && u1 <= T::ONE;
+                let u2_xsect = u2 >= T::ZERO && u2 <= T::ONE;
 
                 if !u1_xsect || !u2_xsect { return None; }
 
-                let diff = self.point2.sub(self.point1);
-                Some(self.point1.add(diff.mul(u1)))
+                let diff = self.point2 - self.point1;
+                Some(self.point1 + diff * u1)
             },
             _ => None
         }
     }
+
+    // As `segment_intersection`, but decides whether the segments intersect
+    // using exact orientations, so the result does not depend on the scale of
+    // the segments. Collinear segments are considered not to intersect.
+    // Points are converted to `f64`, which represents them exactly, and the
+    // intersection is computed there.
+    pub fn exact_segment_intersection(&self, other: &Segment<T>)
+        -> Option<Vec2D<T>>
+    {
+        let (pt1, pt2): (Vec2D, Vec2D) = ( self.point1.cast()
+                                         , self.point2.cast() );
+        let (pt3, pt4): (Vec2D, Vec2D) = ( other.point1.cast()
+                                         , other.point2.cast() );
+
+        let o1 = exact_orientation(pt1, pt2, pt3);
+        let o2 = exact_orientation(pt1, pt2, pt4);
+        if o1 == 0f64 && o2 == 0f64 { return None; }
+        if same_side(o1, o2) { return None; }
+
+        let o3 = exact_orientation(pt3, pt4, pt1);
+        let o4 = exact_orientation(pt3, pt4, pt2);
+        if same_side(o3, o4) { return None; }
+
+        if o1 == 0f64 { return Some(other.point1); }
+        if o2 == 0f64 { return Some(other.point2); }
+
+        // The orientations of this segment's points have opposite signs (or
+        // one is zero), so their difference does not cancel.
+        let u1 = o3 / (o3 - o4);
+        Some((pt1 + (pt2 - pt1) * u1).cast())
+    }
+
+    // Returns the intersection of this segment and another, deciding
+    // orientation with the given predicate.
+    pub fn intersection_with( &self
+                            , other:     &Segment<T>
+                            , predicate: Predicate ) -> Option<Vec2D<T>>
+    {
+        match predicate {
+            Predicate::Float => self.segment_intersection(other),
+            Predicate::Exact => self.exact_segment_intersection(other)
+        }
+    }
 }
 
 // Vehicle with feelers.
-pub struct Vehicle {     frame:         Frame
-                   , pub local_feelers: Vec<Segment>
-                   ,     feelers:       Vec<Segment>
+pub struct Vehicle<T = f64> {     frame:         Frame<T>
+                            , pub local_feelers: Vec<Segment<T>>
+                            ,     feelers:       Vec<Segment<T>>
+                            , pub predicate:     Predicate
 }
 
-impl Vehicle {
+impl<T: Scalar> Vehicle<T> {
     // Creates a vehicle with the given values.
-    pub fn new(frame: Frame, feelers: Vec<Segment>) -> Vehicle {
+    pub fn new(frame: Frame<T>, feelers: Vec<Segment<T>>) -> Vehicle<T> {
         let local_feelers = Vehicle::transform_feelers( &feelers
                                                       , &frame.to_world );
         Vehicle { frame:         frame
                 , feelers:       feelers
-                , local_feelers: local_feelers }
+                , local_feelers: local_feelers
+                , predicate:     Predicate::Float }
     }
 
     // Updates the matrices of the underlying frame.
@@ -126,23 +234,79 @@ impl Vehicle {
                                                        , &self.frame.to_world );
     }
 
+    // Returns a record of the vehicle's parameters: its frame, whether it
+    // uses the exact predicate, and the number of feelers followed by the
+    // endpoints of each relative to the vehicle, named `feeler0_x1` and so on.
+    #[cfg(feature = "std")]
+    pub fn record(&self) -> Record {
+        let exact = if self.predicate == Predicate::Exact { 1f64 }
+                    else { 0f64 };
+        let mut values = vec!(
+            ("x".to_string(), self.frame.position.x.to_f64()),
+            ("y".to_string(), self.frame.position.y.to_f64()),
+            ("orientation".to_string(), self.frame.orientation.to_f64()),
+            ("exact".to_string(), exact),
+            ("feelers".to_string(), self.feelers.len() as f64));
+        for (i, feeler) in self.feelers.iter().enumerate() {
+            let ends = [ ("x1", feeler.point1.x), ("y1", feeler.point1.y)
+                       , ("x2", feeler.point2.x), ("y2", feeler.point2.y) ];
+            for &(name, value) in ends.iter() {
+                let name = format!("feeler{}_{}", i, name);
+                values.push((name, value.to_f64()));
+            }
+        }
+        let values: Vec<(&str, f64)> = values.iter()
+                                             .map(|x| (x.0.as_str(), x.1))
+                                             .collect();
+        Record::new(&values)
+    }
+
+    // Returns a body of the given radius that travels as far ahead as the
+    // furthest reaching feeler.
+    #[cfg(feature = "std")]
+    pub fn swept_body(&self, radius: f64) -> SweptBody {
+        let reach = self.feelers.iter().fold(T::ZERO, |a, x| {
+            let x = x.point2.x;
+            if x > a { x } else { a }
+        });
+        let heading = Vec2D::polar(self.frame.orientation, reach);
+        SweptBody { position: self.frame.position.cast()
+                  , velocity: heading.cast()
+                  , radius:   radius
+                  , horizon:  1f64 }
+    }
+
     // Returns the world positions of the segments attached to this vehicle.
-    fn transform_feelers(feelers: &Vec<Segment>, transform: &Mat2D)
-        -> Vec<Segment>
+    fn transform_feelers(feelers: &Vec<Segment<T>>, transform: &Transform2D<T>)
+        -> Vec<Segment<T>>
     {
-        feelers.iter().map( |x: &Segment| x.transform(&transform) ).collect()
+        feelers.iter().map( |x: &Segment<T>| x.transform(&transform) )
+                      .collect()
     }
 
-    // Returns an interaction, if it exists, between a feeler and wall.
-    pub fn interaction(&self, feeler: &Segment, wall: &Segment)
-        -> Option<Interaction>
+    // Returns the given wall in the space of the vehicle's world feelers,
+    // which are positioned relative to the vehicle by relative frames.
+    fn relative_wall<'a>(&self, wall: &'a Segment<T>)
+        -> Cow<'a, Segment<T>>
     {
-        match feeler.segment_intersection(wall) {
+        match self.frame.origin {
+            Origin::Absolute => Cow::Borrowed(wall),
+            Origin::Relative =>
+                Cow::Owned(wall.shift(-self.frame.position))
+        }
+    }
+
+    // Returns an interaction, if it exists, between a world feeler and wall.
+    pub fn interaction(&self, feeler: &Segment<T>, wall: &Segment<T>)
+        -> Option<Interaction<T>>
+    {
+        let wall = self.relative_wall(wall);
+        match feeler.intersection_with(&wall, self.predicate) {
             Some(point) => {
-                let dist = feeler.point2.sub(point).mag();
-                let mut force = wall.normal.mul(dist);
-                if force.dot(feeler.unit) > 0f64 {
-                    force = force.neg();
+                let dist = (feeler.point2 - point).mag();
+                let mut force = wall.normal * dist;
+                if force.dot(feeler.unit) > T::ZERO {
+                    force = -force;
                 }
                 Some(Interaction::new(force, dist))
             },
@@ -152,8 +316,8 @@ impl Vehicle {
 
     // Return a force intended to prevent collision between the vehicle and a
     // collection of walls.
-    pub fn wall_avoidance(&self, walls: &Vec<Segment>) -> Option<Vec2D> {
-        let mut nearest: Option<Interaction> = None;
+    pub fn wall_avoidance(&self, walls: &[Segment<T>]) -> Option<Vec2D<T>> {
+        let mut nearest: Option<Interaction<T>> = None;
         for feeler in self.local_feelers.iter() {
             for wall in walls.iter() {
 
@@ -170,4 +334,125 @@ impl Vehicle {
         }
         if let Some(x) = nearest { Some(x.force) } else { None }
     }
+
+    // Returns a trace of the evaluation of `wall_avoidance` for the given
+    // walls.
+    #[cfg(feature = "std")]
+    pub fn explain_avoidance(&self, walls: &[Segment<T>]) -> Trace {
+        let mut trace = Trace::new();
+        let mut nearest: Option<(usize, Interaction<T>)> = None;
+        for (j, feeler) in self.local_feelers.iter().enumerate() {
+            for (i, wall) in walls.iter().enumerate() {
+                let relative = self.relative_wall(wall);
+                let intersection = feeler.intersection_with( &relative
+                                                           , self.predicate );
+                let point: Vec2D = match intersection {
+                    Some(point) => self.frame.absolute_point(point).cast(),
+                    None => {
+                        trace.record( Some(i), "no intersection"
+                                    , &[("feeler", j as f64)] );
+                        continue;
+                    }
+                };
+                let int = self.interaction(feeler, wall).unwrap();
+                trace.record( Some(i), "intersection"
+                            , &[ ("feeler", j as f64)
+                               , ("x", point.x), ("y", point.y)
+                               , ("distance", int.dist.to_f64()) ] );
+                if nearest.map_or(true, |(_, x)| int.dist < x.dist) {
+                    nearest = Some((i, int));
+                }
+            }
+        }
+        let force = self.wall_avoidance(walls).map(|x| x.cast());
+        trace.conclude(nearest.map(|x| x.0), force);
+        trace
+    }
+
+    // Returns the quantities of the evaluation of `wall_avoidance` that are
+    // subject to invariants. The force is the wall's normal scaled by the
+    // feeler's penetration, so is no longer than the longest feeler.
+    #[cfg(feature = "std")]
+    pub fn evaluate_avoidance(&self, walls: &[Segment<T>]) -> Evaluation {
+        let mut nearest: Option<(Vec2D<T>, T)> = None;
+        let mut bound = T::ZERO;
+        for feeler in self.local_feelers.iter() {
+            if feeler.length > bound { bound = feeler.length; }
+            for wall in walls.iter() {
+                let wall = self.relative_wall(wall);
+                let intersection = feeler.intersection_with( &wall
+                                                           , self.predicate );
+                if let Some(point) = intersection {
+                    let dist = (feeler.point2 - point).mag();
+                    if nearest.map_or(true, |(_, x)| dist < x) {
+                        nearest = Some((point, dist));
+                    }
+                }
+            }
+        }
+        let away = nearest.map(|(x, _)| {
+            (self.frame.position - self.frame.absolute_point(x)).cast()
+        });
+        Evaluation { force:  self.wall_avoidance(walls).map(|x| x.cast())
+                   , away:   away
+                   , bound:  bound.to_f64()
+                   , budget: None }
+    }
+}
+
+// Serialises the endpoints of the segment, from which the rest follows.
+#[cfg(feature = "serde")]
+impl<T: Scalar + Serialize> Serialize for Segment<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        self.to_segment().serialize(serializer)
+    }
+}
+
+// Deserialises a segment from its endpoints, which must not coincide.
+#[cfg(feature = "serde")]
+impl<'de, T: Scalar + Deserialize<'de>> Deserialize<'de> for Segment<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Segment<T>, D::Error>
+    {
+        let segment = geometry::Segment::deserialize(deserializer)?;
+        Segment::from_segment(&segment)
+            .map_err(|_| D::Error::custom("degenerate segment"))
+    }
+}
+
+// Defining parameters of a vehicle, as which it is serialised: its frame,
+// its feelers in local space and its predicate. The feelers in world space
+// follow from them.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "T: Scalar + Deserialize<'de>"))]
+struct VehicleParameters<T: Scalar> { frame:     Frame<T>
+                                    , feelers:   Vec<Segment<T>>
+                                    , predicate: Predicate }
+
+#[cfg(feature = "serde")]
+impl<T: Scalar + Serialize> Serialize for Vehicle<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        let mut state = serializer.serialize_struct("Vehicle", 3)?;
+        state.serialize_field("frame", &self.frame)?;
+        state.serialize_field("feelers", &self.feelers)?;
+        state.serialize_field("predicate", &self.predicate)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Scalar + Deserialize<'de>> Deserialize<'de> for Vehicle<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Vehicle<T>, D::Error>
+    {
+        let p = VehicleParameters::deserialize(deserializer)?;
+        let mut vehicle = Vehicle::new(p.frame, p.feelers);
+        vehicle.predicate = p.predicate;
+        Ok(vehicle)
+    }
 }
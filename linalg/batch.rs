@@ -0,0 +1,479 @@
+use super::scalar::Scalar;
+use super::transform2d::Transform2D;
+use super::vector2d::Vec2D;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use std::ops::{Add, Sub, Mul};
+
+#[cfg(feature = "simd")]
+use std::simd::{Select, Simd, StdFloat};
+#[cfg(feature = "simd")]
+use std::simd::cmp::SimdPartialOrd;
+
+// Name of the kernels that evaluate batches: portable SIMD when the `simd`
+// feature is enabled, and a scalar loop otherwise.
+#[cfg(feature = "simd")]
+pub const KERNELS: &'static str = "simd";
+#[cfg(not(feature = "simd"))]
+pub const KERNELS: &'static str = "scalar";
+
+// Number of lanes that algorithms evaluate at a time, in buffers on the
+// stack. A multiple of the lanes of every pack.
+pub const BLOCK: usize = 16;
+
+// Values on which kernels operate: a single lane, or a SIMD vector of lanes.
+// Each operation applies to every lane exactly as it applies to a scalar, so
+// kernels produce the same results whatever their pack.
+pub trait Pack: Copy + Add<Output = Self> + Sub<Output = Self>
+              + Mul<Output = Self>
+{
+    type Lane: Scalar;
+
+    const LANES: usize;
+
+    fn splat(x: Self::Lane) -> Self;
+
+    fn load(xs: &[Self::Lane]) -> Self;
+
+    fn store(self, xs: &mut [Self::Lane]);
+
+    fn sqrt(self) -> Self;
+
+    // Returns the lanes of `then` where this is below `bound`, and those of
+    // `otherwise` elsewhere.
+    fn below(self, bound: Self, then: Self, otherwise: Self) -> Self;
+}
+
+// Scalar types that batches can hold, with the pack in which their kernels
+// evaluate whole groups of lanes and the pack in which they evaluate the
+// remaining lanes one at a time.
+pub trait Lanes: Scalar {
+    type Packed: Pack<Lane = Self>;
+
+    type Single: Pack<Lane = Self>;
+}
+
+macro_rules! scalar_pack {
+    ($t:ident) => {
+        impl Pack for $t {
+            type Lane = $t;
+
+            const LANES: usize = 1;
+
+            fn splat(x: $t) -> $t { x }
+
+            fn load(xs: &[$t]) -> $t { xs[0] }
+
+            fn store(self, xs: &mut [$t]) { xs[0] = self; }
+
+            fn sqrt(self) -> $t { Scalar::sqrt(self) }
+
+            fn below(self, bound: $t, then: $t, otherwise: $t) -> $t {
+                if self < bound { then } else { otherwise }
+            }
+        }
+    }
+}
+
+scalar_pack!(f64);
+scalar_pack!(f32);
+
+#[cfg(feature = "simd")]
+macro_rules! simd_pack {
+    ($t:ident, $lanes:expr) => {
+        impl Pack for Simd<$t, $lanes> {
+            type Lane = $t;
+
+            const LANES: usize = $lanes;
+
+            fn splat(x: $t) -> Simd<$t, $lanes> { Simd::splat(x) }
+
+            fn load(xs: &[$t]) -> Simd<$t, $lanes> { Simd::from_slice(xs) }
+
+            fn store(self, xs: &mut [$t]) { self.copy_to_slice(xs); }
+
+            fn sqrt(self) -> Simd<$t, $lanes> { StdFloat::sqrt(self) }
+
+            fn below( self
+                    , bound:     Simd<$t, $lanes>
+                    , then:      Simd<$t, $lanes>
+                    , otherwise: Simd<$t, $lanes> ) -> Simd<$t, $lanes>
+            {
+                self.simd_lt(bound).select(then, otherwise)
+            }
+        }
+
+        impl Lanes for $t {
+            type Packed = Simd<$t, $lanes>;
+
+            type Single = $t;
+        }
+    }
+}
+
+#[cfg(feature = "simd")]
+simd_pack!(f64, 4);
+#[cfg(feature = "simd")]
+simd_pack!(f32, 8);
+
+#[cfg(not(feature = "simd"))]
+impl Lanes for f64 {
+    type Packed = f64;
+
+    type Single = f64;
+}
+
+#[cfg(not(feature = "simd"))]
+impl Lanes for f32 {
+    type Packed = f32;
+
+    type Single = f32;
+}
+
+// Structure-of-arrays vectors: the x and y components of each vector are
+// held in separate lanes, which kernels evaluate several at a time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vec2DBatch<T = f64> { pub xs: Vec<T>
+                               , pub ys: Vec<T> }
+
+impl<T: Lanes> Vec2DBatch<T> {
+    pub fn new() -> Vec2DBatch<T> {
+        Vec2DBatch { xs: Vec::new(), ys: Vec::new() }
+    }
+
+    pub fn from_vecs(vs: &[Vec2D<T>]) -> Vec2DBatch<T> {
+        Vec2DBatch { xs: vs.iter().map(|v| v.x).collect()
+                   , ys: vs.iter().map(|v| v.y).collect() }
+    }
+
+    pub fn push(&mut self, v: Vec2D<T>) {
+        self.xs.push(v.x);
+        self.ys.push(v.y);
+    }
+
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Vec2D<T> {
+        Vec2D::new(self.xs[i], self.ys[i])
+    }
+
+    pub fn dot(&self, v: Vec2D<T>) -> Vec<T> {
+        let mut out = vec![T::ZERO; self.len()];
+        dot(&self.xs, &self.ys, v, &mut out);
+        out
+    }
+
+    pub fn mag_sq(&self) -> Vec<T> {
+        let mut out = vec![T::ZERO; self.len()];
+        mag_sq(&self.xs, &self.ys, &mut out);
+        out
+    }
+
+    pub fn mag(&self) -> Vec<T> {
+        let mut out = vec![T::ZERO; self.len()];
+        mag(&self.xs, &self.ys, &mut out);
+        out
+    }
+
+    pub fn sub(&self, v: Vec2D<T>) -> Vec2DBatch<T> {
+        let mut out = Vec2DBatch { xs: vec![T::ZERO; self.len()]
+                                 , ys: vec![T::ZERO; self.len()] };
+        sub(&self.xs, &self.ys, v, &mut out.xs, &mut out.ys);
+        out
+    }
+
+    pub fn transform(&self, t: &Transform2D<T>) -> Vec2DBatch<T> {
+        let mut out = Vec2DBatch { xs: vec![T::ZERO; self.len()]
+                                 , ys: vec![T::ZERO; self.len()] };
+        transform(t, &self.xs, &self.ys, &mut out.xs, &mut out.ys);
+        out
+    }
+}
+
+// Structure-of-arrays rigid transforms, whose translations form a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform2DBatch<T = f64> { pub cos:         Vec<T>
+                                     , pub sin:         Vec<T>
+                                     , pub translation: Vec2DBatch<T> }
+
+impl<T: Lanes> Transform2DBatch<T> {
+    pub fn new() -> Transform2DBatch<T> {
+        Transform2DBatch { cos:         Vec::new()
+                         , sin:         Vec::new()
+                         , translation: Vec2DBatch::new() }
+    }
+
+    pub fn from_transforms(ts: &[Transform2D<T>]) -> Transform2DBatch<T> {
+        let mut batch = Transform2DBatch::new();
+        for t in ts {
+            batch.push(t);
+        }
+        batch
+    }
+
+    pub fn push(&mut self, t: &Transform2D<T>) {
+        self.cos.push(t.cos);
+        self.sin.push(t.sin);
+        self.translation.push(t.translation);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cos.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Transform2D<T> {
+        Transform2D { cos:         self.cos[i]
+                    , sin:         self.sin[i]
+                    , translation: self.translation.get(i) }
+    }
+
+    pub fn transform(&self, v: Vec2D<T>) -> Vec2DBatch<T> {
+        let mut out = Vec2DBatch { xs: vec![T::ZERO; self.len()]
+                                 , ys: vec![T::ZERO; self.len()] };
+        transform_each(self, 0, v, &mut out.xs, &mut out.ys);
+        out
+    }
+}
+
+// Returns the number of the first `len` lanes that fill whole packs.
+fn packed<T: Lanes>(len: usize) -> usize {
+    len - len % T::Packed::LANES
+}
+
+fn dot_packs<P: Pack>( xs:  &[P::Lane]
+                     , ys:  &[P::Lane]
+                     , v:   Vec2D<P::Lane>
+                     , out: &mut [P::Lane] )
+{
+    let (vx, vy) = (P::splat(v.x), P::splat(v.y));
+    for i in (0..out.len()).step_by(P::LANES) {
+        let (x, y) = (P::load(&xs[i..]), P::load(&ys[i..]));
+        (x * vx + y * vy).store(&mut out[i..]);
+    }
+}
+
+// Writes the dot product of each lane and `v` to `out`.
+pub fn dot<T: Lanes>(xs: &[T], ys: &[T], v: Vec2D<T>, out: &mut [T]) {
+    let n = packed::<T>(out.len());
+    dot_packs::<T::Packed>(&xs[..n], &ys[..n], v, &mut out[..n]);
+    dot_packs::<T::Single>(&xs[n..], &ys[n..], v, &mut out[n..]);
+}
+
+fn dot_lanes_packs<P: Pack>( xs:  &[P::Lane]
+                           , ys:  &[P::Lane]
+                           , vxs: &[P::Lane]
+                           , vys: &[P::Lane]
+                           , out: &mut [P::Lane] )
+{
+    for i in (0..out.len()).step_by(P::LANES) {
+        let (x, y) = (P::load(&xs[i..]), P::load(&ys[i..]));
+        let (vx, vy) = (P::load(&vxs[i..]), P::load(&vys[i..]));
+        (x * vx + y * vy).store(&mut out[i..]);
+    }
+}
+
+// Writes the dot product of each lane of `xs` and `ys` and the corresponding
+// lane of `vxs` and `vys` to `out`.
+pub fn dot_lanes<T: Lanes>( xs:  &[T]
+                          , ys:  &[T]
+                          , vxs: &[T]
+                          , vys: &[T]
+                          , out: &mut [T] )
+{
+    let n = packed::<T>(out.len());
+    dot_lanes_packs::<T::Packed>( &xs[..n], &ys[..n], &vxs[..n], &vys[..n]
+                                , &mut out[..n] );
+    dot_lanes_packs::<T::Single>( &xs[n..], &ys[n..], &vxs[n..], &vys[n..]
+                                , &mut out[n..] );
+}
+
+fn mag_sq_packs<P: Pack>(xs: &[P::Lane], ys: &[P::Lane], out: &mut [P::Lane]) {
+    for i in (0..out.len()).step_by(P::LANES) {
+        let (x, y) = (P::load(&xs[i..]), P::load(&ys[i..]));
+        (x * x + y * y).store(&mut out[i..]);
+    }
+}
+
+// Writes the squared magnitude of each lane to `out`.
+pub fn mag_sq<T: Lanes>(xs: &[T], ys: &[T], out: &mut [T]) {
+    let n = packed::<T>(out.len());
+    mag_sq_packs::<T::Packed>(&xs[..n], &ys[..n], &mut out[..n]);
+    mag_sq_packs::<T::Single>(&xs[n..], &ys[n..], &mut out[n..]);
+}
+
+fn mag_packs<P: Pack>(xs: &[P::Lane], ys: &[P::Lane], out: &mut [P::Lane]) {
+    let epsilon = P::splat(P::Lane::EPSILON);
+    for i in (0..out.len()).step_by(P::LANES) {
+        let (x, y) = (P::load(&xs[i..]), P::load(&ys[i..]));
+        let mag = x * x + y * y;
+        mag.below(epsilon, mag, mag.sqrt()).store(&mut out[i..]);
+    }
+}
+
+// Writes the magnitude of each lane to `out`, as `Vec2D::mag` computes it.
+pub fn mag<T: Lanes>(xs: &[T], ys: &[T], out: &mut [T]) {
+    let n = packed::<T>(out.len());
+    mag_packs::<T::Packed>(&xs[..n], &ys[..n], &mut out[..n]);
+    mag_packs::<T::Single>(&xs[n..], &ys[n..], &mut out[n..]);
+}
+
+fn sub_packs<P: Pack>( xs:     &[P::Lane]
+                     , ys:     &[P::Lane]
+                     , v:      Vec2D<P::Lane>
+                     , out_xs: &mut [P::Lane]
+                     , out_ys: &mut [P::Lane] )
+{
+    let (vx, vy) = (P::splat(v.x), P::splat(v.y));
+    for i in (0..out_xs.len()).step_by(P::LANES) {
+        (P::load(&xs[i..]) - vx).store(&mut out_xs[i..]);
+        (P::load(&ys[i..]) - vy).store(&mut out_ys[i..]);
+    }
+}
+
+// Writes each lane minus `v` to `out_xs` and `out_ys`.
+pub fn sub<T: Lanes>( xs:     &[T]
+                    , ys:     &[T]
+                    , v:      Vec2D<T>
+                    , out_xs: &mut [T]
+                    , out_ys: &mut [T] )
+{
+    let n = packed::<T>(out_xs.len());
+    sub_packs::<T::Packed>( &xs[..n], &ys[..n], v
+                          , &mut out_xs[..n], &mut out_ys[..n] );
+    sub_packs::<T::Single>( &xs[n..], &ys[n..], v
+                          , &mut out_xs[n..], &mut out_ys[n..] );
+}
+
+fn sub_from_packs<P: Pack>( v:      Vec2D<P::Lane>
+                          , xs:     &[P::Lane]
+                          , ys:     &[P::Lane]
+                          , out_xs: &mut [P::Lane]
+                          , out_ys: &mut [P::Lane] )
+{
+    let (vx, vy) = (P::splat(v.x), P::splat(v.y));
+    for i in (0..out_xs.len()).step_by(P::LANES) {
+        (vx - P::load(&xs[i..])).store(&mut out_xs[i..]);
+        (vy - P::load(&ys[i..])).store(&mut out_ys[i..]);
+    }
+}
+
+// Writes `v` minus each lane to `out_xs` and `out_ys`.
+pub fn sub_from<T: Lanes>( v:      Vec2D<T>
+                         , xs:     &[T]
+                         , ys:     &[T]
+                         , out_xs: &mut [T]
+                         , out_ys: &mut [T] )
+{
+    let n = packed::<T>(out_xs.len());
+    sub_from_packs::<T::Packed>( v, &xs[..n], &ys[..n]
+                               , &mut out_xs[..n], &mut out_ys[..n] );
+    sub_from_packs::<T::Single>( v, &xs[n..], &ys[n..]
+                               , &mut out_xs[n..], &mut out_ys[n..] );
+}
+
+fn transform_packs<P: Pack>( t:      &Transform2D<P::Lane>
+                           , xs:     &[P::Lane]
+                           , ys:     &[P::Lane]
+                           , out_xs: &mut [P::Lane]
+                           , out_ys: &mut [P::Lane] )
+{
+    let (cos, sin) = (P::splat(t.cos), P::splat(t.sin));
+    let (tx, ty) = (P::splat(t.translation.x), P::splat(t.translation.y));
+    for i in (0..out_xs.len()).step_by(P::LANES) {
+        let (x, y) = (P::load(&xs[i..]), P::load(&ys[i..]));
+        (cos * x - sin * y + tx).store(&mut out_xs[i..]);
+        (sin * x + cos * y + ty).store(&mut out_ys[i..]);
+    }
+}
+
+// Writes each lane transformed by `t` to `out_xs` and `out_ys`.
+pub fn transform<T: Lanes>( t:      &Transform2D<T>
+                          , xs:     &[T]
+                          , ys:     &[T]
+                          , out_xs: &mut [T]
+                          , out_ys: &mut [T] )
+{
+    let n = packed::<T>(out_xs.len());
+    transform_packs::<T::Packed>( t, &xs[..n], &ys[..n]
+                                , &mut out_xs[..n], &mut out_ys[..n] );
+    transform_packs::<T::Single>( t, &xs[n..], &ys[n..]
+                                , &mut out_xs[n..], &mut out_ys[n..] );
+}
+
+fn transform_each_packs<P: Pack>( ts:     &Transform2DBatch<P::Lane>
+                                , start:  usize
+                                , v:      Vec2D<P::Lane>
+                                , out_xs: &mut [P::Lane]
+                                , out_ys: &mut [P::Lane] )
+{
+    let (x, y) = (P::splat(v.x), P::splat(v.y));
+    for i in (0..out_xs.len()).step_by(P::LANES) {
+        let j = start + i;
+        let (cos, sin) = (P::load(&ts.cos[j..]), P::load(&ts.sin[j..]));
+        let tx = P::load(&ts.translation.xs[j..]);
+        let ty = P::load(&ts.translation.ys[j..]);
+        (cos * x - sin * y + tx).store(&mut out_xs[i..]);
+        (sin * x + cos * y + ty).store(&mut out_ys[i..]);
+    }
+}
+
+// Writes `v` transformed by each of the `out_xs.len()` transforms of `ts`
+// from `start` to `out_xs` and `out_ys`.
+pub fn transform_each<T: Lanes>( ts:     &Transform2DBatch<T>
+                               , start:  usize
+                               , v:      Vec2D<T>
+                               , out_xs: &mut [T]
+                               , out_ys: &mut [T] )
+{
+    let n = packed::<T>(out_xs.len());
+    transform_each_packs::<T::Packed>( ts, start, v
+                                     , &mut out_xs[..n], &mut out_ys[..n] );
+    transform_each_packs::<T::Single>( ts, start + n, v
+                                     , &mut out_xs[n..], &mut out_ys[n..] );
+}
+
+fn transform_lanes_packs<P: Pack>( ts:     &Transform2DBatch<P::Lane>
+                                 , start:  usize
+                                 , xs:     &[P::Lane]
+                                 , ys:     &[P::Lane]
+                                 , out_xs: &mut [P::Lane]
+                                 , out_ys: &mut [P::Lane] )
+{
+    for i in (0..out_xs.len()).step_by(P::LANES) {
+        let j = start + i;
+        let (cos, sin) = (P::load(&ts.cos[j..]), P::load(&ts.sin[j..]));
+        let tx = P::load(&ts.translation.xs[j..]);
+        let ty = P::load(&ts.translation.ys[j..]);
+        let (x, y) = (P::load(&xs[i..]), P::load(&ys[i..]));
+        (cos * x - sin * y + tx).store(&mut out_xs[i..]);
+        (sin * x + cos * y + ty).store(&mut out_ys[i..]);
+    }
+}
+
+// Writes each lane transformed by the corresponding transform of `ts` from
+// `start` to `out_xs` and `out_ys`.
+pub fn transform_lanes<T: Lanes>( ts:     &Transform2DBatch<T>
+                                , start:  usize
+                                , xs:     &[T]
+                                , ys:     &[T]
+                                , out_xs: &mut [T]
+                                , out_ys: &mut [T] )
+{
+    let n = packed::<T>(out_xs.len());
+    transform_lanes_packs::<T::Packed>( ts, start, &xs[..n], &ys[..n]
+                                      , &mut out_xs[..n], &mut out_ys[..n] );
+    transform_lanes_packs::<T::Single>( ts, start + n, &xs[n..], &ys[n..]
+                                      , &mut out_xs[n..], &mut out_ys[n..] );
+}
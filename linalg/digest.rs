@@ -0,0 +1,48 @@
+use super::scalar::Scalar;
+use super::vector2d::Vec2D;
+
+// Offset basis and prime of the 64-bit FNV-1a hash.
+const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const PRIME: u64 = 0x100000001b3;
+
+// Bits hashed for every not-a-number, whose sign and payload differ between
+// platforms.
+const NAN_BITS: u64 = 0x7ff8000000000000;
+
+// Digest of the bits of a sequence of numbers: their 64-bit FNV-1a hash,
+// which unlike the standard library's hashers is fixed across platforms and
+// compiler versions. Comparing digests of outputs against committed values
+// checks that evaluation is bitwise reproducible.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Digest(u64);
+
+impl Digest {
+    pub fn new() -> Digest { Digest(OFFSET_BASIS) }
+
+    // Hashes the bytes of `x` in little-endian order.
+    pub fn write_u64(&mut self, x: u64) {
+        for byte in x.to_le_bytes().iter() {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(PRIME);
+        }
+    }
+
+    pub fn write_u32(&mut self, x: u32) { self.write_u64(x as u64); }
+
+    // Hashes the bits of `x` converted to `f64`, which represents every
+    // scalar exactly. Zeroes of either sign hash differently.
+    pub fn write<T: Scalar>(&mut self, x: T) {
+        let x = x.to_f64();
+        self.write_u64(if x.is_nan() { NAN_BITS } else { x.to_bits() });
+    }
+
+    pub fn write_vector<T: Scalar>(&mut self, v: Vec2D<T>) {
+        self.write(v.x);
+        self.write(v.y);
+    }
+
+    pub fn finish(&self) -> u64 { self.0 }
+}
+
+impl Default for Digest {
+    fn default() -> Digest { Digest::new() }
+}
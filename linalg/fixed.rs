@@ -0,0 +1,3 @@
+// Fixed-capacity collections, which hold obstacles without an allocator.
+// Algorithms take obstacles as slices, to which these dereference.
+pub use arrayvec::{ArrayVec, CapacityError};
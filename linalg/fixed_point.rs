@@ -0,0 +1,306 @@
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::ops::{AddAssign, SubAssign, MulAssign, DivAssign};
+
+use super::scalar::Scalar;
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Signed 32-bit fixed-point number with `FRAC` fractional bits, for targets
+// without a floating-point unit. `FRAC` is between 1 and 30. Arithmetic
+// rounds to the nearest representable value and saturates at the limits of
+// the format, which stand in for infinities: a saturated value is not
+// finite. Division by zero saturates towards the sign of the dividend.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Fixed<const FRAC: u32>(i32);
+
+// The Q16.16 format: 16 integer and 16 fractional bits, which covers
+// (-32768, 32768) in steps of about 1.5 x 10^-5.
+pub type Q16 = Fixed<16>;
+
+// Fractional bits of the angles, sines and cosines computed by CORDIC.
+const CORDIC_FRAC: u32 = 32;
+
+// Arctangents of 2^-i, for each CORDIC iteration i, with `CORDIC_FRAC`
+// fractional bits.
+const ARCTANGENTS: [i64; 32] =
+    [ 3373259426, 1991351318, 1052175346, 534100635, 268086748, 134174063
+    , 67103403, 33553749, 16777131, 8388597, 4194303, 2097152, 1048576
+    , 524288, 262144, 131072, 65536, 32768, 16384, 8192, 4096, 2048, 1024
+    , 512, 256, 128, 64, 32, 16, 8, 4, 2 ];
+
+// Reciprocal of the gain of the CORDIC iterations, with `CORDIC_FRAC`
+// fractional bits.
+const CORDIC_GAIN: i64 = 2608131496;
+
+// Pi, half pi and two pi, with `CORDIC_FRAC` fractional bits.
+const PI: i64 = 13493037705;
+const HALF_PI: i64 = 6746518852;
+const TWO_PI: i64 = 26986075409;
+
+// Fractional bits of the arguments and results of `exponential`, enough that
+// errors stay below the resolution of the format after scaling by powers of
+// two up to 2^30.
+const EXP_FRAC: u32 = 60;
+
+// The natural logarithm of two, with `EXP_FRAC` fractional bits.
+const LN_2: i128 = 799144290325165979;
+
+// Terms of the Taylor series summed by `exponential`, enough for its
+// argument to converge to `EXP_FRAC` fractional bits.
+const EXP_TERMS: i128 = 20;
+
+// Returns `x` shifted right by `shift` bits, rounded to nearest and saturated
+// to 32 bits.
+fn narrow(x: i64, shift: u32) -> i32 {
+    let rounded = match shift {
+        0 => x,
+        _ => (x + (1 << (shift - 1))) >> shift
+    };
+    if rounded > i32::MAX as i64 {
+        i32::MAX
+    } else if rounded < i32::MIN as i64 {
+        i32::MIN
+    } else {
+        rounded as i32
+    }
+}
+
+// Returns the integer square root of `n`, rounded to nearest.
+fn isqrt(n: u64) -> u64 {
+    let mut root = 0u64;
+    let mut remainder = n;
+    let mut bit = 1u64 << 62;
+    while bit > n { bit >>= 2; }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    if remainder > root { root + 1 } else { root }
+}
+
+// Returns the cosine and sine of `angle`, which has `CORDIC_FRAC` fractional
+// bits, with the same number of fractional bits.
+fn cordic_rotate(angle: i64) -> (i64, i64) {
+    let mut z = angle % TWO_PI;
+    if z > PI { z -= TWO_PI; } else if z < -PI { z += TWO_PI; }
+    let negate = z > HALF_PI || z < -HALF_PI;
+    if z > HALF_PI { z -= PI; } else if z < -HALF_PI { z += PI; }
+
+    let (mut x, mut y) = (CORDIC_GAIN, 0i64);
+    for (i, arctangent) in ARCTANGENTS.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+        if z >= 0 {
+            x -= dx; y += dy; z -= arctangent;
+        } else {
+            x += dx; y -= dy; z += arctangent;
+        }
+    }
+    if negate { (-x, -y) } else { (x, y) }
+}
+
+// Returns the angle of the vector (`x`, `y`) from the x-axis, in (-pi, pi],
+// with `CORDIC_FRAC` fractional bits. The origin has angle zero.
+fn cordic_vector(x: i64, y: i64) -> i64 {
+    if x == 0 && y == 0 { return 0; }
+    // Rotating by pi brings the vector into the right half-plane, where the
+    // iterations converge.
+    let (mut x, mut y, mut z) = match (x < 0, y < 0) {
+        (true, false) => (-x, -y, PI),
+        (true, true) => (-x, -y, -PI),
+        _ => (x, y, 0)
+    };
+    // Scaling leaves headroom for the gain whilst keeping precision when
+    // components are small.
+    x <<= 16;
+    y <<= 16;
+    for (i, arctangent) in ARCTANGENTS.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+        if y > 0 {
+            x += dx; y -= dy; z += arctangent;
+        } else {
+            x -= dx; y += dy; z -= arctangent;
+        }
+    }
+    z
+}
+
+// Returns e to the power of `x`, where both have `EXP_FRAC` fractional bits
+// and |`x`| is at most half the natural logarithm of two.
+fn exponential(x: i128) -> i64 {
+    let (mut term, mut sum) = (1i128 << EXP_FRAC, 1i128 << EXP_FRAC);
+    for i in 1..EXP_TERMS {
+        term = ((term * x) >> EXP_FRAC) / i;
+        sum += term;
+    }
+    sum as i64
+}
+
+impl<const FRAC: u32> Fixed<FRAC> {
+    // Returns the number whose underlying integer is `raw`, which is the
+    // number multiplied by 2^`FRAC`.
+    pub const fn from_raw(raw: i32) -> Fixed<FRAC> { Fixed(raw) }
+
+    pub const fn raw(self) -> i32 { self.0 }
+
+    // Returns the number with `CORDIC_FRAC` fractional bits.
+    fn widen(self) -> i64 { (self.0 as i64) << (CORDIC_FRAC - FRAC) }
+
+    // Returns the number nearest `x`, which has `CORDIC_FRAC` fractional
+    // bits.
+    fn from_wide(x: i64) -> Fixed<FRAC> {
+        Fixed(narrow(x, CORDIC_FRAC - FRAC))
+    }
+}
+
+impl<const FRAC: u32> Scalar for Fixed<FRAC> {
+    // The square root of the resolution, as for floating-point types.
+    const EPSILON: Fixed<FRAC> = Fixed(1 << (FRAC / 2));
+
+    const ZERO: Fixed<FRAC> = Fixed(0);
+
+    const ONE: Fixed<FRAC> = Fixed(1 << FRAC);
+
+    const NAME: &'static str = match FRAC {
+        8 => "q24.8",
+        12 => "q20.12",
+        16 => "q16.16",
+        20 => "q12.20",
+        24 => "q8.24",
+        _ => "fixed"
+    };
+
+    // Rounds to nearest and saturates. Not-a-number converts to zero.
+    fn from_f64(x: f64) -> Fixed<FRAC> {
+        let scaled = x * (1u64 << FRAC) as f64;
+        let rounded = if scaled < 0f64 { scaled - 0.5 } else { scaled + 0.5 };
+        Fixed(narrow(rounded as i64, 0))
+    }
+
+    fn to_f64(self) -> f64 { self.0 as f64 / (1u64 << FRAC) as f64 }
+
+    // Negative numbers have a square root of zero.
+    fn sqrt(self) -> Fixed<FRAC> {
+        if self.0 <= 0 { return Fixed(0); }
+        Fixed(narrow(isqrt((self.0 as u64) << FRAC) as i64, 0))
+    }
+
+    fn sin(self) -> Fixed<FRAC> {
+        Fixed::from_wide(cordic_rotate(self.widen()).1)
+    }
+
+    fn cos(self) -> Fixed<FRAC> {
+        Fixed::from_wide(cordic_rotate(self.widen()).0)
+    }
+
+    fn atan2(self, x: Fixed<FRAC>) -> Fixed<FRAC> {
+        Fixed::from_wide(cordic_vector(x.0 as i64, self.0 as i64))
+    }
+
+    // Splits the argument into a multiple of the natural logarithm of two,
+    // which becomes a shift, and a remainder of at most half of it, whose
+    // exponential is a Taylor series.
+    fn exp(self) -> Fixed<FRAC> {
+        let x = (self.0 as i128) << (EXP_FRAC - FRAC);
+        let k = (x + LN_2 / 2).div_euclid(LN_2);
+        if k >= 31 { return Fixed(i32::MAX); }
+        let shift = EXP_FRAC as i128 - FRAC as i128 - k;
+        if shift >= 63 { return Fixed(0); }
+        Fixed(narrow(exponential(x - k * LN_2), shift as u32))
+    }
+
+    fn abs(self) -> Fixed<FRAC> { Fixed(self.0.saturating_abs()) }
+
+    fn is_finite(self) -> bool { self.0 != i32::MAX && self.0 != i32::MIN }
+}
+
+impl<const FRAC: u32> fmt::Display for Fixed<FRAC> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_f64(), f)
+    }
+}
+
+// Serialises the number's value, which `f64` represents exactly.
+#[cfg(feature = "serde")]
+impl<const FRAC: u32> Serialize for Fixed<FRAC> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+// Deserialises a value, rounded to the nearest number of the format.
+#[cfg(feature = "serde")]
+impl<'de, const FRAC: u32> Deserialize<'de> for Fixed<FRAC> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Fixed<FRAC>, D::Error>
+    {
+        f64::deserialize(deserializer).map(Fixed::from_f64)
+    }
+}
+
+impl<const FRAC: u32> Add for Fixed<FRAC> {
+    type Output = Fixed<FRAC>;
+
+    fn add(self, other: Fixed<FRAC>) -> Fixed<FRAC> {
+        Fixed(self.0.saturating_add(other.0))
+    }
+}
+
+impl<const FRAC: u32> Sub for Fixed<FRAC> {
+    type Output = Fixed<FRAC>;
+
+    fn sub(self, other: Fixed<FRAC>) -> Fixed<FRAC> {
+        Fixed(self.0.saturating_sub(other.0))
+    }
+}
+
+impl<const FRAC: u32> Mul for Fixed<FRAC> {
+    type Output = Fixed<FRAC>;
+
+    fn mul(self, other: Fixed<FRAC>) -> Fixed<FRAC> {
+        Fixed(narrow(self.0 as i64 * other.0 as i64, FRAC))
+    }
+}
+
+impl<const FRAC: u32> Div for Fixed<FRAC> {
+    type Output = Fixed<FRAC>;
+
+    fn div(self, other: Fixed<FRAC>) -> Fixed<FRAC> {
+        if other.0 == 0 {
+            return Fixed(if self.0 < 0 { i32::MIN } else { i32::MAX });
+        }
+        let (n, d) = ((self.0 as i64) << FRAC, other.0 as i64);
+        let quotient = (n.abs() + d.abs() / 2) / d.abs();
+        let signed = if (n < 0) != (d < 0) { -quotient } else { quotient };
+        Fixed(narrow(signed, 0))
+    }
+}
+
+impl<const FRAC: u32> Neg for Fixed<FRAC> {
+    type Output = Fixed<FRAC>;
+
+    fn neg(self) -> Fixed<FRAC> { Fixed(self.0.saturating_neg()) }
+}
+
+impl<const FRAC: u32> AddAssign for Fixed<FRAC> {
+    fn add_assign(&mut self, other: Fixed<FRAC>) { *self = *self + other; }
+}
+
+impl<const FRAC: u32> SubAssign for Fixed<FRAC> {
+    fn sub_assign(&mut self, other: Fixed<FRAC>) { *self = *self - other; }
+}
+
+impl<const FRAC: u32> MulAssign for Fixed<FRAC> {
+    fn mul_assign(&mut self, other: Fixed<FRAC>) { *self = *self * other; }
+}
+
+impl<const FRAC: u32> DivAssign for Fixed<FRAC> {
+    fn div_assign(&mut self, other: Fixed<FRAC>) { *self = *self / other; }
+}
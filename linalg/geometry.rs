@@ -0,0 +1,429 @@
+use super::scalar::Scalar;
+use super::vector2d::Vec2D;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+// Queries shared by the geometric primitives. Distances are Euclidean, and
+// signed distances are negative inside a shape.
+pub trait Shape<T: Scalar = f64> {
+    // Returns the shape as an outline.
+    fn outline(&self) -> Outline<T>;
+
+    // Returns the point on the boundary of the shape nearest to `p`.
+    fn closest_point(&self, p: Vec2D<T>) -> Vec2D<T> {
+        self.outline().closest_point(p)
+    }
+
+    // Returns the distance from `p` to the boundary of the shape, negated if
+    // `p` lies inside it.
+    fn signed_distance(&self, p: Vec2D<T>) -> T {
+        self.outline().signed_distance(p)
+    }
+
+    // Returns the distance along the ray, in multiples of its direction, at
+    // which it first meets the shape. It is zero if the ray starts inside.
+    fn ray_cast(&self, ray: &Ray<T>) -> Option<T> {
+        self.outline().ray_cast(ray)
+    }
+
+    // Returns whether the shape and another share at least one point.
+    fn overlaps(&self, other: &Shape<T>) -> bool {
+        self.outline().overlaps(&other.outline())
+    }
+
+    // Returns the smallest axis-aligned box containing the shape.
+    fn bounds(&self) -> Aabb<T> {
+        self.outline().bounds()
+    }
+}
+
+// Points within `radius` of the chain of segments joining `points` in order,
+// and, if the chain is `closed`, of the region it encloses. A single point
+// describes a circle. There is always at least one point, which its fields
+// are private to ensure.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "OutlineFields<T>"))]
+pub struct Outline<T = f64> { points: Vec<Vec2D<T>>
+                            , closed: bool
+                            , radius: T }
+
+// Serialised form of an outline, which is checked to have at least one point
+// when it is read.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct OutlineFields<T> { points: Vec<Vec2D<T>>
+                        , closed: bool
+                        , radius: T }
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<OutlineFields<T>> for Outline<T> {
+    type Error = &'static str;
+
+    fn try_from(x: OutlineFields<T>) -> Result<Outline<T>, &'static str> {
+        if x.points.is_empty() { return Err("an outline needs a point"); }
+        Ok(Outline { points: x.points, closed: x.closed, radius: x.radius })
+    }
+}
+
+impl<T: Scalar> Outline<T> {
+    // Creates an outline from its points, of which there must be at least
+    // one, whether the chain is closed and its radius.
+    pub fn new(points: Vec<Vec2D<T>>, closed: bool, radius: T) -> Outline<T> {
+        assert!(!points.is_empty(), "An outline needs at least one point");
+        Outline { points: points, closed: closed, radius: radius }
+    }
+
+    // Returns the points of the chain, in order.
+    pub fn points(&self) -> &[Vec2D<T>] {
+        &self.points
+    }
+
+    // Returns whether the chain encloses a region.
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    // Returns the distance within which points belong to the outline.
+    pub fn radius(&self) -> T {
+        self.radius
+    }
+
+    // Returns the segments of the chain. A single point is a segment of zero
+    // length.
+    fn edges(&self) -> Vec<Segment<T>> {
+        let n = self.points.len();
+        let count = if self.closed && n > 2 { n } else { n - 1 };
+        if count == 0 {
+            return vec!(Segment::new(self.points[0], self.points[0]));
+        }
+        (0..count).map(|i| {
+            Segment::new(self.points[i], self.points[(i + 1) % n])
+        }).collect()
+    }
+
+    // Returns the point of the chain nearest to `p`.
+    fn nearest(&self, p: Vec2D<T>) -> Vec2D<T> {
+        let mut best = (self.points[0], (p - self.points[0]).mag_sq());
+        for edge in self.edges().iter() {
+            let q = edge.nearest(p);
+            let distance_sq = (p - q).mag_sq();
+            if distance_sq < best.1 { best = (q, distance_sq); }
+        }
+        best.0
+    }
+
+    // Returns whether `p` lies in the region enclosed by a closed chain.
+    fn encloses(&self, p: Vec2D<T>) -> bool {
+        if !self.closed || self.points.len() < 3 { return false; }
+        let mut inside = false;
+        for edge in self.edges().iter() {
+            let (a, b) = (edge.point1, edge.point2);
+            if (a.y > p.y) != (b.y > p.y) {
+                let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if p.x < x { inside = !inside; }
+            }
+        }
+        inside
+    }
+
+    pub fn closest_point(&self, p: Vec2D<T>) -> Vec2D<T> {
+        let q = self.nearest(p);
+        if self.radius == T::ZERO { return q; }
+        let away = (p - q).try_normalize().unwrap_or(Vec2D::unitx());
+        if self.encloses(p) {
+            q - away * self.radius
+        } else {
+            q + away * self.radius
+        }
+    }
+
+    pub fn signed_distance(&self, p: Vec2D<T>) -> T {
+        let distance = p.distance(self.nearest(p));
+        if self.encloses(p) {
+            -distance - self.radius
+        } else {
+            distance - self.radius
+        }
+    }
+
+    pub fn ray_cast(&self, ray: &Ray<T>) -> Option<T> {
+        if self.signed_distance(ray.origin) <= T::ZERO {
+            return Some(T::ZERO);
+        }
+        let mut first: Option<T> = None;
+        let mut hit = |t: Option<T>| if let Some(t) = t {
+            if first.map_or(true, |f| t < f) { first = Some(t); }
+        };
+        for edge in self.edges().iter() {
+            hit(ray.circle(edge.point1, self.radius));
+            hit(ray.circle(edge.point2, self.radius));
+            let offset = match (edge.point2 - edge.point1).try_normalize() {
+                Some(unit) => unit.perp() * self.radius,
+                None => continue
+            };
+            hit(ray.segment(edge.point1 + offset, edge.point2 + offset));
+            hit(ray.segment(edge.point1 - offset, edge.point2 - offset));
+        }
+        first
+    }
+
+    pub fn overlaps(&self, other: &Outline<T>) -> bool {
+        let reach = self.radius + other.radius;
+        let edges = other.edges();
+        let touching = self.edges().iter().any(|a| {
+            edges.iter().any(|b| a.distance(b) <= reach)
+        });
+        touching || self.encloses(other.points[0])
+                 || other.encloses(self.points[0])
+    }
+
+    pub fn bounds(&self) -> Aabb<T> {
+        let mut min = self.points[0];
+        let mut max = self.points[0];
+        for p in self.points.iter() {
+            if p.x < min.x { min.x = p.x; }
+            if p.y < min.y { min.y = p.y; }
+            if p.x > max.x { max.x = p.x; }
+            if p.y > max.y { max.y = p.y; }
+        }
+        let r = Vec2D::new(self.radius, self.radius);
+        Aabb::new(min - r, max + r)
+    }
+}
+
+// Defines a circle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Circle<T = f64> { pub centre: Vec2D<T>
+                           , pub radius: T }
+
+impl<T: Scalar> Circle<T> {
+    // Creates a circle from the given centre and radius.
+    pub fn new(centre: Vec2D<T>, radius: T) -> Circle<T> {
+        Circle { centre: centre, radius: radius }
+    }
+}
+
+impl<T: Scalar> Shape<T> for Circle<T> {
+    fn outline(&self) -> Outline<T> {
+        Outline { points: vec!(self.centre)
+                , closed: false
+                , radius: self.radius }
+    }
+}
+
+// Defines a line segment between two points.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Segment<T = f64> { pub point1: Vec2D<T>
+                            , pub point2: Vec2D<T> }
+
+impl<T: Scalar> Segment<T> {
+    // Creates a segment from two endpoints.
+    pub fn new(point1: Vec2D<T>, point2: Vec2D<T>) -> Segment<T> {
+        Segment { point1: point1, point2: point2 }
+    }
+
+    // Returns the point on the segment nearest to `p`. Segments shorter than
+    // the magnitude threshold are treated as their first endpoint.
+    fn nearest(&self, p: Vec2D<T>) -> Vec2D<T> {
+        let ab = self.point2 - self.point1;
+        let length_sq = ab.mag_sq();
+        if length_sq < T::EPSILON { return self.point1; }
+        let mut t = (p - self.point1).dot(ab) / length_sq;
+        if t < T::ZERO { t = T::ZERO; }
+        if t > T::ONE { t = T::ONE; }
+        self.point1 + ab * t
+    }
+
+    // Returns the shortest distance between this segment and another, which
+    // is zero if they cross.
+    fn distance(&self, other: &Segment<T>) -> T {
+        let (a1, a2) = (self.point1, self.point2);
+        let (b1, b2) = (other.point1, other.point2);
+        let o1 = orientation(a1, a2, b1);
+        let o2 = orientation(a1, a2, b2);
+        let o3 = orientation(b1, b2, a1);
+        let o4 = orientation(b1, b2, a2);
+        if o1 * o2 < T::ZERO && o3 * o4 < T::ZERO { return T::ZERO; }
+
+        let distances = [ b1.distance(self.nearest(b1))
+                        , b2.distance(self.nearest(b2))
+                        , a1.distance(other.nearest(a1))
+                        , a2.distance(other.nearest(a2)) ];
+        distances.iter().fold(distances[0], |a, &x| if x < a { x } else { a })
+    }
+}
+
+impl<T: Scalar> Shape<T> for Segment<T> {
+    fn outline(&self) -> Outline<T> {
+        Outline { points: vec!(self.point1, self.point2)
+                , closed: false
+                , radius: T::ZERO }
+    }
+}
+
+// Returns the sign of the turn from `a` to `b` to `c`.
+fn orientation<T: Scalar>(a: Vec2D<T>, b: Vec2D<T>, c: Vec2D<T>) -> T {
+    let o = (b - a).cross(c - a);
+    if o > T::ZERO { T::ONE } else if o < T::ZERO { -T::ONE } else { T::ZERO }
+}
+
+// Defines a ray from an origin along a direction, which need not be a unit
+// vector.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ray<T = f64> { pub origin:    Vec2D<T>
+                        , pub direction: Vec2D<T> }
+
+impl<T: Scalar> Ray<T> {
+    // Creates a ray from the given origin and direction.
+    pub fn new(origin: Vec2D<T>, direction: Vec2D<T>) -> Ray<T> {
+        Ray { origin: origin, direction: direction }
+    }
+
+    // Returns the point at the given distance along the ray.
+    pub fn at(&self, t: T) -> Vec2D<T> {
+        self.origin + self.direction * t
+    }
+
+    // Returns the first non-negative distance at which the ray meets the
+    // circle with the given centre and radius. Rays that miss by less than
+    // the magnitude threshold, in squared distance, graze the circle.
+    fn circle(&self, centre: Vec2D<T>, radius: T) -> Option<T> {
+        let a = self.direction.mag_sq();
+        if a < T::EPSILON { return None; }
+        let offset = self.origin - centre;
+        let nearest = -offset.dot(self.direction) / a;
+        let miss = offset + self.direction * nearest;
+        let mut h = radius * radius - miss.mag_sq();
+        if h < -T::EPSILON { return None; }
+        if h < T::ZERO { h = T::ZERO; }
+        let half = (h / a).sqrt();
+        if nearest - half >= T::ZERO {
+            Some(nearest - half)
+        } else if nearest + half >= T::ZERO {
+            Some(nearest + half)
+        } else {
+            None
+        }
+    }
+
+    // Returns the distance at which the ray crosses the segment between `a`
+    // and `b`, if it does so at a single point.
+    fn segment(&self, a: Vec2D<T>, b: Vec2D<T>) -> Option<T> {
+        let edge = b - a;
+        let denominator = self.direction.cross(edge);
+        if denominator.abs() < T::EPSILON { return None; }
+        let offset = a - self.origin;
+        let t = offset.cross(edge) / denominator;
+        let s = offset.cross(self.direction) / denominator;
+        let within = s >= T::ZERO && s <= T::ONE;
+        if t >= T::ZERO && within { Some(t) } else { None }
+    }
+}
+
+// Defines an axis-aligned box by its least and greatest corners.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Aabb<T = f64> { pub min: Vec2D<T>
+                         , pub max: Vec2D<T> }
+
+impl<T: Scalar> Aabb<T> {
+    // Creates a box from its least and greatest corners.
+    pub fn new(min: Vec2D<T>, max: Vec2D<T>) -> Aabb<T> {
+        Aabb { min: min, max: max }
+    }
+
+    // Returns whether the box contains `p`, including its boundary.
+    pub fn contains(&self, p: Vec2D<T>) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x
+            && p.y >= self.min.y && p.y <= self.max.y
+    }
+
+    // Returns whether the box and another share at least one point, which is
+    // cheaper than the general test.
+    pub fn intersects(&self, other: &Aabb<T>) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x
+            && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+}
+
+impl<T: Scalar> Shape<T> for Aabb<T> {
+    fn outline(&self) -> Outline<T> {
+        Outline { points: vec!( self.min
+                              , Vec2D::new(self.max.x, self.min.y)
+                              , self.max
+                              , Vec2D::new(self.min.x, self.max.y) )
+                , closed: true
+                , radius: T::ZERO }
+    }
+}
+
+// Defines the points within a radius of a line segment.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Capsule<T = f64> { pub segment: Segment<T>
+                            , pub radius:  T }
+
+impl<T: Scalar> Capsule<T> {
+    // Creates a capsule from the endpoints of its segment and its radius.
+    pub fn new(point1: Vec2D<T>, point2: Vec2D<T>, radius: T) -> Capsule<T> {
+        Capsule { segment: Segment::new(point1, point2), radius: radius }
+    }
+}
+
+impl<T: Scalar> Shape<T> for Capsule<T> {
+    fn outline(&self) -> Outline<T> {
+        Outline { radius: self.radius, ..self.segment.outline() }
+    }
+}
+
+// Defines a simple polygon by its vertices in order, either clockwise or
+// anticlockwise. There is always at least one vertex.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PolygonFields<T>"))]
+pub struct Polygon<T = f64> { vertices: Vec<Vec2D<T>> }
+
+// Serialised form of a polygon, which is checked to have at least one vertex
+// when it is read.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct PolygonFields<T> { vertices: Vec<Vec2D<T>> }
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<PolygonFields<T>> for Polygon<T> {
+    type Error = &'static str;
+
+    fn try_from(x: PolygonFields<T>) -> Result<Polygon<T>, &'static str> {
+        if x.vertices.is_empty() { return Err("a polygon needs a vertex"); }
+        Ok(Polygon { vertices: x.vertices })
+    }
+}
+
+impl<T: Scalar> Polygon<T> {
+    // Creates a polygon from its vertices, of which there must be at least
+    // one.
+    pub fn new(vertices: Vec<Vec2D<T>>) -> Polygon<T> {
+        assert!(!vertices.is_empty(), "A polygon needs at least one vertex");
+        Polygon { vertices: vertices }
+    }
+
+    // Returns the vertices of the polygon, in order.
+    pub fn vertices(&self) -> &[Vec2D<T>] {
+        &self.vertices
+    }
+}
+
+impl<T: Scalar> Shape<T> for Polygon<T> {
+    fn outline(&self) -> Outline<T> {
+        Outline { points: self.vertices.clone()
+                , closed: true
+                , radius: T::ZERO }
+    }
+}
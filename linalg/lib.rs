@@ -1,2 +1,35 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+// Without `std`, `core` stands in for it, so modules keep their `std` paths,
+// and vectors come from `alloc`.
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(any(not(feature = "std"), feature = "deterministic"))]
+extern crate libm;
+extern crate arrayvec;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+
+#[cfg(test)]
+extern crate proptest;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+pub mod scalar;
 pub mod vector2d;
 pub mod matrix2d;
+pub mod transform2d;
+pub mod batch;
+pub mod geometry;
+pub mod fixed;
+pub mod fixed_point;
+pub mod origin;
+pub mod digest;
+mod tests;
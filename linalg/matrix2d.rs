@@ -1,22 +1,26 @@
+use super::scalar::Scalar;
 use super::vector2d::Vec2D;
 
-#[derive(Clone)]
-pub struct Mat2D { m: [[f64; 3]; 3] }
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Mat2D<T = f64> { m: [[T; 3]; 3] }
 
-impl Mat2D {
-    pub fn new(values: [[f64; 3]; 3]) -> Mat2D {
+impl<T: Scalar> Mat2D<T> {
+    pub fn new(values: [[T; 3]; 3]) -> Mat2D<T> {
         Mat2D { m: values }
     }
 
-    pub fn identity() -> Mat2D {
-        Mat2D::new( [ [ 1f64, 0f64, 0f64]
-                    , [ 0f64, 1f64, 0f64]
-                    , [ 0f64, 0f64, 1f64] ] )
+    pub fn identity() -> Mat2D<T> {
+        let (o, i) = (T::ZERO, T::ONE);
+        Mat2D::new( [ [ i, o, o ]
+                    , [ o, i, o ]
+                    , [ o, o, i ] ] )
     }
 
-    pub fn rotation(rad: f64) -> Mat2D {
-        let c = f64::cos(rad);
-        let s = f64::sin(rad);
+    pub fn rotation(rad: T) -> Mat2D<T> {
+        let c = rad.cos();
+        let s = rad.sin();
         let mut result = Mat2D::identity();
         result.m[0][0] = c;
         result.m[1][0] = -s;
@@ -25,33 +29,33 @@ impl Mat2D {
         result
     }
 
-    pub fn scalation(v: Vec2D) -> Mat2D {
+    pub fn scalation(v: Vec2D<T>) -> Mat2D<T> {
         let mut result = Mat2D::identity();
         result.m[0][0] = v.x;
         result.m[1][1] = v.y;
         result
     }
 
-    pub fn translation(v: Vec2D) -> Mat2D {
+    pub fn translation(v: Vec2D<T>) -> Mat2D<T> {
         let mut result = Mat2D::identity();
         result.m[2][0] = v.x;
         result.m[2][1] = v.y;
         result
     }
 
-    pub fn turn(&self, rad: f64) -> Mat2D {
+    pub fn turn(&self, rad: T) -> Mat2D<T> {
         self.mul(Mat2D::rotation(rad))
     }
 
-    pub fn scale(&self, v: Vec2D) -> Mat2D {
+    pub fn scale(&self, v: Vec2D<T>) -> Mat2D<T> {
         self.mul(Mat2D::scalation(v))
     }
 
-    pub fn shift(&self, v: Vec2D) -> Mat2D {
+    pub fn shift(&self, v: Vec2D<T>) -> Mat2D<T> {
         self.mul(Mat2D::translation(v))
     }
 
-    pub fn mul(&self, m: Mat2D) -> Mat2D {
+    pub fn mul(&self, m: Mat2D<T>) -> Mat2D<T> {
         let m11 = self.m[0][0] * m.m[0][0]
                 + self.m[0][1] * m.m[1][0]
                 + self.m[0][2] * m.m[2][0];
@@ -91,9 +95,53 @@ impl Mat2D {
         Mat2D::new([ [ m11, m21, m31 ], [ m12, m22, m32 ], [ m13, m23, m33 ] ])
     }
 
-    pub fn transform(&self, v: Vec2D) -> Vec2D {
+    pub fn determinant(&self) -> T {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn transpose(&self) -> Mat2D<T> {
+        let mut result = Mat2D::identity();
+        for i in 0..3 {
+            for j in 0..3 {
+                result.m[i][j] = self.m[j][i];
+            }
+        }
+        result
+    }
+
+    pub fn inverse(&self) -> Option<Mat2D<T>> {
+        let det = self.determinant();
+        if det == T::ZERO || !det.is_finite() { return None; }
+
+        // Each entry is the cofactor of the transposed entry, divided by the
+        // determinant.
+        let m = &self.m;
+        let cofactor = |i: usize, j: usize| {
+            let (r1, r2) = ((i + 1) % 3, (i + 2) % 3);
+            let (c1, c2) = ((j + 1) % 3, (j + 2) % 3);
+            m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+        };
+        let mut result = Mat2D::identity();
+        for i in 0..3 {
+            for j in 0..3 {
+                result.m[i][j] = cofactor(j, i) / det;
+            }
+        }
+        Some(result)
+    }
+
+    pub fn transform(&self, v: Vec2D<T>) -> Vec2D<T> {
         let x = self.m[0][0] * v.x + self.m[1][0] * v.y + self.m[2][0];
         let y = self.m[0][1] * v.x + self.m[1][1] * v.y + self.m[2][1];
         Vec2D { x: x, y: y }
     }
+
+    pub fn transform_vector(&self, v: Vec2D<T>) -> Vec2D<T> {
+        let x = self.m[0][0] * v.x + self.m[1][0] * v.y;
+        let y = self.m[0][1] * v.x + self.m[1][1] * v.y;
+        Vec2D { x: x, y: y }
+    }
 }
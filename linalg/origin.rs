@@ -0,0 +1,20 @@
+// Origins about which algorithms transform world coordinates. Absolute
+// evaluation transforms them directly, whilst relative evaluation first
+// translates them by the vehicle's position, so does not lose precision far
+// from the world origin.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Origin { Absolute
+                , Relative }
+
+impl Origin {
+    // Returns the name of the origin, which labels the variant of algorithms
+    // that evaluate about it.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Origin::Absolute => "absolute",
+            Origin::Relative => "relative"
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::ops::{AddAssign, SubAssign, MulAssign, DivAssign};
+
+// Number types over which vectors, matrices and algorithms are generic:
+// floating-point types and fixed-point formats. Values are converted through
+// `f64`, which represents every `f32` and every fixed-point value exactly.
+pub trait Scalar: Copy + Debug + Display + PartialEq + PartialOrd
+                + Add<Output = Self> + Sub<Output = Self>
+                + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+                + AddAssign + SubAssign + MulAssign + DivAssign
+                + 'static
+{
+    // Threshold below which squared magnitudes, lengths and determinants are
+    // treated as zero: about the square root of the type's machine epsilon.
+    const EPSILON: Self;
+
+    const ZERO: Self;
+
+    const ONE: Self;
+
+    // Name of the type, which labels the variant of algorithms that evaluate
+    // in it.
+    const NAME: &'static str;
+
+    fn from_f64(x: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    fn sqrt(self) -> Self;
+
+    fn sin(self) -> Self;
+
+    fn cos(self) -> Self;
+
+    fn atan2(self, x: Self) -> Self;
+
+    fn exp(self) -> Self;
+
+    fn abs(self) -> Self;
+
+    fn is_finite(self) -> bool;
+}
+
+// Calls the standard library's method for the float type `$t`, or without
+// `std` or in deterministic mode, the libm function `$libm`. The standard
+// library defers to the platform's libm, whose results differ between
+// platforms in the last bits.
+#[cfg(all(feature = "std", not(feature = "deterministic")))]
+macro_rules! math {
+    ($t:ident, $method:ident, $libm:ident, $($x:expr),*) => {
+        $t::$method($($x),*)
+    }
+}
+#[cfg(any(not(feature = "std"), feature = "deterministic"))]
+macro_rules! math {
+    ($t:ident, $method:ident, $libm:ident, $($x:expr),*) => {
+        ::libm::$libm($($x),*)
+    }
+}
+
+// Implements `Scalar` for `$t`, whose libm functions are named by the
+// remaining arguments.
+macro_rules! scalar {
+    ( $t:ident, $epsilon:expr
+    , $sqrt:ident, $sin:ident, $cos:ident, $atan2:ident, $exp:ident
+    , $abs:ident ) => {
+        impl Scalar for $t {
+            const EPSILON: $t = $epsilon;
+
+            const ZERO: $t = 0 as $t;
+
+            const ONE: $t = 1 as $t;
+
+            const NAME: &'static str = stringify!($t);
+
+            fn from_f64(x: f64) -> $t { x as $t }
+
+            fn to_f64(self) -> f64 { self as f64 }
+
+            fn sqrt(self) -> $t { math!($t, sqrt, $sqrt, self) }
+
+            fn sin(self) -> $t { math!($t, sin, $sin, self) }
+
+            fn cos(self) -> $t { math!($t, cos, $cos, self) }
+
+            fn atan2(self, x: $t) -> $t { math!($t, atan2, $atan2, self, x) }
+
+            fn exp(self) -> $t { math!($t, exp, $exp, self) }
+
+            fn abs(self) -> $t { math!($t, abs, $abs, self) }
+
+            fn is_finite(self) -> bool { $t::is_finite(self) }
+        }
+    }
+}
+
+scalar!(f64, 1e-8, sqrt, sin, cos, atan2, exp, fabs);
+scalar!(f32, 1e-4, sqrtf, sinf, cosf, atan2f, expf, fabsf);
@@ -0,0 +1,624 @@
+#![cfg(test)]
+
+use super::batch::{self, Transform2DBatch, Vec2DBatch};
+use super::geometry::{Aabb, Capsule, Circle, Polygon, Ray, Segment, Shape};
+use super::matrix2d::Mat2D;
+use super::scalar::Scalar;
+use super::transform2d::Transform2D;
+use super::vector2d::Vec2D;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+use std::f64::consts::PI;
+
+// Returns the configuration of every property test in this file. Proptest
+// would save the seeds of failing cases in the directory above a crate whose
+// `lib.rs` is not under `src`, shared with its neighbours, so they are saved
+// beside this file as `tests.proptest-regressions` instead.
+fn config() -> ProptestConfig {
+    let saved = FileFailurePersistence::WithSource("proptest-regressions");
+    ProptestConfig { failure_persistence: Some(Box::new(saved))
+                   , .. ProptestConfig::default() }
+}
+
+// Defines the tests for the scalar type `$t` in the module `$name`, comparing
+// transformed points to within the relative tolerance `$tolerance`.
+macro_rules! tests { ($name:ident, $t:ident, $tolerance:expr) => { mod $name {
+use super::*;
+
+type T = $t;
+
+// Relative tolerance of comparisons between transformed points.
+const TOLERANCE: f64 = $tolerance;
+
+// Returns the vector with the given coordinates.
+fn vector(x: f64, y: f64) -> Vec2D<T> {
+    Vec2D::new(T::from_f64(x), T::from_f64(y))
+}
+
+// Returns a strategy producing angles over two full turns.
+fn angle() -> BoxedStrategy<T> {
+    (-2f64 * PI..2f64 * PI).prop_map(T::from_f64).boxed()
+}
+
+// Returns a strategy producing points and translations.
+fn point() -> BoxedStrategy<Vec2D<T>> {
+    (-1e3f64..1e3f64, -1e3f64..1e3f64).prop_map(|(x, y)| vector(x, y))
+                                      .boxed()
+}
+
+// Returns a strategy producing up to twenty points, some shorter than the
+// magnitude threshold, so that batches include whole packs and remainders.
+fn points() -> BoxedStrategy<Vec<Vec2D<T>>> {
+    let tiny = (-1e-4f64..1e-4f64, -1e-4f64..1e-4f64)
+        .prop_map(|(x, y)| vector(x, y));
+    prop::collection::vec(prop_oneof![point(), tiny], 0..20).boxed()
+}
+
+// Returns a strategy producing scalars in the given range.
+fn scalar(low: f64, high: f64) -> BoxedStrategy<T> {
+    (low..high).prop_map(T::from_f64).boxed()
+}
+
+// Returns a strategy producing non-degenerate scale factors of either sign.
+fn scale() -> BoxedStrategy<Vec2D<T>> {
+    let factor = || (1e-2f64..1e2f64, any::<bool>())
+        .prop_map(|(x, negative)| if negative { -x } else { x });
+    (factor(), factor()).prop_map(|(x, y)| vector(x, y)).boxed()
+}
+
+// Returns a strategy producing matrices composed of a rotation, a scale and a
+// translation.
+fn matrix() -> BoxedStrategy<Mat2D<T>> {
+    (angle(), scale(), point()).prop_map(|(a, s, t)| {
+        Mat2D::rotation(a).scale(s).shift(t)
+    }).boxed()
+}
+
+// Returns a strategy producing rigid transforms composed of a rotation and a
+// translation.
+fn rigid() -> BoxedStrategy<Transform2D<T>> {
+    (angle(), point()).prop_map(|(a, t)| Transform2D::new(a, t)).boxed()
+}
+
+// Returns a strategy producing points near the origin, where shapes are
+// placed.
+fn nearby() -> BoxedStrategy<Vec2D<T>> {
+    (-10f64..10f64, -10f64..10f64).prop_map(|(x, y)| vector(x, y)).boxed()
+}
+
+// Returns one shape of each kind built from two points and a radius.
+fn shapes(p: Vec2D<T>, q: Vec2D<T>, r: T) -> Vec<Box<Shape<T>>> {
+    let min = Vec2D::new(if p.x < q.x { p.x } else { q.x }
+                        , if p.y < q.y { p.y } else { q.y });
+    let max = Vec2D::new(if p.x < q.x { q.x } else { p.x }
+                        , if p.y < q.y { q.y } else { p.y });
+    vec!( Box::new(Circle::new(p, r)) as Box<Shape<T>>
+        , Box::new(Segment::new(p, q))
+        , Box::new(Capsule::new(p, q, r))
+        , Box::new(Aabb::new(min, max))
+        , Box::new(Polygon::new(vec!(p, q, p + (q - p).perp()))) )
+}
+
+// Returns whether two points are equal to within the tolerance, relative to
+// the given magnitude.
+fn close(a: Vec2D<T>, b: Vec2D<T>, magnitude: f64) -> bool {
+    a.sub(b).mag().to_f64() <= TOLERANCE * (1f64 + magnitude)
+}
+
+// Returns whether two matrices transform the origin and unit vectors (and
+// therefore all points) to the same positions.
+fn same_transform(a: &Mat2D<T>, b: &Mat2D<T>, magnitude: f64) -> bool {
+    [Vec2D::zero(), Vec2D::unitx(), Vec2D::unity()].iter().all(|&v| {
+        close(a.transform(v), b.transform(v), magnitude)
+    })
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn mul_is_associative(a in matrix(), b in matrix(), c in matrix()) {
+        let left = a.mul(b.clone()).mul(c.clone());
+        let right = a.mul(b.mul(c));
+        prop_assert!(same_transform(&left, &right, 1e7));
+    }
+
+    #[test]
+    fn turn_equals_mul_by_rotation(m in matrix(), a in angle()) {
+        prop_assert!(same_transform( &m.turn(a)
+                                   , &m.mul(Mat2D::rotation(a))
+                                   , 1e3 ));
+    }
+
+    #[test]
+    fn turns_compose_to_rotation(a in angle(), b in angle()) {
+        let turned = Mat2D::identity().turn(a).turn(b);
+        prop_assert!(same_transform(&turned, &Mat2D::rotation(a + b), 1f64));
+    }
+
+    #[test]
+    fn rotation_preserves_magnitude(a in angle(), b in angle(),
+                                    r in scalar(0f64, 1e3))
+    {
+        let rotated = Mat2D::rotation(a).transform(Vec2D::polar(b, r));
+        prop_assert!(close(rotated, Vec2D::polar(a + b, r), r.to_f64()));
+    }
+
+    #[test]
+    fn composed_transform_is_sequential(a in matrix(), b in matrix(),
+                                        v in point())
+    {
+        let composed = a.mul(b.clone()).transform(v);
+        let sequential = b.transform(a.transform(v));
+        prop_assert!(close(composed, sequential, 1e7));
+    }
+
+    #[test]
+    fn inverse_steps_round_trip(a in angle(), s in scale(), t in point(),
+                                v in point())
+    {
+        let inverse_scale = Vec2D::new(T::ONE / s.x, T::ONE / s.y);
+        let forward = Mat2D::identity().scale(s).turn(a).shift(t);
+        let inverse = Mat2D::identity().shift(t.neg())
+                                       .turn(-a)
+                                       .scale(inverse_scale);
+        let round_trip = inverse.transform(forward.transform(v));
+        prop_assert!(close(round_trip, v, 1e5));
+    }
+
+    #[test]
+    fn vectors_ignore_translation(m in matrix(), v in point()) {
+        let difference = m.transform(v).sub(m.transform(Vec2D::zero()));
+        prop_assert!(close(m.transform_vector(v), difference, 1e7));
+    }
+
+    #[test]
+    fn operators_match_methods(a in point(), b in point(),
+                               s in scalar(-1e3, 1e3))
+    {
+        prop_assert_eq!(a + b, a.add(b));
+        prop_assert_eq!(a - b, a.sub(b));
+        prop_assert_eq!(a * s, a.mul(s));
+        prop_assert_eq!(s * a, a.mul(s));
+        prop_assert_eq!(-a, a.neg());
+        let mut c = a;
+        c += b;
+        c -= a;
+        c *= s;
+        prop_assert_eq!(c, (a + b - a) * s);
+    }
+
+    #[test]
+    fn normalized_vectors_are_unit(v in point()) {
+        match v.try_normalize() {
+            Some(n) => {
+                let length = v.length();
+                prop_assert!((n.length().to_f64() - 1f64).abs() <= TOLERANCE);
+                prop_assert!(close(n * length, v, length.to_f64()));
+            },
+            None => prop_assert!(v.length() < T::EPSILON)
+        }
+    }
+
+    #[test]
+    fn rotate_matches_rotation(a in angle(), v in point()) {
+        prop_assert!(close(v.rotate(a), Mat2D::rotation(a).transform(v), 1e3));
+    }
+
+    #[test]
+    fn lerp_interpolates(a in point(), b in point(), t in scalar(0f64, 1f64)) {
+        prop_assert!(a.lerp(b, T::ZERO).approx_eq(a, T::ZERO));
+        prop_assert!(close(a.lerp(b, T::ONE), b, 1e3));
+        let d = a.distance(a.lerp(b, t)) + b.distance(a.lerp(b, t));
+        prop_assert!((d - a.distance(b)).to_f64().abs() <= TOLERANCE * 1e3);
+    }
+
+    #[test]
+    fn cross_is_dot_with_perpendicular(a in point(), b in point()) {
+        prop_assert_eq!(a.cross(b), a.perp().dot(b));
+        prop_assert_eq!(a.cross(b), -b.cross(a));
+    }
+
+    #[test]
+    fn projection_leaves_perpendicular_remainder(a in point(), b in point()) {
+        prop_assume!(b.length() > T::ONE);
+        let remainder = a - a.project(b);
+        prop_assert!(remainder.dot(b).to_f64().abs() <= TOLERANCE * 1e6);
+    }
+
+    #[test]
+    fn reflection_preserves_length(v in point(), a in angle()) {
+        let normal = Vec2D::polar(a, T::ONE);
+        let reflected = v.reflect(normal);
+        let change = (reflected.length() - v.length()).to_f64();
+        prop_assert!(change.abs() <= TOLERANCE * 1e3);
+        prop_assert!(close(reflected.reflect(normal), v, 1e3));
+        let sum = (reflected.dot(normal) + v.dot(normal)).to_f64();
+        prop_assert!(sum.abs() <= TOLERANCE * 1e3);
+    }
+
+    #[test]
+    fn determinant_is_product_of_scales(a in angle(), s in scale(),
+                                        t in point())
+    {
+        let m = Mat2D::rotation(a).scale(s).shift(t);
+        let expected = (s.x * s.y).to_f64();
+        let error = m.determinant().to_f64() - expected;
+        prop_assert!(error.abs() <= TOLERANCE * (1f64 + expected.abs()));
+    }
+
+    #[test]
+    fn transpose_is_an_involution(m in matrix(), v in point()) {
+        let twice = m.transpose().transpose();
+        prop_assert_eq!(twice.transform(v), m.transform(v));
+    }
+
+    #[test]
+    fn rotation_inverse_is_transpose(a in angle(), v in point()) {
+        let rotation = Mat2D::rotation(a);
+        prop_assert!(same_transform( &rotation.inverse().unwrap()
+                                   , &rotation.transpose()
+                                   , 1f64 ));
+        prop_assert!(close(rotation.transpose().transform(v),
+                           Mat2D::rotation(-a).transform(v), 1e3));
+    }
+
+    #[test]
+    fn inverse_round_trips(m in matrix(), v in point()) {
+        let inverse = m.inverse().unwrap();
+        prop_assert!(close(inverse.transform(m.transform(v)), v, 1e7));
+        prop_assert!(same_transform(&m.mul(inverse), &Mat2D::identity(), 1e7));
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse(m in matrix()) {
+        let flattened = m.scale(Vec2D::new(T::ONE, T::ZERO));
+        prop_assert!(flattened.inverse().is_none());
+    }
+
+    #[test]
+    fn rigid_matches_matrix(t in rigid(), u in rigid(), a in angle(),
+                            v in point())
+    {
+        let matrix = t.to_matrix();
+        prop_assert!(close(t.transform(v), matrix.transform(v), 1e3));
+        prop_assert!(close( t.transform_vector(v)
+                          , matrix.transform_vector(v)
+                          , 1e3 ));
+        prop_assert!(same_transform( &t.turn(a).shift(v).to_matrix()
+                                   , &matrix.turn(a).shift(v)
+                                   , 1e3 ));
+        prop_assert!(same_transform( &t.mul(u).to_matrix()
+                                   , &matrix.mul(u.to_matrix())
+                                   , 1e3 ));
+    }
+
+    #[test]
+    fn rigid_inverse_round_trips(t in rigid(), v in point()) {
+        let inverse = t.inverse();
+        prop_assert!(close(inverse.transform(t.transform(v)), v, 1e3));
+        prop_assert!(same_transform( &inverse.to_matrix()
+                                   , &t.to_matrix().inverse().unwrap()
+                                   , 1e3 ));
+    }
+
+    #[test]
+    fn rigid_composition_is_sequential(t in rigid(), u in rigid(),
+                                       v in point())
+    {
+        let composed = t.mul(u).transform(v);
+        prop_assert!(close(composed, u.transform(t.transform(v)), 1e3));
+    }
+
+    #[test]
+    fn closest_points_lie_on_boundaries(p in nearby(), q in nearby(),
+                                        r in scalar(0.1f64, 5f64),
+                                        x in nearby())
+    {
+        let tolerance = T::from_f64(TOLERANCE * 1e2);
+        for shape in shapes(p, q, r).iter() {
+            let closest = shape.closest_point(x);
+            let distance = shape.signed_distance(x).abs();
+            prop_assert!((distance - x.distance(closest)).abs() <= tolerance);
+            prop_assert!(shape.signed_distance(closest).abs() <= tolerance);
+            let bounds = shape.bounds();
+            let margin = Vec2D::new(tolerance, tolerance);
+            prop_assert!(Aabb::new(bounds.min - margin, bounds.max + margin)
+                             .contains(closest));
+        }
+    }
+
+    #[test]
+    fn rays_to_closest_points_hit_them(p in nearby(), q in nearby(),
+                                       r in scalar(0.1f64, 5f64),
+                                       x in nearby(), a in angle())
+    {
+        let tolerance = T::from_f64(TOLERANCE * 1e2);
+        for shape in shapes(p, q, r).iter() {
+            let distance = shape.signed_distance(x);
+            if distance <= T::ZERO {
+                let ray = Ray::new(x, Vec2D::polar(a, T::ONE));
+                prop_assert_eq!(shape.ray_cast(&ray), Some(T::ZERO));
+            }
+            if distance <= tolerance { continue; }
+            let ray = Ray::new(x, shape.closest_point(x) - x);
+            let t = shape.ray_cast(&ray);
+            prop_assert!(t.is_some());
+            prop_assert!((t.unwrap() - T::ONE).abs() <= tolerance);
+            let stray = Ray::new(x, Vec2D::polar(a, T::ONE));
+            if let Some(t) = shape.ray_cast(&stray) {
+                let hit = shape.signed_distance(stray.at(t));
+                prop_assert!(hit.abs() <= tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn overlaps_match_signed_distances(p in nearby(), q in nearby(),
+                                       r in scalar(0.1f64, 5f64),
+                                       c in nearby(),
+                                       radius in scalar(0f64, 5f64))
+    {
+        let tolerance = T::from_f64(TOLERANCE * 1e2);
+        let circle = Circle::new(c, radius);
+        for shape in shapes(p, q, r).iter() {
+            let gap = shape.signed_distance(c) - radius;
+            if gap.abs() <= tolerance { continue; }
+            prop_assert_eq!(circle.overlaps(&**shape), gap < T::ZERO);
+            prop_assert_eq!(shape.overlaps(&circle), gap < T::ZERO);
+        }
+    }
+
+    #[test]
+    fn batch_kernels_match_vectors(vs in points(), v in point()) {
+        let batch = Vec2DBatch::from_vecs(&vs);
+        let (dots, mags_sq, mags) = (batch.dot(v), batch.mag_sq(), batch.mag());
+        let differences = batch.sub(v);
+        let mut from = Vec2DBatch { xs: vec![T::ZERO; vs.len()]
+                                  , ys: vec![T::ZERO; vs.len()] };
+        batch::sub_from(v, &batch.xs, &batch.ys, &mut from.xs, &mut from.ys);
+        let mut lanes = vec![T::ZERO; vs.len()];
+        batch::dot_lanes( &batch.xs, &batch.ys, &from.xs, &from.ys
+                        , &mut lanes );
+        for (i, u) in vs.iter().enumerate() {
+            prop_assert_eq!(batch.get(i), *u);
+            prop_assert_eq!(dots[i], u.dot(v));
+            prop_assert_eq!(mags_sq[i], u.mag_sq());
+            prop_assert_eq!(mags[i], u.mag());
+            prop_assert_eq!(differences.get(i), *u - v);
+            prop_assert_eq!(from.get(i), v - *u);
+            prop_assert_eq!(lanes[i], u.dot(v - *u));
+        }
+    }
+
+    #[test]
+    fn batch_transforms_match_transforms(vs in points(), t in rigid(),
+                                         ts in prop::collection::vec(rigid(),
+                                                                     20))
+    {
+        let batch = Vec2DBatch::from_vecs(&vs);
+        let transformed = batch.transform(&t);
+        let transforms = Transform2DBatch::from_transforms(&ts[..vs.len()]);
+        let each = transforms.transform(t.translation);
+        let mut lanes = Vec2DBatch { xs: vec![T::ZERO; vs.len()]
+                                   , ys: vec![T::ZERO; vs.len()] };
+        batch::transform_lanes( &transforms, 0, &batch.xs, &batch.ys
+                              , &mut lanes.xs, &mut lanes.ys );
+        for (i, u) in vs.iter().enumerate() {
+            prop_assert_eq!(transforms.get(i), ts[i]);
+            prop_assert_eq!(transformed.get(i), t.transform(*u));
+            prop_assert_eq!(each.get(i), ts[i].transform(t.translation));
+            prop_assert_eq!(lanes.get(i), ts[i].transform(*u));
+        }
+    }
+}
+} } }
+
+tests!(double, f64, 1e-9);
+tests!(single, f32, 1e-3);
+
+// Tests of the fixed-point format, whose operations are compared with double
+// precision on the values it represents exactly.
+mod fixed_point {
+use super::*;
+use super::super::fixed_point::Q16;
+
+// Resolution of the format.
+const RESOLUTION: f64 = 1f64 / 65536f64;
+
+// Returns values within the given bounds, in the format.
+fn value(low: f64, high: f64) -> BoxedStrategy<Q16> {
+    (low..high).prop_map(Q16::from_f64).boxed()
+}
+
+// Returns angles, in the format.
+fn angle() -> BoxedStrategy<Q16> { value(-2f64 * PI, 2f64 * PI) }
+
+// Returns whether `a` is within `ulps` steps of the format of `expected`.
+fn near(a: Q16, expected: f64, ulps: f64) -> bool {
+    (a.to_f64() - expected).abs() <= ulps * RESOLUTION
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn conversions_round_to_nearest(x in -3e4f64..3e4f64) {
+        prop_assert!(near(Q16::from_f64(x), x, 0.5));
+    }
+
+    #[test]
+    fn arithmetic_rounds_to_nearest(a in value(-1e2, 1e2),
+                                    b in value(-1e2, 1e2)) {
+        let (x, y) = (a.to_f64(), b.to_f64());
+        prop_assert_eq!((a + b).to_f64(), x + y);
+        prop_assert_eq!((a - b).to_f64(), x - y);
+        prop_assert!(near(a * b, x * y, 0.5));
+        prop_assume!(y.abs() >= 1e-2);
+        prop_assert!(near(a / b, x / y, 0.5));
+    }
+
+    #[test]
+    fn square_roots_round_to_nearest(a in value(0f64, 3e4)) {
+        prop_assert!(near(a.sqrt(), a.to_f64().sqrt(), 0.5));
+    }
+
+    #[test]
+    fn trigonometry_matches_double(a in angle()) {
+        prop_assert!(near(a.sin(), a.to_f64().sin(), 2f64));
+        prop_assert!(near(a.cos(), a.to_f64().cos(), 2f64));
+    }
+
+    #[test]
+    fn arctangents_match_double(y in value(-1e2, 1e2), x in value(-1e2, 1e2)) {
+        let expected = y.to_f64().atan2(x.to_f64());
+        prop_assert!(near(y.atan2(x), expected, 2f64));
+    }
+
+    #[test]
+    fn exponentials_match_double(a in value(-20f64, 10f64)) {
+        let expected = a.to_f64().exp();
+        prop_assert!(near(a.exp(), expected, 0.5));
+    }
+
+    #[test]
+    fn rotation_preserves_magnitude(a in angle(), x in value(-1e2, 1e2),
+                                    y in value(-1e2, 1e2)) {
+        let v = Vec2D::new(x, y);
+        let rotated = v.rotate(a);
+        let (before, after) = (v.mag().to_f64(), rotated.mag().to_f64());
+        prop_assert!((before - after).abs() <= 1e-4 * (1f64 + before));
+    }
+}
+
+#[test]
+fn overflow_saturates() {
+    let large = Q16::from_f64(3e4);
+    assert!(!(large * large).is_finite());
+    assert!(!(-large * large).is_finite());
+    assert!(!(large + large).is_finite());
+    assert_eq!((large * large).to_f64(), (large + large).to_f64());
+    assert!(!(Q16::ONE / Q16::ZERO).is_finite());
+    assert!(-Q16::ONE / Q16::ZERO < Q16::ZERO);
+    assert!(large.is_finite());
+    assert!(!Q16::from_f64(11f64).exp().is_finite());
+    assert_eq!((-large).exp(), Q16::ZERO);
+    assert_eq!(Q16::from_f64(1e9), Q16::from_f64(::std::f64::INFINITY));
+    assert_eq!(Q16::from_f64(::std::f64::NAN), Q16::ZERO);
+}
+}
+
+// Tests of the serialised representations of vectors, matrices, transforms,
+// origins, shapes and fixed-point numbers.
+#[cfg(feature = "serde")]
+mod serialisation {
+use super::*;
+use super::super::fixed_point::Q16;
+use super::super::geometry::Outline;
+use super::super::origin::Origin;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{from_str, to_string};
+
+// Returns the value deserialised from its serialisation.
+fn round_trip<V: Serialize + DeserializeOwned>(value: &V) -> V {
+    from_str(&to_string(value).unwrap()).unwrap()
+}
+
+#[test]
+fn representations_are_stable() {
+    let v = Vec2D::new(3f64, -4f64);
+    assert_eq!(to_string(&v).unwrap(), r#"{"x":3.0,"y":-4.0}"#);
+    assert_eq!( to_string(&Mat2D::translation(v)).unwrap()
+              , "[[1.0,0.0,0.0],[0.0,1.0,0.0],[3.0,-4.0,1.0]]" );
+    assert_eq!( to_string(&Transform2D::translation(v)).unwrap()
+              , r#"{"cos":1.0,"sin":0.0,"translation":{"x":3.0,"y":-4.0}}"# );
+    assert_eq!(to_string(&Origin::Relative).unwrap(), r#""relative""#);
+    assert_eq!( to_string(&Circle::new(v, 2f64)).unwrap()
+              , r#"{"centre":{"x":3.0,"y":-4.0},"radius":2.0}"# );
+    assert_eq!(to_string(&Q16::from_f64(-0.5)).unwrap(), "-0.5");
+}
+
+#[test]
+fn values_round_trip() {
+    let (p, q) = (Vec2D::new(1.5f64, -2f64), Vec2D::new(-3f64, 0.25f64));
+    let m = Mat2D::rotation(0.5f64).mul(Mat2D::translation(p));
+    let json = to_string(&m).unwrap();
+    assert_eq!(to_string(&round_trip(&m)).unwrap(), json);
+
+    let t = Transform2D::new(0.5f64, p);
+    assert_eq!(round_trip(&t), t);
+    assert_eq!(round_trip(&Origin::Absolute), Origin::Absolute);
+    assert_eq!(round_trip(&Origin::Relative), Origin::Relative);
+
+    let segment = Segment::new(p, q);
+    let ray = Ray::new(p, q);
+    let aabb = Aabb::new(q, p);
+    let capsule = Capsule::new(p, q, 0.5f64);
+    let polygon = Polygon::new(vec![p, q, Vec2D::zero()]);
+    let outline: Outline = polygon.outline();
+    assert_eq!(round_trip(&segment), segment);
+    assert_eq!(round_trip(&ray), ray);
+    assert_eq!(round_trip(&aabb), aabb);
+    assert_eq!(round_trip(&capsule), capsule);
+    assert_eq!(round_trip(&polygon), polygon);
+    assert_eq!(round_trip(&outline), outline);
+
+    let v = Vec2D::new(Q16::from_f64(1.5), Q16::from_f64(-2.25));
+    let json = to_string(&v).unwrap();
+    assert_eq!(json, r#"{"x":1.5,"y":-2.25}"#);
+    assert_eq!(from_str::<Vec2D<Q16>>(&json).unwrap(), v);
+}
+
+// Polygons and outlines without points are rejected when read, as they are
+// by their constructors, rather than panicking when queried.
+#[test]
+fn empty_shapes_rejected() {
+    assert!(from_str::<Polygon>(r#"{"vertices":[]}"#).is_err());
+    let outline = r#"{"points":[],"closed":true,"radius":0.0}"#;
+    assert!(from_str::<Outline>(outline).is_err());
+    let polygon = r#"{"vertices":[{"x":1.0,"y":2.0}]}"#;
+    let point = Vec2D::new(1f64, 2f64);
+    assert_eq!(from_str::<Polygon>(polygon).unwrap().vertices(), &[point]);
+}
+}
+
+// Tests that the functions whose results depend on the platform's libm are
+// bitwise reproducible in deterministic mode.
+#[cfg(feature = "deterministic")]
+mod deterministic {
+use super::*;
+use super::super::digest::Digest;
+
+// Returns the digest of rotations, polar vectors, their angles and square
+// roots over a fixed range of arguments, evaluated in `T`.
+fn digest<T: Scalar>() -> u64 {
+    let mut digest = Digest::new();
+    let point = Vec2D::new(T::from_f64(0.75), T::from_f64(-1.25));
+    for k in -100..100 {
+        let rad = T::from_f64(k as f64 * 0.0731);
+        let v = Vec2D::polar(rad, T::from_f64(2.5));
+        digest.write_vector(Mat2D::rotation(rad).transform(point));
+        digest.write_vector(v);
+        digest.write(v.angle());
+        digest.write(T::from_f64((k * k) as f64 * 0.37).sqrt());
+    }
+    digest.finish()
+}
+
+// Digests of the outputs in double and single precision.
+const DOUBLE: u64 = 0x38f9dfa2b897ce8b;
+const SINGLE: u64 = 0x5be3161bca248107;
+
+#[test]
+fn outputs_match_digests() {
+    let (double, single) = (digest::<f64>(), digest::<f32>());
+    assert!(double == DOUBLE, "double precision digest {:#018x}", double);
+    assert!(single == SINGLE, "single precision digest {:#018x}", single);
+}
+}
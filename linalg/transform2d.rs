@@ -0,0 +1,72 @@
+use super::matrix2d::Mat2D;
+use super::scalar::Scalar;
+use super::vector2d::Vec2D;
+
+// Rigid transform: a rotation, stored as its cosine and sine, followed by a
+// translation. Composes in the same order as `Mat2D`, so `a.mul(b)` applies
+// `a` and then `b`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transform2D<T = f64> { pub cos:         T
+                                , pub sin:         T
+                                , pub translation: Vec2D<T> }
+
+impl<T: Scalar> Transform2D<T> {
+    pub fn new(rad: T, translation: Vec2D<T>) -> Transform2D<T> {
+        Transform2D { cos:         rad.cos()
+                    , sin:         rad.sin()
+                    , translation: translation }
+    }
+
+    pub fn identity() -> Transform2D<T> {
+        Transform2D { cos: T::ONE, sin: T::ZERO, translation: Vec2D::zero() }
+    }
+
+    pub fn rotation(rad: T) -> Transform2D<T> {
+        Transform2D::new(rad, Vec2D::zero())
+    }
+
+    pub fn translation(v: Vec2D<T>) -> Transform2D<T> {
+        Transform2D { cos: T::ONE, sin: T::ZERO, translation: v }
+    }
+
+    pub fn turn(&self, rad: T) -> Transform2D<T> {
+        self.mul(Transform2D::rotation(rad))
+    }
+
+    pub fn shift(&self, v: Vec2D<T>) -> Transform2D<T> {
+        Transform2D { cos:         self.cos
+                    , sin:         self.sin
+                    , translation: self.translation + v }
+    }
+
+    pub fn mul(&self, t: Transform2D<T>) -> Transform2D<T> {
+        Transform2D { cos:         t.cos * self.cos - t.sin * self.sin
+                    , sin:         t.sin * self.cos + t.cos * self.sin
+                    , translation: t.transform(self.translation) }
+    }
+
+    pub fn inverse(&self) -> Transform2D<T> {
+        let inverse = Transform2D { cos:         self.cos
+                                  , sin:         -self.sin
+                                  , translation: Vec2D::zero() };
+        Transform2D { translation: -inverse.transform(self.translation)
+                    , ..inverse }
+    }
+
+    pub fn transform(&self, v: Vec2D<T>) -> Vec2D<T> {
+        self.transform_vector(v) + self.translation
+    }
+
+    pub fn transform_vector(&self, v: Vec2D<T>) -> Vec2D<T> {
+        Vec2D { x: self.cos * v.x - self.sin * v.y
+              , y: self.sin * v.x + self.cos * v.y }
+    }
+
+    pub fn to_matrix(&self) -> Mat2D<T> {
+        let (o, i) = (T::ZERO, T::ONE);
+        Mat2D::new( [ [  self.cos,           self.sin,           o ]
+                    , [ -self.sin,           self.cos,           o ]
+                    , [  self.translation.x, self.translation.y, i ] ] )
+    }
+}
@@ -1,70 +1,202 @@
+use super::scalar::Scalar;
+
 use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::ops::{AddAssign, SubAssign, MulAssign, DivAssign};
 
-pub const EPSILON: f64 = 1e-8;
+pub const EPSILON: f64 = <f64 as Scalar>::EPSILON;
 
-#[derive(Copy, Clone)]
-pub struct Vec2D { pub x: f64
-                 , pub y: f64 }
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Vec2D<T = f64> { pub x: T
+                          , pub y: T }
 
-impl fmt::Display for Vec2D {
+impl<T: Scalar> fmt::Display for Vec2D<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}, {})", self.x, self.y)
     }
 }
 
-impl Vec2D {
-    pub fn new(x: f64, y: f64) -> Vec2D {
+impl<T: Scalar> Vec2D<T> {
+    pub fn new(x: T, y: T) -> Vec2D<T> {
         Vec2D { x: x, y: y }
     }
 
-    pub fn zero() -> Vec2D { Vec2D::new(0f64, 0f64) }
+    pub fn zero() -> Vec2D<T> { Vec2D::new(T::ZERO, T::ZERO) }
 
-    pub fn unitx() -> Vec2D { Vec2D::new(1f64, 0f64) }
+    pub fn unitx() -> Vec2D<T> { Vec2D::new(T::ONE, T::ZERO) }
 
-    pub fn unity() -> Vec2D { Vec2D::new(0f64, 1f64) }
+    pub fn unity() -> Vec2D<T> { Vec2D::new(T::ZERO, T::ONE) }
 
-    pub fn polar(angle: f64, mag: f64) -> Vec2D {
+    pub fn polar(angle: T, mag: T) -> Vec2D<T> {
         Vec2D { x: angle.cos(), y: angle.sin() }.mul(mag)
     }
 
-    pub fn copy(&self) -> Vec2D {
+    pub fn copy(&self) -> Vec2D<T> {
         Vec2D { x: self.x, y: self.y }
     }
 
-    pub fn neg(&self) -> Vec2D {
+    pub fn neg(&self) -> Vec2D<T> {
         Vec2D { x: -self.x, y: -self.y }
     }
 
-    pub fn add(&self, v: Vec2D) -> Vec2D {
+    pub fn add(&self, v: Vec2D<T>) -> Vec2D<T> {
         Vec2D { x: self.x + v.x, y: self.y + v.y }
     }
 
-    pub fn sub(&self, v: Vec2D) -> Vec2D {
+    pub fn sub(&self, v: Vec2D<T>) -> Vec2D<T> {
         Vec2D { x: self.x - v.x, y: self.y - v.y }
     }
 
-    pub fn mul(&self, s: f64) -> Vec2D {
+    pub fn mul(&self, s: T) -> Vec2D<T> {
         Vec2D { x: self.x * s, y: self.y * s }
     }
 
-    pub fn mag_sq(&self) -> f64 {
+    pub fn mag_sq(&self) -> T {
         self.x * self.x + self.y * self.y
     }
 
-    pub fn mag(&self) -> f64 {
+    pub fn mag(&self) -> T {
         let mag = self.mag_sq();
-        if mag < EPSILON { mag } else { mag.sqrt() }
+        if mag < T::EPSILON { mag } else { mag.sqrt() }
     }
 
-    pub fn dot(&self, v: Vec2D) -> f64 {
+    pub fn dot(&self, v: Vec2D<T>) -> T {
         self.x * v.x + self.y * v.y
     }
 
-    pub fn perp(&self) -> Vec2D {
+    pub fn perp(&self) -> Vec2D<T> {
         Vec2D { x: -self.y, y: self.x }
     }
 
-    pub fn angle(&self) -> f64 {
+    pub fn angle(&self) -> T {
         self.y.atan2(self.x)
     }
+
+    pub fn length(&self) -> T {
+        self.mag_sq().sqrt()
+    }
+
+    pub fn normalize(&self) -> Vec2D<T> {
+        *self / self.length()
+    }
+
+    pub fn try_normalize(&self) -> Option<Vec2D<T>> {
+        let length = self.length();
+        if length < T::EPSILON { None } else { Some(*self / length) }
+    }
+
+    pub fn rotate(&self, rad: T) -> Vec2D<T> {
+        let c = rad.cos();
+        let s = rad.sin();
+        Vec2D { x: self.x * c - self.y * s, y: self.x * s + self.y * c }
+    }
+
+    pub fn lerp(&self, v: Vec2D<T>, t: T) -> Vec2D<T> {
+        *self + (v - *self) * t
+    }
+
+    pub fn cross(&self, v: Vec2D<T>) -> T {
+        self.x * v.y - self.y * v.x
+    }
+
+    pub fn distance(&self, v: Vec2D<T>) -> T {
+        (v - *self).length()
+    }
+
+    pub fn project(&self, onto: Vec2D<T>) -> Vec2D<T> {
+        onto * (self.dot(onto) / onto.mag_sq())
+    }
+
+    pub fn reflect(&self, normal: Vec2D<T>) -> Vec2D<T> {
+        *self - normal * (T::from_f64(2f64) * self.dot(normal))
+    }
+
+    pub fn cast<U: Scalar>(&self) -> Vec2D<U> {
+        Vec2D { x: U::from_f64(self.x.to_f64())
+              , y: U::from_f64(self.y.to_f64()) }
+    }
+
+    pub fn approx_eq(&self, v: Vec2D<T>, epsilon: T) -> bool {
+        (self.x - v.x).abs() <= epsilon && (self.y - v.y).abs() <= epsilon
+    }
+}
+
+impl<T: Scalar> Add for Vec2D<T> {
+    type Output = Vec2D<T>;
+
+    fn add(self, v: Vec2D<T>) -> Vec2D<T> {
+        Vec2D { x: self.x + v.x, y: self.y + v.y }
+    }
+}
+
+impl<T: Scalar> Sub for Vec2D<T> {
+    type Output = Vec2D<T>;
+
+    fn sub(self, v: Vec2D<T>) -> Vec2D<T> {
+        Vec2D { x: self.x - v.x, y: self.y - v.y }
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vec2D<T> {
+    type Output = Vec2D<T>;
+
+    fn mul(self, s: T) -> Vec2D<T> {
+        Vec2D { x: self.x * s, y: self.y * s }
+    }
+}
+
+macro_rules! scalar_mul {
+    ($t:ident) => {
+        impl Mul<Vec2D<$t>> for $t {
+            type Output = Vec2D<$t>;
+
+            fn mul(self, v: Vec2D<$t>) -> Vec2D<$t> {
+                Vec2D { x: self * v.x, y: self * v.y }
+            }
+        }
+    }
+}
+
+scalar_mul!(f64);
+scalar_mul!(f32);
+
+impl<T: Scalar> Div<T> for Vec2D<T> {
+    type Output = Vec2D<T>;
+
+    fn div(self, s: T) -> Vec2D<T> {
+        Vec2D { x: self.x / s, y: self.y / s }
+    }
+}
+
+impl<T: Scalar> Neg for Vec2D<T> {
+    type Output = Vec2D<T>;
+
+    fn neg(self) -> Vec2D<T> {
+        Vec2D { x: -self.x, y: -self.y }
+    }
+}
+
+impl<T: Scalar> AddAssign for Vec2D<T> {
+    fn add_assign(&mut self, v: Vec2D<T>) {
+        *self = *self + v;
+    }
+}
+
+impl<T: Scalar> SubAssign for Vec2D<T> {
+    fn sub_assign(&mut self, v: Vec2D<T>) {
+        *self = *self - v;
+    }
+}
+
+impl<T: Scalar> MulAssign<T> for Vec2D<T> {
+    fn mul_assign(&mut self, s: T) {
+        *self = *self * s;
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Vec2D<T> {
+    fn div_assign(&mut self, s: T) {
+        *self = *self / s;
+    }
 }
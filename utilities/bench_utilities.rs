@@ -1,20 +1,80 @@
+use super::drift::{offset, offset_scenario, report_drift};
+use super::explain::Format;
+use super::golden::{regenerate_corpus, write_corpus};
+use super::histogram::Histogram;
+use super::invariants::report_invariants;
+use super::linalg::batch::KERNELS;
+use super::oracle::report_risk;
+use super::precision::{report_precision, FIXED_VARIANT, SINGLE_VARIANT};
+use super::progress::Progress;
+use super::record::Record;
+use super::results::Results;
 use super::test::black_box;
 use super::time::PreciseTime;
 use super::types::{HasScenario, Obstacles};
+use super::utilities::{get_command, get_offset, write_batches, Command};
+use super::world::{Kind, World, Worlds, WORLD_VARIANT};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Label of the variant of algorithms that evaluate obstacles in batches.
+// Results are further labelled with the kernels that evaluate the batches.
+pub const BATCH_VARIANT: &'static str = "batch";
 
 // Number of scenarios to run for each benchmark.
 #[allow(dead_code)]
 pub const NUM_RUNS: u32 = 1_000_000;
 
-// Contains details and results of a batch of benchmarks.
+// Ways in which run times are recorded.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Recording { Samples
+                   , Histogram(u32) }
+
+// Contains details and results of a batch of benchmarks. Run times are either
+// kept individually or recorded in a histogram.
 #[derive(Serialize, Deserialize)]
 pub struct Batch { num_runs:  u32
-                 , run_times: Vec<i64> }
+                 , run_times: Vec<i64>
+                 , #[serde(default, skip_serializing_if = "Option::is_none")]
+                   histogram: Option<Histogram> }
 
 impl Batch {
     // Creates a Batch from the given values.
     pub fn new(num_runs: u32, run_times: Vec<i64>) -> Batch {
-        Batch { num_runs: num_runs, run_times: run_times }
+        Batch { num_runs: num_runs, run_times: run_times, histogram: None }
+    }
+
+    // Creates a Batch whose run times are recorded in the given histogram.
+    pub fn with_histogram(num_runs: u32, histogram: Histogram) -> Batch {
+        Batch { num_runs:  num_runs
+              , run_times: vec!()
+              , histogram: Some(histogram) }
+    }
+
+    // Returns the number of scenarios the batch was configured to run.
+    pub fn num_runs(&self) -> u32 {
+        self.num_runs
+    }
+
+    // Returns the recorded run times in nanoseconds. Empty if the run times
+    // were recorded in a histogram.
+    pub fn run_times(&self) -> &Vec<i64> {
+        &self.run_times
+    }
+
+    // Returns the histogram of run times in nanoseconds, if they were
+    // recorded in one.
+    pub fn histogram(&self) -> Option<&Histogram> {
+        self.histogram.as_ref()
+    }
+
+    // Returns the way in which the batch's run times were recorded.
+    pub fn recording(&self) -> Recording {
+        match self.histogram {
+            Some(ref h) => Recording::Histogram(h.significant_digits()),
+            None => Recording::Samples
+        }
     }
 }
 
@@ -28,18 +88,40 @@ impl ObstacleBatch {
     pub fn new(obstacles: Obstacles, batch: Batch) -> ObstacleBatch {
         ObstacleBatch { obstacles: obstacles, batch: batch }
     }
+
+    // Returns the obstacle configuration the batch was timed against.
+    pub fn obstacles(&self) -> &Obstacles {
+        &self.obstacles
+    }
+
+    // Returns the timing results of the batch.
+    pub fn batch(&self) -> &Batch {
+        &self.batch
+    }
 }
 
-// Runs a series of tests on scenarios generated using the provided function.
-pub fn time_batch<F>(obstacles: &Obstacles, creator: F)
+// Runs a series of tests on scenarios generated using the provided function,
+// recording run times as specified. Progress is reported on standard error
+// as it goes. Timing progress is reported between samples, outside the timed
+// region, and only a hundred times per configuration.
+pub fn time_batch<F>(obstacles: &Obstacles, recording: Recording, creator: F)
     -> ObstacleBatch
     where F: Fn(&Obstacles) -> Box<HasScenario>
 {
+    let generating = Progress::new("Generating scenarios", NUM_RUNS + 100);
     let mut scenarios: Vec<Box<HasScenario>> =
-        (0..NUM_RUNS + 100).map(|_| creator(obstacles)).collect();
+        (0..NUM_RUNS + 100).map(|i| {
+            generating.update(i + 1);
+            creator(obstacles)
+        }).collect();
 
+    let timing_progress = Progress::new("Timing scenarios", NUM_RUNS + 100);
     let mut count = 0;
     let mut timing = vec!();
+    let mut histogram = match recording {
+        Recording::Histogram(digits) => Some(Histogram::new(digits)),
+        Recording::Samples => None
+    };
     for s in scenarios.iter_mut() {
         let start = PreciseTime::now();
         black_box(s.avoidance());
@@ -47,9 +129,272 @@ pub fn time_batch<F>(obstacles: &Obstacles, creator: F)
 
         count += 1;
         if count >= 100 {
-            timing.push(elapsed.num_nanoseconds().unwrap());
+            let nanoseconds = elapsed.num_nanoseconds().unwrap();
+            match histogram {
+                Some(ref mut h) => h.record(nanoseconds),
+                None => timing.push(nanoseconds)
+            }
+        }
+        timing_progress.update(count);
+    }
+
+    let batch = match histogram {
+        Some(h) => Batch::with_histogram(NUM_RUNS, h),
+        None => Batch::new(NUM_RUNS, timing)
+    };
+    ObstacleBatch::new(obstacles.clone(), batch)
+}
+
+// Returns the obstacle configurations benchmarked by each program, in the
+// order in which they are run.
+pub fn configurations() -> Vec<Obstacles> {
+    let mut configurations = vec!();
+    for i in 1..6 {
+        configurations.push(Obstacles::new(i, 0u32));
+        configurations.push(Obstacles::new(0u32, i));
+    }
+    configurations
+}
+
+// Returns the path of the checkpoint file kept alongside the given results
+// file.
+fn checkpoint_path(filepath: &Path) -> PathBuf {
+    let mut checkpoint = filepath.as_os_str().to_os_string();
+    checkpoint.push(".checkpoint");
+    PathBuf::from(checkpoint)
+}
+
+// Writes results to the checkpoint file at the given path. The file is
+// replaced in a single step so that an interruption cannot corrupt it.
+fn write_checkpoint(checkpoint: &Path, results: &Results) {
+    let partial = checkpoint.with_extension("checkpoint.partial");
+    write_batches(&partial, results);
+    if let Err(error) = fs::rename(&partial, checkpoint) {
+        panic!("couldn't write {}: {}", checkpoint.display(), error);
+    }
+}
+
+// Returns the checkpointed results at the given path if they were produced by
+// the same algorithm and variant on this machine and recorded in the same
+// way, or empty results otherwise.
+fn resume( checkpoint: &Path
+         , recording:  Recording
+         , algorithm:  &str
+         , variant:    &str ) -> Results
+{
+    let fresh = Results::new(algorithm, variant, vec!());
+    if !checkpoint.exists() { return fresh; }
+
+    let matching = |x: &Results| {
+        x.algorithm == fresh.algorithm
+            && x.variant == fresh.variant
+            && x.machine == fresh.machine
+            && x.batches.iter().all(|b| b.batch().recording() == recording)
+    };
+    match Results::read(checkpoint) {
+        Ok(mut results) => match results.drain(..).find(matching) {
+            Some(x) => {
+                eprintln!( "Resuming from {} ({} configurations complete)"
+                         , checkpoint.display()
+                         , x.batches.len() );
+                x
+            },
+            None => {
+                eprintln!( "Ignoring {}: produced by another benchmark or \
+                            recorded in another way"
+                         , checkpoint.display() );
+                fresh
+            }
+        },
+        Err(error) => {
+            eprintln!("Ignoring {}: {}", checkpoint.display(), error);
+            fresh
+        }
+    }
+}
+
+// Times every configuration using scenarios generated by `creator` and writes
+// the labelled results to the specified file. Results are checkpointed after
+// each configuration, so an interrupted run resumes from the first unfinished
+// configuration.
+pub fn run_batches<F>( filepath:  &Path
+                     , recording: Recording
+                     , algorithm: &str
+                     , variant:   &str
+                     , creator:   F )
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let start = PreciseTime::now();
+    let checkpoint = checkpoint_path(filepath);
+
+    let mut results = resume(&checkpoint, recording, algorithm, variant);
+    let configurations = configurations();
+    for (i, obstacles) in configurations.iter().enumerate() {
+        if results.batches.iter().any(|x| x.obstacles() == obstacles) {
+            continue;
         }
+
+        eprintln!( "Configuration {} of {}: {} insignificant, {} significant"
+                 , i + 1
+                 , configurations.len()
+                 , obstacles.insignificant
+                 , obstacles.significant );
+        let batch = time_batch(obstacles, recording, |x| creator(x));
+        results.batches.push(batch);
+        write_checkpoint(&checkpoint, &results);
+    }
+
+    write_batches(filepath, &results);
+    if let Err(error) = fs::remove_file(&checkpoint) {
+        eprintln!("couldn't remove {}: {}", checkpoint.display(), error);
+    }
+
+    let runtime = start.to(PreciseTime::now()).num_seconds();
+    println!("Total time: {} seconds", runtime);
+}
+
+// Prints a trace of the evaluation of a single scenario with the given
+// obstacles.
+pub fn explain_scenario<F>(obstacles: &Obstacles, format: Format, creator: F)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    print!("{}", creator(obstacles).explain().format(format));
+}
+
+// Performs the given command on scenarios generated by `creator`, labelling
+// benchmark results with the given algorithm and variant.
+fn perform<F>(command: Command, algorithm: &str, variant: &str, creator: F)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    match command {
+        Command::Benchmark(filestring, recording) => {
+            let filepath = Path::new(&filestring);
+            run_batches(&filepath, recording, algorithm, variant, creator);
+        },
+        Command::Explain(obstacles, format) =>
+            explain_scenario(&obstacles, format, creator),
+        Command::Risk => report_risk(creator),
+        Command::Invariants => report_invariants(creator),
+        Command::FreshGolden(filestring) =>
+            write_corpus(Path::new(&filestring), creator),
+        Command::Golden(_) | Command::Drift(_) | Command::Precision(_) => ()
+    }
+}
+
+// Benchmarks the algorithm or explains a scenario, as specified on the
+// command line. `rebuild` recreates scenarios from their records, so that
+// they can be translated by the offset given on the command line, their
+// drift reported or the golden corpus re-evaluated, and `single` and `fixed`
+// recreate them in single precision and in fixed point, so that they can be
+// compared with their evaluation in double precision.
+pub fn run<F, G, H, I>( algorithm: &str
+                      , variant:   &str
+                      , creator:   F
+                      , rebuild:   G
+                      , single:    H
+                      , fixed:     I )
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+        , H: Fn(&Record) -> Option<Box<HasScenario>>
+        , I: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let command = match get_command() {
+        Some(Command::Drift(distance)) => {
+            report_drift(distance, creator, rebuild);
+            return;
+        },
+        Some(Command::Precision(ref name)) if name == FIXED_VARIANT => {
+            report_precision(name, creator, fixed);
+            return;
+        },
+        Some(Command::Precision(ref name)) => {
+            report_precision(name, creator, single);
+            return;
+        },
+        Some(Command::Golden(filestring)) => {
+            regenerate_corpus(Path::new(&filestring), rebuild);
+            return;
+        },
+        Some(command) => command,
+        None => return
+    };
+    match get_offset() {
+        Some(distance) => {
+            let label = format!("{}@{}", variant, distance);
+            let translated = |o: &Obstacles| -> Box<HasScenario> {
+                offset_scenario(o, offset(distance), &creator, &rebuild)
+                    .unwrap()
+            };
+            perform(command, algorithm, &label, translated);
+        },
+        None => perform(command, algorithm, variant, creator)
     }
+}
+
+// Runs the algorithm, as `run` does, on scenarios built by `from_world` from
+// the sequence of shared worlds of the given kind. Results are labelled with
+// the world variant.
+pub fn run_worlds<F, G, H, I>( algorithm:  &str
+                             , kind:       Kind
+                             , from_world: F
+                             , rebuild:    G
+                             , single:     H
+                             , fixed:      I )
+    where F: Fn(&World) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+        , H: Fn(&Record) -> Option<Box<HasScenario>>
+        , I: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let worlds = Worlds::new(kind);
+    let creator = |o: &Obstacles| from_world(&worlds.next(o));
+    run(algorithm, WORLD_VARIANT, creator, rebuild, single, fixed);
+}
 
-    ObstacleBatch::new(obstacles.clone(), Batch::new(NUM_RUNS, timing))
+// Runs the algorithm, as `run` does, on scenarios generated by `creator` and
+// rebuilt from their records by `lower`, which evaluates in a lower
+// precision. Results are labelled with the given variant.
+fn run_lowered<F, G>(algorithm: &str, variant: &str, creator: F, lower: G)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let rebuilt = |o: &Obstacles| lower(&creator(o).record()).unwrap();
+    run(algorithm, variant, rebuilt, &lower, &lower, &lower);
+}
+
+// Runs the algorithm, as `run` does, on scenarios generated by `creator` and
+// rebuilt from their records by `single`, which evaluates in single
+// precision. Results are labelled with the single precision variant.
+pub fn run_single<F, G>(algorithm: &str, creator: F, single: G)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    run_lowered(algorithm, SINGLE_VARIANT, creator, single);
+}
+
+// Runs the algorithm, as `run` does, on scenarios generated by `creator` and
+// rebuilt from their records by `fixed`, which evaluates in fixed point.
+// Results are labelled with the fixed-point variant.
+pub fn run_fixed<F, G>(algorithm: &str, creator: F, fixed: G)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    run_lowered(algorithm, FIXED_VARIANT, creator, fixed);
+}
+
+// Runs the algorithm, as `run` does, on scenarios generated by `creator` and
+// rebuilt from their records by `batched`, which evaluates obstacles in
+// batches. Results are labelled with the batch variant and its kernels.
+pub fn run_batched<F, G, H, I>( algorithm: &str
+                              , creator:   F
+                              , batched:   G
+                              , single:    H
+                              , fixed:     I )
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+        , H: Fn(&Record) -> Option<Box<HasScenario>>
+        , I: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let label = format!("{}-{}", BATCH_VARIANT, KERNELS);
+    let rebuilt = |o: &Obstacles| batched(&creator(o).record()).unwrap();
+    run(algorithm, &label, rebuilt, &batched, single, fixed);
 }
@@ -0,0 +1,182 @@
+use super::bench_utilities::configurations;
+use super::linalg::vector2d::Vec2D;
+use super::record::Record;
+use super::types::{HasScenario, Obstacles};
+
+// Number of scenarios compared for each obstacle configuration.
+pub const NUM_RUNS: u32 = 1_000;
+
+// Distance by which scenarios are translated along each axis when measuring
+// drift, unless another is given.
+pub const DEFAULT_OFFSET: f64 = 1e6;
+
+// Returns the offset that translates scenarios by the given distance along
+// each axis.
+pub fn offset(distance: f64) -> Vec2D {
+    Vec2D::new(distance, distance)
+}
+
+// Returns the record with every position translated by `offset`. Positions
+// are recorded as the `x` and `y` parameters of vehicles and disks, and the
+// numbered endpoints of segments.
+pub fn translate_record(record: &Record, offset: Vec2D) -> Record {
+    let translate = |name: &str, value: f64| match name {
+        "x" | "x1" | "x2" => value + offset.x,
+        "y" | "y1" | "y2" => value + offset.y,
+        _ => value
+    };
+    Record { vehicle:   record.vehicle.map(&translate)
+           , obstacles: record.obstacles.iter()
+                                        .map(|x| x.map(&translate))
+                                        .collect() }
+}
+
+// Returns a scenario generated by `creator` and translated by `offset`,
+// rebuilt from its record by `rebuild`. Returns none if it could not be
+// rebuilt.
+pub fn offset_scenario<F, G>( obstacles: &Obstacles
+                            , offset:    Vec2D
+                            , creator:   F
+                            , rebuild:   G ) -> Option<Box<HasScenario>>
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    rebuild(&translate_record(&creator(obstacles).record(), offset))
+}
+
+// Returns a scenario generated by `creator` centred near the origin and the
+// same scenario translated by `offset`, both rebuilt from records by
+// `rebuild`. The centred scenario is the translated one moved back, which is
+// exact for offsets much larger than the scenario, so both describe exactly
+// the same geometry. Returns none if either could not be rebuilt.
+pub fn offset_scenarios<F, G>( obstacles: &Obstacles
+                             , offset:    Vec2D
+                             , creator:   F
+                             , rebuild:   G )
+    -> Option<(Box<HasScenario>, Box<HasScenario>)>
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let translated = translate_record(&creator(obstacles).record(), offset);
+    let centred = translate_record(&translated, -offset);
+    Some((rebuild(&centred)?, rebuild(&translated)?))
+}
+
+// Differences between the evaluations of pairs of scenarios describing the
+// same geometry, such as scenarios centred near the origin and the same
+// scenarios translated far from it. A decision differs if the number of
+// interactions differs or only one produces a force. Forces are compared,
+// relative to their scenarios' force origins, when both are finite.
+#[derive(Copy, Clone, Default)]
+pub struct Drift { pub runs:        u32
+                 , pub decisions:   u32
+                 , pub forces:      u32
+                 , pub max_drift:   f64
+                 , pub total_drift: f64 }
+
+impl Drift {
+    // Compares the evaluations of a reference scenario, such as a centred
+    // one, and another describing the same geometry, such as the same
+    // scenario translated.
+    pub fn add( &mut self
+              , reference: &mut Box<HasScenario>
+              , other:     &mut Box<HasScenario> )
+    {
+        self.runs += 1;
+        if reference.interactions() != other.interactions() {
+            self.decisions += 1;
+            return;
+        }
+        let origins = (reference.force_origin(), other.force_origin());
+        match (reference.avoidance(), other.avoidance()) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (a - origins.0, b - origins.1);
+                let finite = |v: Vec2D| v.x.is_finite() && v.y.is_finite();
+                if finite(a) && finite(b) {
+                    // `mag` would return the squared distance of forces
+                    // closer than its threshold.
+                    let drift = (b - a).length();
+                    if drift > self.max_drift { self.max_drift = drift; }
+                    self.total_drift += drift;
+                    self.forces += 1;
+                }
+            },
+            (None, None) => {},
+            _ => self.decisions += 1
+        }
+    }
+
+    // Counts the comparisons in another drift.
+    pub fn merge(&mut self, other: &Drift) {
+        self.runs += other.runs;
+        self.decisions += other.decisions;
+        self.forces += other.forces;
+        if other.max_drift > self.max_drift {
+            self.max_drift = other.max_drift;
+        }
+        self.total_drift += other.total_drift;
+    }
+
+    // Returns the mean distance between compared forces.
+    pub fn mean_drift(&self) -> f64 {
+        if self.forces == 0 { return 0f64; }
+        self.total_drift / self.forces as f64
+    }
+}
+
+// Returns the drift of `NUM_RUNS` scenarios with the given obstacles,
+// generated by `creator`, when translated by `offset`.
+pub fn measure_drift<F, G>( obstacles: &Obstacles
+                          , offset:    Vec2D
+                          , creator:   F
+                          , rebuild:   G ) -> Drift
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let mut drift = Drift::default();
+    for _ in 0..NUM_RUNS {
+        let scenarios = offset_scenarios(obstacles, offset, &creator, &rebuild);
+        if let Some((mut centred, mut translated)) = scenarios {
+            drift.add(&mut centred, &mut translated);
+        }
+    }
+    drift
+}
+
+// Prints the number of changed decisions and the largest and mean drift of
+// forces for each obstacle configuration, and for all configurations
+// combined, as measured by `measure`.
+pub fn print_drift<F>(measure: F)
+    where F: Fn(&Obstacles) -> Drift
+{
+    let print = |label: &str, d: &Drift| {
+        println!( "{:>12} {:>8} {:>10} {:>12.4e} {:>12.4e}"
+                , label, d.runs, d.decisions, d.max_drift, d.mean_drift() );
+    };
+
+    println!( "{:>12} {:>8} {:>10} {:>12} {:>12}"
+            , "obstacles", "runs", "decisions", "max drift", "mean drift" );
+    let mut total = Drift::default();
+    for obstacles in configurations().iter() {
+        let drift = measure(obstacles);
+        let label = format!( "{} / {}"
+                           , obstacles.insignificant
+                           , obstacles.significant );
+        print(&label, &drift);
+        total.merge(&drift);
+    }
+    print("all", &total);
+}
+
+// Prints the drift of each obstacle configuration, as `print_drift` does,
+// when scenarios are translated by the given distance along each axis.
+pub fn report_drift<F, G>(distance: f64, creator: F, rebuild: G)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    println!("Offset: {} along each axis", distance);
+    print_drift(|obstacles| {
+        measure_drift( obstacles, offset(distance)
+                     , |x| creator(x), |x| rebuild(x) )
+    });
+}
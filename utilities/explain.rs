@@ -0,0 +1,184 @@
+use super::linalg::scalar::Scalar;
+use super::linalg::vector2d::Vec2D;
+use super::serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use super::serde::ser::{Serialize, SerializeMap, Serializer};
+use super::serde_json::to_string_pretty;
+
+use std::fmt;
+
+// Formats in which traces can be printed.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Format { Text
+                , Json }
+
+impl Format {
+    // Returns the format with the given name, if it exists.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None
+        }
+    }
+}
+
+// Named values recorded at a step, kept in the order they were recorded.
+pub struct Values(Vec<(String, f64)>);
+
+impl Values {
+    // Creates values from the given names and values.
+    pub fn new(values: &[(&str, f64)]) -> Values {
+        Values(values.iter().map(|&(n, v)| (n.to_string(), v)).collect())
+    }
+
+    // Returns the value with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0.iter().find(|x| x.0 == name).map(|x| x.1)
+    }
+
+    // Returns whether every value is matched by a value of the same name in
+    // `other`, to within the given relative tolerance.
+    pub fn close(&self, other: &Values, tolerance: f64) -> bool {
+        self.0.iter().all(|&(ref name, value)| match other.get(name) {
+            Some(x) => (x - value).abs() <= tolerance * (1f64 + value.abs()),
+            None => false
+        })
+    }
+
+    // Returns the values with each replaced by the result of `f`, given its
+    // name and value.
+    pub fn map<F>(&self, f: F) -> Values
+        where F: Fn(&str, f64) -> f64
+    {
+        Values(self.0.iter().map(|x| (x.0.clone(), f(&x.0, x.1))).collect())
+    }
+
+    // Returns the vector whose components have the given names, if both
+    // exist.
+    pub fn vec(&self, x: &str, y: &str) -> Option<Vec2D> {
+        match (self.get(x), self.get(y)) {
+            (Some(x), Some(y)) => Some(Vec2D::new(x, y)),
+            _ => None
+        }
+    }
+
+    // As `get`, but converts the value to the scalar type `T`.
+    pub fn get_as<T: Scalar>(&self, name: &str) -> Option<T> {
+        self.get(name).map(T::from_f64)
+    }
+
+    // As `vec`, but converts the vector to the scalar type `T`.
+    pub fn vec_as<T: Scalar>(&self, x: &str, y: &str) -> Option<Vec2D<T>> {
+        self.vec(x, y).map(|v| v.cast())
+    }
+}
+
+impl Serialize for Values {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for &(ref name, value) in self.0.iter() {
+            map.serialize_entry(name, &value)?;
+        }
+        map.end()
+    }
+}
+
+// Reads values from a map of names to numbers, keeping their order.
+struct ValuesVisitor;
+
+impl<'de> Visitor<'de> for ValuesVisitor {
+    type Value = Values;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of names to numbers")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M)
+        -> Result<Values, M::Error>
+    {
+        let mut values = vec!();
+        while let Some(entry) = map.next_entry()? {
+            values.push(entry);
+        }
+        Ok(Values(values))
+    }
+}
+
+impl<'de> Deserialize<'de> for Values {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Values, D::Error>
+    {
+        deserializer.deserialize_map(ValuesVisitor)
+    }
+}
+
+// A step in the evaluation of an avoidance algorithm, optionally concerning
+// a single obstacle.
+#[derive(Serialize)]
+pub struct Step { pub obstacle: Option<usize>
+                , pub stage:    String
+                , pub values:   Values }
+
+// Record of the steps taken by an avoidance algorithm while evaluating a
+// scenario, the obstacle that determined the result and the result itself.
+#[derive(Serialize)]
+pub struct Trace { pub steps:  Vec<Step>
+                 , pub winner: Option<usize>
+                 , pub result: Option<[f64; 2]> }
+
+impl Trace {
+    // Creates an empty trace.
+    pub fn new() -> Trace {
+        Trace { steps: vec!(), winner: None, result: None }
+    }
+
+    // Records a step with the given values.
+    pub fn record( &mut self
+                 , obstacle: Option<usize>
+                 , stage:    &str
+                 , values:   &[(&str, f64)] )
+    {
+        self.steps.push(Step { obstacle: obstacle
+                             , stage:    stage.to_string()
+                             , values:   Values::new(values) });
+    }
+
+    // Records the obstacle that determined the result and the result.
+    pub fn conclude(&mut self, winner: Option<usize>, result: Option<Vec2D>) {
+        self.winner = winner;
+        self.result = result.map(|x| [x.x, x.y]);
+    }
+
+    // Returns the trace in the given format.
+    pub fn format(&self, format: Format) -> String {
+        match format {
+            Format::Text => self.to_string(),
+            Format::Json => to_string_pretty(self).unwrap() + "\n"
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in self.steps.iter() {
+            match step.obstacle {
+                Some(i) => write!(f, "[{}] {}:", i, step.stage)?,
+                None => write!(f, "{}:", step.stage)?
+            }
+            for &(ref name, value) in (step.values.0).iter() {
+                write!(f, " {}={}", name, value)?;
+            }
+            writeln!(f, "")?;
+        }
+        match self.winner {
+            Some(i) => writeln!(f, "winner: {}", i)?,
+            None => writeln!(f, "winner: none")?
+        }
+        match self.result {
+            Some(x) => writeln!(f, "result: ({}, {})", x[0], x[1]),
+            None => writeln!(f, "result: none")
+        }
+    }
+}
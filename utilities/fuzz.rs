@@ -0,0 +1,43 @@
+use super::linalg::vector2d::Vec2D;
+
+use std::fmt::Debug;
+
+// Largest magnitude of coordinates and lengths fed to the algorithms. Larger
+// values overflow in intermediate products for reasons unrelated to the
+// algorithms' geometry.
+pub const LIMIT: f64 = 1e6f64;
+
+// Point produced from arbitrary fuzzer input.
+#[derive(Arbitrary, Debug, Copy, Clone)]
+pub struct Point { pub x: f64
+                 , pub y: f64 }
+
+impl Point {
+    // Returns the point as a vector, or none if either coordinate is out of
+    // range.
+    pub fn vec(&self) -> Option<Vec2D> {
+        match (coordinate(self.x), coordinate(self.y)) {
+            (Some(x), Some(y)) => Some(Vec2D::new(x, y)),
+            _ => None
+        }
+    }
+}
+
+// Returns the value, or none if it is not finite or exceeds `LIMIT`.
+pub fn coordinate(x: f64) -> Option<f64> {
+    if x.is_finite() && x.abs() <= LIMIT { Some(x) } else { None }
+}
+
+// Returns the value, or none if it is not a positive coordinate.
+pub fn positive(x: f64) -> Option<f64> {
+    coordinate(x).filter(|&x| x > 0f64)
+}
+
+// Panics, reporting the input, if the output is not finite.
+pub fn check_finite<T: Debug>(output: Option<Vec2D>, input: &T) {
+    if let Some(v) = output {
+        if !v.x.is_finite() || !v.y.is_finite() {
+            panic!("non-finite output {} for input {:?}", v, input);
+        }
+    }
+}
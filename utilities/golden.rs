@@ -0,0 +1,229 @@
+use super::bench_utilities::configurations;
+use super::linalg::digest::Digest;
+use super::linalg::vector2d::Vec2D;
+use super::record::Record;
+use super::serde::de::{Deserialize, Deserializer};
+use super::serde::ser::{Serialize, Serializer};
+use super::serde_json::{from_str, Value};
+use super::types::{HasScenario, Obstacles};
+use super::utilities::write_batches;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// Number of scenarios recorded in the corpus for each obstacle configuration.
+pub const NUM_ENTRIES: u32 = 5;
+
+// Relative tolerance of comparisons between expected and actual forces.
+pub const TOLERANCE: f64 = 1e-9;
+
+// A fixed scenario with the interactions and avoidance force an algorithm is
+// expected to produce for it. Non-finite force components are written as
+// strings, as JSON has no representation for them.
+#[derive(Serialize, Deserialize)]
+pub struct Entry { pub scenario:     Record
+                 , pub interactions: u32
+                 , #[serde(with = "force")]
+                   pub force:        Option<[f64; 2]> }
+
+// Converts forces to and from JSON, keeping non-finite components.
+mod force {
+    use super::*;
+
+    // Writes finite components as numbers and others as strings.
+    pub fn serialize<S: Serializer>(force: &Option<[f64; 2]>, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        let component = |x: f64| if x.is_finite() {
+            Value::from(x)
+        } else {
+            Value::String(x.to_string())
+        };
+        force.map(|x| [component(x[0]), component(x[1])]).serialize(serializer)
+    }
+
+    // Reads components written as numbers or strings.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D)
+        -> Result<Option<[f64; 2]>, D::Error>
+    {
+        let component = |x: &Value| match *x {
+            Value::Number(ref n) => n.as_f64(),
+            Value::String(ref s) => s.parse().ok(),
+            _ => None
+        };
+        let force: Option<[Value; 2]> = Deserialize::deserialize(deserializer)?;
+        Ok(force.and_then(|x| match (component(&x[0]), component(&x[1])) {
+            (Some(a), Some(b)) => Some([a, b]),
+            _ => None
+        }))
+    }
+}
+
+impl Entry {
+    // Creates an entry recording the algorithm's current evaluation of the
+    // scenario.
+    pub fn new(scenario: &mut Box<HasScenario>) -> Entry {
+        let interactions = scenario.interactions();
+        let force = scenario.avoidance().map(|x| [x.x, x.y]);
+        Entry { scenario:     scenario.record()
+              , interactions: interactions
+              , force:        force }
+    }
+}
+
+// Details of a corpus entry whose scenario the algorithm no longer evaluates
+// as expected.
+pub struct Mismatch { pub index:    usize
+                    , pub detail:   String
+                    , pub scenario: String }
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "corpus entry {}: {}", self.index, self.detail)?;
+        write!(f, "scenario: {}", self.scenario)
+    }
+}
+
+// Mismatches are reported in full when unwrapped.
+impl fmt::Debug for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\n{}", self)
+    }
+}
+
+// Returns whether a force matches the expected force to within the
+// tolerance. Non-finite forces must match exactly.
+fn close(expected: [f64; 2], actual: Vec2D) -> bool {
+    let expected = Vec2D::new(expected[0], expected[1]);
+    let finite = |v: Vec2D| v.x.is_finite() && v.y.is_finite();
+    if !finite(expected) || !finite(actual) {
+        let same = |a: f64, b: f64| a == b || (a.is_nan() && b.is_nan());
+        return same(expected.x, actual.x) && same(expected.y, actual.y);
+    }
+    // `mag` would return the squared magnitude of differences and forces
+    // closer to zero than its threshold, loosening the tolerance.
+    let difference = (actual - expected).length();
+    difference <= TOLERANCE * (1f64 + expected.length())
+}
+
+// Returns a description of how the evaluation of the rebuilt scenario differs
+// from the entry, if it does.
+fn compare(entry: &Entry, scenario: &mut Box<HasScenario>) -> Option<String> {
+    let interactions = scenario.interactions();
+    if interactions != entry.interactions {
+        return Some(format!( "interactions: expected {}, actual {}"
+                           , entry.interactions, interactions ));
+    }
+    match (entry.force, scenario.avoidance()) {
+        (None, None) => None,
+        (Some(x), Some(y)) if close(x, y) => None,
+        (Some(x), Some(y)) =>
+            Some(format!("force: expected ({}, {}), actual {}", x[0], x[1], y)),
+        (Some(x), None) =>
+            Some(format!("force: expected ({}, {}), actual none", x[0], x[1])),
+        (None, Some(y)) => Some(format!("force: expected none, actual {}", y))
+    }
+}
+
+// Returns a corpus of `NUM_ENTRIES` scenarios for each obstacle
+// configuration, generated by `creator`.
+pub fn generate_corpus<F>(creator: F) -> Vec<Entry>
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let mut corpus = vec!();
+    for obstacles in configurations().iter() {
+        for _ in 0..NUM_ENTRIES {
+            corpus.push(Entry::new(&mut creator(obstacles)));
+        }
+    }
+    corpus
+}
+
+// Replaces the corpus at the given path with new scenarios generated by
+// `creator`.
+pub fn write_corpus<F>(filepath: &Path, creator: F)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let corpus = generate_corpus(creator);
+    write_batches(filepath, &corpus);
+    println!("Wrote {} entries to {}", corpus.len(), filepath.display());
+}
+
+// Re-evaluates every scenario of the corpus at the given path, rebuilt from
+// its record by `rebuild`, and rewrites the expected interactions and forces.
+// The scenarios themselves are kept, so that differences in the corpus are
+// differences in the algorithm's output. Entries that changed are printed.
+pub fn regenerate_corpus<F>(filepath: &Path, rebuild: F)
+    where F: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let corpus = match fs::read_to_string(filepath) {
+        Ok(corpus) => corpus,
+        Err(error) => panic!("couldn't read {}: {}", filepath.display(), error)
+    };
+    let mut entries: Vec<Entry> = from_str(&corpus).unwrap();
+    let mut changed = 0;
+    for (index, entry) in entries.iter_mut().enumerate() {
+        let mut scenario = match rebuild(&entry.scenario) {
+            Some(scenario) => scenario,
+            None => panic!("corpus entry {} could not be rebuilt", index)
+        };
+        if let Some(detail) = compare(entry, &mut scenario) {
+            println!("corpus entry {}: {}", index, detail);
+            changed += 1;
+        }
+        entry.interactions = scenario.interactions();
+        entry.force = scenario.avoidance().map(|x| [x.x, x.y]);
+    }
+    write_batches(filepath, &entries);
+    println!( "Rewrote {} entries of {}, {} changed"
+            , entries.len(), filepath.display(), changed );
+}
+
+// Checks that every scenario in the JSON corpus, rebuilt from its record by
+// `rebuild`, is evaluated as recorded. Returns the first mismatch otherwise.
+pub fn check_corpus<F>(corpus: &str, rebuild: F) -> Result<(), Mismatch>
+    where F: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let entries: Vec<Entry> = from_str(corpus).unwrap();
+    for (index, entry) in entries.iter().enumerate() {
+        let scenario = entry.scenario.to_json();
+        let detail = match rebuild(&entry.scenario) {
+            Some(mut x) => compare(entry, &mut x),
+            None => Some("scenario could not be rebuilt".to_string())
+        };
+        if let Some(detail) = detail {
+            return Err(Mismatch { index:    index
+                                , detail:   detail
+                                , scenario: scenario });
+        }
+    }
+    Ok(())
+}
+
+// Returns the digest of the interactions and forces of every scenario in the
+// JSON corpus, rebuilt from its record by `rebuild`. Unlike `check_corpus`,
+// which allows for rounding, this identifies the exact bits the algorithm
+// produces.
+pub fn digest_corpus<F>(corpus: &str, rebuild: F) -> u64
+    where F: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let entries: Vec<Entry> = from_str(corpus).unwrap();
+    let mut digest = Digest::new();
+    for entry in entries.iter() {
+        match rebuild(&entry.scenario) {
+            Some(mut x) => {
+                digest.write_u32(x.interactions());
+                match x.avoidance() {
+                    Some(force) => {
+                        digest.write_u32(1);
+                        digest.write_vector(force);
+                    },
+                    None => digest.write_u32(0)
+                }
+            },
+            None => digest.write_u32(u32::MAX)
+        }
+    }
+    digest.finish()
+}
@@ -0,0 +1,264 @@
+// Samples exceeding the median by this factor are reported as outliers. Such
+// samples are typically caused by interrupts or page faults rather than the
+// code being timed.
+pub const OUTLIER_FACTOR: f64 = 10f64;
+
+// Log-linear histogram of non-negative values in the style of HDR histograms.
+// Values are grouped into buckets covering successive powers of two, each
+// divided into equally-sized sub-buckets, so that every recorded value is
+// represented with a relative error determined by the number of significant
+// decimal digits requested.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "CompactHistogram", into = "CompactHistogram")]
+pub struct Histogram { digits:   u32
+                     , sub_bits: u32
+                     , counts:   Vec<u64>
+                     , count:    u64
+                     , min:      i64
+                     , max:      i64
+                     , sum:      f64
+                     , sum_sq:   f64 }
+
+// Percentiles of a histogram's upper tail, its maximum, and the values
+// considered outliers.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Tail { pub p99:               i64
+                , pub p99_9:             i64
+                , pub p99_99:            i64
+                , pub max:               i64
+                , pub outlier_threshold: i64
+                , pub outliers:          Vec<(i64, u64)> }
+
+// Serialised form of a histogram in which only non-empty sub-buckets are
+// stored, as index and count pairs. The tail is included for the benefit of
+// readers and is recomputed when the histogram is read.
+#[derive(Serialize, Deserialize)]
+struct CompactHistogram { significant_digits: u32
+                        , count:              u64
+                        , min:                i64
+                        , max:                i64
+                        , sum:                f64
+                        , sum_sq:             f64
+                        , counts:             Vec<(usize, u64)>
+                        , #[serde(default)]
+                          tail:               Tail }
+
+impl From<Histogram> for CompactHistogram {
+    fn from(h: Histogram) -> CompactHistogram {
+        let counts = h.counts.iter()
+                             .enumerate()
+                             .filter(|&(_, &c)| c > 0)
+                             .map(|(i, &c)| (i, c))
+                             .collect();
+        CompactHistogram { significant_digits: h.digits
+                         , count:              h.count
+                         , min:                h.min
+                         , max:                h.max
+                         , sum:                h.sum
+                         , sum_sq:             h.sum_sq
+                         , counts:             counts
+                         , tail:               h.tail() }
+    }
+}
+
+impl From<CompactHistogram> for Histogram {
+    fn from(c: CompactHistogram) -> Histogram {
+        let mut h = Histogram::new(c.significant_digits);
+        for &(i, count) in c.counts.iter() {
+            if i >= h.counts.len() { h.counts.resize(i + 1, 0u64); }
+            h.counts[i] += count;
+        }
+        h.count = c.count;
+        h.min = c.min;
+        h.max = c.max;
+        h.sum = c.sum;
+        h.sum_sq = c.sum_sq;
+        h
+    }
+}
+
+impl Histogram {
+    // Creates an empty histogram that records values to the given number of
+    // significant decimal digits.
+    pub fn new(significant_digits: u32) -> Histogram {
+        // Each bucket is split into enough sub-buckets that adjacent values
+        // in its upper half differ by less than one part in 10^digits.
+        let resolution = 10f64.powi(significant_digits as i32);
+        let sub_bits = resolution.log2().ceil() as u32 + 1;
+        Histogram { digits:   significant_digits
+                  , sub_bits: sub_bits
+                  , counts:   vec!()
+                  , count:    0u64
+                  , min:      i64::max_value()
+                  , max:      0i64
+                  , sum:      0f64
+                  , sum_sq:   0f64 }
+    }
+
+    // Returns the index of the sub-bucket containing the given value.
+    fn index(&self, value: u64) -> usize {
+        let sub_count = 1u64 << self.sub_bits;
+        if value < sub_count { return value as usize; }
+        let half = sub_count >> 1;
+        let shift = 63 - value.leading_zeros() - (self.sub_bits - 1);
+        let sub = value >> shift;
+        (sub_count + (shift as u64 - 1) * half + (sub - half)) as usize
+    }
+
+    // Returns the largest value that falls in the sub-bucket with the given
+    // index.
+    fn highest_equivalent(&self, index: usize) -> i64 {
+        let sub_count = 1u64 << self.sub_bits;
+        let index = index as u64;
+        if index < sub_count { return index as i64; }
+        let half = sub_count >> 1;
+        let shift = (index - sub_count) / half + 1;
+        let sub = (index - sub_count) % half + half;
+        (((sub + 1) << shift) - 1) as i64
+    }
+
+    // Records a value. Negative values are recorded as zero.
+    pub fn record(&mut self, value: i64) {
+        let value = if value < 0 { 0 } else { value };
+        let index = self.index(value as u64);
+        if index >= self.counts.len() { self.counts.resize(index + 1, 0u64); }
+        self.counts[index] += 1;
+
+        self.count += 1;
+        if value < self.min { self.min = value; }
+        if value > self.max { self.max = value; }
+        self.sum += value as f64;
+        self.sum_sq += value as f64 * value as f64;
+    }
+
+    // Returns a histogram of the same values recorded to the given number of
+    // significant digits, which must not exceed the histogram's own. Each
+    // sub-bucket lies within one sub-bucket of a coarser histogram, so no
+    // value moves by more than the coarser precision.
+    pub fn coarsened(&self, significant_digits: u32) -> Histogram {
+        assert!( significant_digits <= self.digits
+               , "histograms can only be coarsened" );
+        let mut coarse = Histogram::new(significant_digits);
+        for (i, &count) in self.counts.iter().enumerate() {
+            if count == 0 { continue; }
+            let index = coarse.index(self.highest_equivalent(i) as u64);
+            if index >= coarse.counts.len() {
+                coarse.counts.resize(index + 1, 0u64);
+            }
+            coarse.counts[index] += count;
+        }
+        coarse.count = self.count;
+        coarse.min = self.min;
+        coarse.max = self.max;
+        coarse.sum = self.sum;
+        coarse.sum_sq = self.sum_sq;
+        coarse
+    }
+
+    // Adds the values recorded in another histogram. If the histograms differ
+    // in precision, the result has the lower of the two.
+    pub fn add(&mut self, other: &Histogram) {
+        if other.digits < self.digits {
+            *self = self.coarsened(other.digits);
+        }
+        let coarsened;
+        let other = if other.digits > self.digits {
+            coarsened = other.coarsened(self.digits);
+            &coarsened
+        } else {
+            other
+        };
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0u64);
+        }
+        for (i, &count) in other.counts.iter().enumerate() {
+            self.counts[i] += count;
+        }
+        self.count += other.count;
+        if other.min < self.min { self.min = other.min; }
+        if other.max > self.max { self.max = other.max; }
+        self.sum += other.sum;
+        self.sum_sq += other.sum_sq;
+    }
+
+    // Returns the number of significant decimal digits values are recorded
+    // to.
+    pub fn significant_digits(&self) -> u32 {
+        self.digits
+    }
+
+    // Returns the number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // Returns the smallest recorded value, or zero if there are none.
+    pub fn min(&self) -> i64 {
+        if self.count == 0 { 0 } else { self.min }
+    }
+
+    // Returns the largest recorded value, or zero if there are none.
+    pub fn max(&self) -> i64 {
+        self.max
+    }
+
+    // Returns the exact mean of the recorded values.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { return 0f64; }
+        self.sum / self.count as f64
+    }
+
+    // Returns the exact sample variance of the recorded values.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 { return 0f64; }
+        let n = self.count as f64;
+        let variance = (self.sum_sq - self.sum * self.sum / n) / (n - 1f64);
+        if variance < 0f64 { 0f64 } else { variance }
+    }
+
+    // Returns the value below which the given fraction of recorded values
+    // fall, to the histogram's precision.
+    pub fn value_at_quantile(&self, quantile: f64) -> i64 {
+        if self.count == 0 { return 0; }
+        let target = (quantile * self.count as f64).ceil().max(1f64) as u64;
+        let mut seen = 0u64;
+        for (i, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                let value = self.highest_equivalent(i);
+                return if value > self.max { self.max } else { value };
+            }
+        }
+        self.max
+    }
+
+    // Returns the values exceeding `OUTLIER_FACTOR` times the median, to the
+    // histogram's precision, with the number of times each was recorded.
+    pub fn outliers(&self) -> Vec<(i64, u64)> {
+        let threshold = self.outlier_threshold();
+        let mut outliers = vec!();
+        for (i, &count) in self.counts.iter().enumerate() {
+            let value = self.highest_equivalent(i);
+            if count > 0 && value > threshold {
+                let value = if value > self.max { self.max } else { value };
+                outliers.push((value, count));
+            }
+        }
+        outliers
+    }
+
+    // Returns the value above which recorded values are outliers.
+    fn outlier_threshold(&self) -> i64 {
+        (self.value_at_quantile(0.5f64) as f64 * OUTLIER_FACTOR) as i64
+    }
+
+    // Returns the upper tail of the histogram.
+    pub fn tail(&self) -> Tail {
+        Tail { p99:               self.value_at_quantile(0.99f64)
+             , p99_9:             self.value_at_quantile(0.999f64)
+             , p99_99:            self.value_at_quantile(0.9999f64)
+             , max:               self.max
+             , outlier_threshold: self.outlier_threshold()
+             , outliers:          self.outliers() }
+    }
+}
@@ -0,0 +1,156 @@
+use super::bench_utilities::configurations;
+use super::explain::{Format, Trace};
+use super::linalg::vector2d::Vec2D;
+use super::types::{HasScenario, Obstacles};
+
+// Number of scenarios checked for each obstacle configuration.
+pub const NUM_RUNS: u32 = 1_000;
+
+// Number of violations of each invariant itemised in reports.
+const MAX_ITEMS: usize = 5;
+
+// Relative tolerance applied to invariants involving floating-point
+// comparisons.
+const TOLERANCE: f64 = 1e-9;
+
+// Properties expected of avoidance forces.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Invariant { Away
+                   , Bounded
+                   , Finite
+                   , Budget }
+
+impl Invariant {
+    // Returns all invariants, in the order they are reported.
+    pub fn all() -> [Invariant; 4] {
+        [ Invariant::Away
+        , Invariant::Bounded
+        , Invariant::Finite
+        , Invariant::Budget ]
+    }
+
+    // Returns a short description of the invariant.
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Invariant::Away => "no component towards main threat",
+            Invariant::Bounded => "magnitude within documented bound",
+            Invariant::Finite => "force is finite",
+            Invariant::Budget => "potential budget between zero and one"
+        }
+    }
+}
+
+// Quantities of an algorithm's evaluation of a scenario that are subject to
+// invariants. `away` points away from the most threatening obstacle, `bound`
+// is the documented maximum magnitude of the force and `budget` is the
+// smallest remaining budget of algorithms that accumulate a bounded total.
+#[derive(Copy, Clone)]
+pub struct Evaluation { pub force:  Option<Vec2D>
+                      , pub away:   Option<Vec2D>
+                      , pub bound:  f64
+                      , pub budget: Option<f64> }
+
+impl Evaluation {
+    // Returns the invariants the evaluation violates, with details.
+    pub fn violations(&self) -> Vec<(Invariant, String)> {
+        let mut violations = vec!();
+        if let Some(budget) = self.budget {
+            if !(budget >= 0f64 && budget <= 1f64) {
+                let detail = format!("budget {}", budget);
+                violations.push((Invariant::Budget, detail));
+            }
+        }
+
+        let force = match self.force {
+            Some(force) => force,
+            None => return violations
+        };
+        if !force.x.is_finite() || !force.y.is_finite() {
+            violations.push((Invariant::Finite, format!("force {}", force)));
+            return violations;
+        }
+
+        let magnitude = force.mag();
+        if magnitude > self.bound * (1f64 + TOLERANCE) {
+            let detail = format!( "magnitude {} exceeds bound {}"
+                                , magnitude, self.bound );
+            violations.push((Invariant::Bounded, detail));
+        }
+
+        if let Some(away) = self.away {
+            let component = force.dot(away);
+            if component < -TOLERANCE * magnitude * away.mag() {
+                let detail = format!( "force {} opposes away direction {}"
+                                    , force, away );
+                violations.push((Invariant::Away, detail));
+            }
+        }
+        violations
+    }
+}
+
+// A violated invariant, the scenario in which it occurred and a trace of the
+// evaluation of that scenario.
+pub struct Violation { pub invariant: Invariant
+                     , pub obstacles: Obstacles
+                     , pub run:       u32
+                     , pub detail:    String
+                     , pub trace:     Trace }
+
+// Returns the violations of invariants in `NUM_RUNS` scenarios with the given
+// obstacles.
+pub fn check_scenarios<F>(obstacles: &Obstacles, creator: F) -> Vec<Violation>
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let mut violations = vec!();
+    for run in 0..NUM_RUNS {
+        let mut scenario = creator(obstacles);
+        let found = scenario.evaluation().violations();
+        for (invariant, detail) in found {
+            violations.push(Violation { invariant: invariant
+                                      , obstacles: *obstacles
+                                      , run:       run
+                                      , detail:    detail
+                                      , trace:     scenario.explain() });
+        }
+    }
+    violations
+}
+
+// Prints the number of violations of each invariant for each obstacle
+// configuration, followed by the first few violations of each invariant and
+// the scenarios in which they occurred.
+pub fn report_invariants<F>(creator: F)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let invariants = Invariant::all();
+    let mut violations = vec!();
+
+    println!( "{:>12} {:>8} {:>8} {:>8} {:>8}"
+            , "obstacles", "away", "bounded", "finite", "budget" );
+    for obstacles in configurations().iter() {
+        let found = check_scenarios(obstacles, |x| creator(x));
+        let counts: Vec<usize> = invariants.iter().map(|&i| {
+            found.iter().filter(|x| x.invariant == i).count()
+        }).collect();
+        let label = format!( "{} / {}"
+                           , obstacles.insignificant
+                           , obstacles.significant );
+        println!( "{:>12} {:>8} {:>8} {:>8} {:>8}"
+                , label, counts[0], counts[1], counts[2], counts[3] );
+        violations.extend(found);
+    }
+
+    for &invariant in invariants.iter() {
+        let items = violations.iter().filter(|x| x.invariant == invariant);
+        for violation in items.take(MAX_ITEMS) {
+            println!( "\n{} ({} / {} obstacles, run {}): {}"
+                    , invariant.description()
+                    , violation.obstacles.insignificant
+                    , violation.obstacles.significant
+                    , violation.run
+                    , violation.detail );
+            print!("{}", violation.trace.format(Format::Text));
+        }
+    }
+}
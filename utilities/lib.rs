@@ -1,5 +1,8 @@
 #![feature(test)]
 
+#[cfg(feature = "fuzz")]
+#[macro_use]
+extern crate arbitrary;
 extern crate getopts;
 extern crate linalg;
 extern crate rand;
@@ -11,7 +14,22 @@ extern crate test;
 extern crate time;
 
 pub mod bench_utilities;
+pub mod drift;
+pub mod explain;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod golden;
+pub mod histogram;
+pub mod invariants;
+pub mod merge;
+pub mod oracle;
+pub mod precision;
+pub mod progress;
+pub mod record;
+pub mod results;
 pub mod rng_utilities;
 pub mod test_utilities;
+mod tests;
 pub mod types;
 pub mod utilities;
+pub mod world;
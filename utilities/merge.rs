@@ -0,0 +1,213 @@
+use super::bench_utilities::{Batch, ObstacleBatch};
+use super::histogram::Histogram;
+use super::results::{csv_field, Results};
+use super::types::Obstacles;
+
+use std::io;
+use std::io::prelude::*;
+
+// Batches from several runs that share a configuration and machine.
+pub struct Group<'a> { pub algorithm: &'a str
+                     , pub variant:   &'a str
+                     , pub machine:   &'a str
+                     , pub obstacles: Obstacles
+                     , pub runs:      Vec<&'a Batch> }
+
+// Variance of the run times of a group, separated into the variance of
+// samples within each run and the variance of the means of the runs.
+#[derive(Copy, Clone)]
+pub struct Variance { pub runs:        usize
+                    , pub samples:     usize
+                    , pub mean:        f64
+                    , pub within_run:  f64
+                    , pub between_run: f64 }
+
+impl Variance {
+    // Returns the standard deviation of the means of the runs. Differences
+    // between configurations smaller than this are within run-to-run noise.
+    pub fn noise(&self) -> f64 {
+        self.between_run.sqrt()
+    }
+}
+
+impl<'a> Group<'a> {
+    // Returns whether the batch, taken from the given results, belongs in the
+    // group.
+    fn matches(&self, results: &Results, batch: &ObstacleBatch) -> bool {
+        self.algorithm == results.algorithm
+            && self.variant == results.variant
+            && self.machine == results.machine
+            && self.obstacles == *batch.obstacles()
+    }
+
+    // Returns a batch containing the run times of every run in the group. If
+    // any run was recorded in a histogram, all run times are pooled into a
+    // histogram of the lowest precision among the runs.
+    pub fn pooled(&self) -> ObstacleBatch {
+        let mut num_runs = 0u32;
+        let mut run_times = vec!();
+        let mut histogram: Option<Histogram> = None;
+        for run in self.runs.iter() {
+            num_runs += run.num_runs();
+            run_times.extend(run.run_times().iter().cloned());
+            if let Some(h) = run.histogram() {
+                match histogram {
+                    Some(ref mut pooled) => pooled.add(h),
+                    None => histogram = Some(h.clone())
+                }
+            }
+        }
+
+        let batch = match histogram {
+            Some(mut h) => {
+                for &t in run_times.iter() { h.record(t); }
+                Batch::with_histogram(num_runs, h)
+            },
+            None => Batch::new(num_runs, run_times)
+        };
+        ObstacleBatch::new(self.obstacles, batch)
+    }
+
+    // Returns the within-run and between-run variance of the group, or none if
+    // the group contains no run times.
+    pub fn variance(&self) -> Option<Variance> {
+        let runs: Vec<Moments> = self.runs.iter()
+                                          .map(|x| Moments::of(x))
+                                          .filter(|x| x.count > 0)
+                                          .collect();
+        if runs.is_empty() { return None; }
+
+        // Pool the variance of samples about the means of their runs.
+        let samples = runs.iter().fold(0usize, |a, x| a + x.count);
+        let total = runs.iter().fold(0f64, |a, x| a + x.mean * x.count as f64);
+        let sum_sq = runs.iter().fold(0f64, |a, x| {
+            a + x.variance * (x.count - 1) as f64
+        });
+        let k = runs.len();
+        let within_run = if samples > k {
+            sum_sq / (samples - k) as f64
+        } else {
+            0f64
+        };
+
+        // Determine spread of the means of the runs.
+        let mean_of_means = runs.iter().fold(0f64, |a, x| a + x.mean)
+                          / k as f64;
+        let between_run = if k > 1 {
+            runs.iter().fold(0f64, |a, x| {
+                a + (x.mean - mean_of_means) * (x.mean - mean_of_means)
+            }) / (k - 1) as f64
+        } else {
+            0f64
+        };
+
+        Some(Variance { runs:        k
+                      , samples:     samples
+                      , mean:        total / samples as f64
+                      , within_run:  within_run
+                      , between_run: between_run })
+    }
+}
+
+// Number, mean and sample variance of the run times of a single run.
+struct Moments { count:    usize
+               , mean:     f64
+               , variance: f64 }
+
+impl Moments {
+    // Returns the moments of the run times of the given batch.
+    fn of(batch: &Batch) -> Moments {
+        if let Some(h) = batch.histogram() {
+            return Moments { count:    h.count() as usize
+                           , mean:     h.mean()
+                           , variance: h.variance() };
+        }
+
+        let run_times = batch.run_times();
+        let count = run_times.len();
+        if count == 0 {
+            return Moments { count: 0, mean: 0f64, variance: 0f64 };
+        }
+        let mean = run_times.iter().fold(0f64, |a, &t| a + t as f64)
+                 / count as f64;
+        let sum_sq = run_times.iter().fold(0f64, |a, &t| {
+            a + (t as f64 - mean) * (t as f64 - mean)
+        });
+        let variance = if count > 1 {
+            sum_sq / (count - 1) as f64
+        } else {
+            0f64
+        };
+        Moments { count: count, mean: mean, variance: variance }
+    }
+}
+
+// Groups the batches of the given results by algorithm, variant, machine and
+// obstacle configuration. Groups are ordered by first appearance.
+pub fn group<'a>(results: &'a [Results]) -> Vec<Group<'a>> {
+    let mut groups: Vec<Group<'a>> = vec!();
+    for result in results.iter() {
+        for batch in result.batches.iter() {
+            if let Some(group) = groups.iter_mut()
+                                       .find(|x| x.matches(result, batch)) {
+                group.runs.push(batch.batch());
+                continue;
+            }
+            groups.push(Group { algorithm: &result.algorithm
+                              , variant:   &result.variant
+                              , machine:   &result.machine
+                              , obstacles: *batch.obstacles()
+                              , runs:      vec!(batch.batch()) });
+        }
+    }
+    groups
+}
+
+// Merges results from several runs, pooling the run times of batches that
+// share a configuration and machine. Returns one set of results for each
+// combination of algorithm, variant and machine.
+pub fn pool(results: &[Results]) -> Vec<Results> {
+    let mut pooled: Vec<Results> = vec!();
+    for group in group(results).iter() {
+        let batch = group.pooled();
+        if let Some(x) = pooled.iter_mut().find(|x| {
+            x.algorithm == group.algorithm
+                && x.variant == group.variant
+                && x.machine == group.machine
+        }) {
+            x.batches.push(batch);
+            continue;
+        }
+        pooled.push(Results { algorithm: group.algorithm.to_string()
+                            , variant:   group.variant.to_string()
+                            , machine:   group.machine.to_string()
+                            , batches:   vec!(batch) });
+    }
+    pooled
+}
+
+// Writes the variance of each of the given groups as CSV, one row per group.
+// Groups without run times are omitted.
+pub fn write_variance_csv<W: Write>(writer: &mut W, groups: &[Group])
+    -> io::Result<()>
+{
+    writeln!( writer
+            , "algorithm,variant,machine,total,insignificant,significant,\
+               runs,samples,mean,within_run_variance,between_run_variance,\
+               noise" )?;
+    for group in groups.iter() {
+        if let Some(v) = group.variance() {
+            writeln!( writer
+                    , "{},{},{},{},{},{},{},{},{},{},{},{}"
+                    , csv_field(group.algorithm)
+                    , csv_field(group.variant)
+                    , csv_field(group.machine)
+                    , group.obstacles.total
+                    , group.obstacles.insignificant
+                    , group.obstacles.significant
+                    , v.runs, v.samples, v.mean
+                    , v.within_run, v.between_run, v.noise() )?;
+        }
+    }
+    Ok(())
+}
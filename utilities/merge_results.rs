@@ -0,0 +1,65 @@
+extern crate getopts;
+use getopts::Options;
+
+extern crate utilities;
+use utilities::merge::{group, pool, write_variance_csv};
+use utilities::results::Results;
+use utilities::utilities::write_batches;
+
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::path::Path;
+use std::process::exit;
+
+// Merges the results of repeated runs and reports their variance.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optflag("p", "pool", "pool the run times of matching batches");
+    opts.optopt("r", "report", "write the variance report to FILE", "FILE");
+
+    let matches = opts.parse(&args[1..]).unwrap();
+    if matches.opt_present("h") || matches.free.len() < 2 {
+        let brief = format!("Usage: {} [options] OUTPUT INPUT...", program);
+        print!("{}", opts.usage(&brief));
+        return;
+    }
+
+    // Read the results of every run.
+    let mut results = vec!();
+    for input in matches.free[1..].iter() {
+        match Results::read(Path::new(input)) {
+            Ok(x) => results.extend(x),
+            Err(error) => {
+                println!("couldn't read {}: {}", input, error);
+                exit(1);
+            }
+        }
+    }
+
+    // Write merged results.
+    let output = Path::new(&matches.free[0]);
+    if matches.opt_present("pool") {
+        write_batches(&output, &pool(&results));
+    } else {
+        write_batches(&output, &results);
+    }
+
+    // Report variance of each configuration.
+    let groups = group(&results);
+    let reported = match matches.opt_str("report") {
+        Some(report) => match File::create(&report) {
+            Ok(file) => write_variance_csv(&mut BufWriter::new(file), &groups),
+            Err(error) => panic!("couldn't create {}: {}", report, error)
+        },
+        None => write_variance_csv(&mut io::stdout(), &groups)
+    };
+    if let Err(error) = reported {
+        panic!("couldn't write variance report: {}", error);
+    }
+}
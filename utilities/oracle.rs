@@ -0,0 +1,174 @@
+use super::bench_utilities::configurations;
+use super::linalg::geometry::{Capsule, Circle, Segment, Shape};
+use super::linalg::vector2d::{EPSILON, Vec2D};
+use super::types::{HasScenario, Obstacles};
+
+// Number of scenarios assessed for each obstacle configuration.
+pub const NUM_RUNS: u32 = 1_000;
+
+// Exact geometry of an obstacle.
+#[derive(Copy, Clone)]
+pub enum Geometry { Disk { centre: Vec2D, radius: f64 }
+                  , Segment { point1: Vec2D, point2: Vec2D }
+                  , Vehicle { position: Vec2D
+                            , velocity: Vec2D
+                            , radius:   f64 } }
+
+impl Geometry {
+    // Returns the named parameters defining the geometry.
+    pub fn parameters(&self) -> Vec<(&'static str, f64)> {
+        match *self {
+            Geometry::Disk { centre, radius } =>
+                vec!(("x", centre.x), ("y", centre.y), ("radius", radius)),
+            Geometry::Segment { point1, point2 } =>
+                vec!( ("x1", point1.x), ("y1", point1.y)
+                    , ("x2", point2.x), ("y2", point2.y) ),
+            Geometry::Vehicle { position, velocity, radius } =>
+                vec!( ("x", position.x), ("y", position.y)
+                    , ("vx", velocity.x), ("vy", velocity.y)
+                    , ("radius", radius) )
+        }
+    }
+}
+
+// Circular body of a vehicle moving at constant velocity for a period of
+// time.
+#[derive(Copy, Clone)]
+pub struct SweptBody { pub position: Vec2D
+                     , pub velocity: Vec2D
+                     , pub radius:   f64
+                     , pub horizon:  f64 }
+
+impl SweptBody {
+    // Returns the position of the body at the end of the horizon.
+    fn destination(&self) -> Vec2D {
+        self.position + self.velocity * self.horizon
+    }
+
+    // Returns the region the body covers within the horizon.
+    fn swept(&self) -> Capsule {
+        Capsule::new(self.position, self.destination(), self.radius)
+    }
+
+    // Returns whether the body touches the given obstacle at any time within
+    // the horizon, assuming moving obstacles keep their velocity.
+    pub fn collides(&self, geometry: &Geometry) -> bool {
+        match *geometry {
+            Geometry::Disk { centre, radius } =>
+                self.swept().overlaps(&Circle::new(centre, radius)),
+            Geometry::Segment { point1, point2 } =>
+                self.swept().overlaps(&Segment::new(point1, point2)),
+            Geometry::Vehicle { position, velocity, radius } => {
+                let relative_position = position - self.position;
+                let relative_velocity = velocity - self.velocity;
+                let speed_sq = relative_velocity.mag_sq();
+                let mut t = if speed_sq < EPSILON {
+                    0f64
+                } else {
+                    -relative_position.dot(relative_velocity) / speed_sq
+                };
+                if t < 0f64 { t = 0f64; }
+                if t > self.horizon { t = self.horizon; }
+                let nearest = relative_position + relative_velocity * t;
+                nearest.length() <= self.radius + radius
+            }
+        }
+    }
+}
+
+// An algorithm's assessment of the risk posed by an obstacle, and whether the
+// obstacle actually poses a risk according to the oracle.
+#[derive(Copy, Clone)]
+pub struct Risk { pub predicted: bool
+                , pub actual:    bool }
+
+impl Risk {
+    // Returns the risk of collision between the body and an obstacle with the
+    // given geometry, as predicted by an algorithm.
+    pub fn new(predicted: bool, body: &SweptBody, geometry: &Geometry) -> Risk {
+        Risk { predicted: predicted, actual: body.collides(geometry) }
+    }
+}
+
+// Counts of correct and incorrect risk assessments.
+#[derive(Copy, Clone, Default)]
+pub struct ConfusionMatrix { pub true_positives:  u32
+                           , pub false_positives: u32
+                           , pub true_negatives:  u32
+                           , pub false_negatives: u32 }
+
+impl ConfusionMatrix {
+    // Counts the given risk assessments.
+    pub fn add(&mut self, risks: &[Risk]) {
+        for risk in risks.iter() {
+            match (risk.predicted, risk.actual) {
+                (true, true) => self.true_positives += 1,
+                (true, false) => self.false_positives += 1,
+                (false, false) => self.true_negatives += 1,
+                (false, true) => self.false_negatives += 1
+            }
+        }
+    }
+
+    // Counts the assessments in another confusion matrix.
+    pub fn merge(&mut self, other: &ConfusionMatrix) {
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.true_negatives += other.true_negatives;
+        self.false_negatives += other.false_negatives;
+    }
+
+    // Returns the fraction of harmless obstacles assessed as risks.
+    pub fn false_positive_rate(&self) -> f64 {
+        let negatives = self.false_positives + self.true_negatives;
+        if negatives == 0 { return 0f64; }
+        self.false_positives as f64 / negatives as f64
+    }
+
+    // Returns the fraction of risky obstacles assessed as harmless.
+    pub fn false_negative_rate(&self) -> f64 {
+        let positives = self.true_positives + self.false_negatives;
+        if positives == 0 { return 0f64; }
+        self.false_negatives as f64 / positives as f64
+    }
+}
+
+// Returns the confusion matrix of the risk assessments made in `NUM_RUNS`
+// scenarios with the given obstacles.
+pub fn assess_scenarios<F>(obstacles: &Obstacles, creator: F)
+    -> ConfusionMatrix
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let mut matrix = ConfusionMatrix::default();
+    for _ in 0..NUM_RUNS {
+        matrix.add(&creator(obstacles).risks());
+    }
+    matrix
+}
+
+// Prints the confusion matrix of the algorithm's risk assessments for each
+// obstacle configuration, and for all configurations combined.
+pub fn report_risk<F>(creator: F)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+{
+    let print = |label: &str, m: &ConfusionMatrix| {
+        println!( "{:>12} {:>8} {:>8} {:>8} {:>8} {:>8.4} {:>8.4}"
+                , label
+                , m.true_positives, m.false_positives
+                , m.true_negatives, m.false_negatives
+                , m.false_positive_rate(), m.false_negative_rate() );
+    };
+
+    println!( "{:>12} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}"
+            , "obstacles", "TP", "FP", "TN", "FN", "FPR", "FNR" );
+    let mut total = ConfusionMatrix::default();
+    for obstacles in configurations().iter() {
+        let matrix = assess_scenarios(obstacles, |x| creator(x));
+        let label = format!( "{} / {}"
+                           , obstacles.insignificant
+                           , obstacles.significant );
+        print(&label, &matrix);
+        total.merge(&matrix);
+    }
+    print("all", &total);
+}
@@ -0,0 +1,61 @@
+use super::drift::{print_drift, Drift, NUM_RUNS};
+use super::linalg::scalar::Scalar;
+use super::record::Record;
+use super::types::{HasScenario, Obstacles};
+
+// Fixed-point format in which the fixed-point variant of algorithms
+// evaluates.
+pub use super::linalg::fixed_point::Q16;
+
+// Label of the variant of algorithms that evaluate in single precision.
+pub const SINGLE_VARIANT: &'static str = <f32 as Scalar>::NAME;
+
+// Label of the variant of algorithms that evaluate in fixed point.
+pub const FIXED_VARIANT: &'static str = <Q16 as Scalar>::NAME;
+
+// Returns a scenario generated by `creator` and the same scenario rebuilt
+// from its record by `lower`, which evaluates in a lower precision, such as
+// single precision or fixed point. Returns none if it could not be rebuilt.
+pub fn precision_scenarios<F, G>( obstacles: &Obstacles
+                                , creator:   F
+                                , lower:     G )
+    -> Option<(Box<HasScenario>, Box<HasScenario>)>
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let double = creator(obstacles);
+    let lower = lower(&double.record())?;
+    Some((double, lower))
+}
+
+// Returns the differences between `NUM_RUNS` scenarios with the given
+// obstacles, generated by `creator`, and the same scenarios evaluated in a
+// lower precision by `lower`.
+pub fn measure_precision<F, G>(obstacles: &Obstacles, creator: F, lower: G)
+    -> Drift
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let mut drift = Drift::default();
+    for _ in 0..NUM_RUNS {
+        let scenarios = precision_scenarios(obstacles, &creator, &lower);
+        if let Some((mut double, mut lower)) = scenarios {
+            drift.add(&mut double, &mut lower);
+        }
+    }
+    drift
+}
+
+// Prints the number of changed decisions and the largest and mean difference
+// between forces for each obstacle configuration, and for all configurations
+// combined, when scenarios are evaluated by `lower` in the scalar type
+// labelled `variant`.
+pub fn report_precision<F, G>(variant: &str, creator: F, lower: G)
+    where F: Fn(&Obstacles) -> Box<HasScenario>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    println!("{} against f64", variant);
+    print_drift(|obstacles| {
+        measure_precision(obstacles, |x| creator(x), |x| lower(x))
+    });
+}
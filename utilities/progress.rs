@@ -0,0 +1,44 @@
+use super::time::PreciseTime;
+
+use std::io::prelude::*;
+use std::io::stderr;
+
+// Number of progress updates printed over the course of a task.
+const NUM_UPDATES: u32 = 100;
+
+// Reports the progress of a long-running task, and an estimate of the time
+// remaining, to standard error.
+pub struct Progress { label: String
+                    , total: u32
+                    , step:  u32
+                    , start: PreciseTime }
+
+impl Progress {
+    // Begins reporting the progress of a task consisting of `total` steps.
+    pub fn new(label: &str, total: u32) -> Progress {
+        let step = if total < NUM_UPDATES { 1 } else { total / NUM_UPDATES };
+        Progress { label: label.to_string()
+                 , total: total
+                 , step:  step
+                 , start: PreciseTime::now() }
+    }
+
+    // Records that `done` steps have been completed. Progress is only printed
+    // periodically to limit interference with the task.
+    pub fn update(&self, done: u32) {
+        if done == 0 || (done % self.step != 0 && done != self.total) {
+            return;
+        }
+
+        let elapsed = self.start.to(PreciseTime::now()).num_milliseconds();
+        let remaining = elapsed as f64 * (self.total - done) as f64
+                      / done as f64;
+        let percent = 100f64 * done as f64 / self.total as f64;
+        let mut err = stderr();
+        let _ = write!( err
+                      , "\r{}: {:5.1}% (ETA {:.0} seconds)  "
+                      , self.label, percent, remaining / 1000f64 );
+        if done == self.total { let _ = writeln!(err, ""); }
+        let _ = err.flush();
+    }
+}
@@ -0,0 +1,25 @@
+use super::explain::Values;
+use super::serde_json::to_string;
+
+// Parameters defining a scenario's vehicle and each of its obstacles, from
+// which the scenario can be reconstructed.
+#[derive(Serialize, Deserialize)]
+pub struct Record { pub vehicle:   Values
+                  , pub obstacles: Vec<Values> }
+
+impl Record {
+    // Creates a record of a scenario with the given vehicle and no obstacles.
+    pub fn new(vehicle: &[(&str, f64)]) -> Record {
+        Record { vehicle: Values::new(vehicle), obstacles: vec!() }
+    }
+
+    // Records an obstacle defined by the given parameters.
+    pub fn obstacle(&mut self, parameters: &[(&str, f64)]) {
+        self.obstacles.push(Values::new(parameters));
+    }
+
+    // Returns the record as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        to_string(self).unwrap()
+    }
+}
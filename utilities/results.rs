@@ -0,0 +1,342 @@
+use super::bench_utilities::{Batch, ObstacleBatch};
+use super::histogram::{Histogram, OUTLIER_FACTOR};
+use super::serde_json;
+
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Label given to results written before algorithms and variants were
+// recorded.
+pub const UNLABELLED: &'static str = "unknown";
+
+// Label of the variant produced by running a benchmark without options.
+pub const DEFAULT_VARIANT: &'static str = "default";
+
+// Benchmark results produced by a single program, labelled with the algorithm
+// and variant that produced them and the machine they were produced on.
+#[derive(Serialize, Deserialize)]
+pub struct Results { pub algorithm: String
+                   , pub variant:   String
+                   , #[serde(default = "unlabelled")]
+                     pub machine:   String
+                   , pub batches:   Vec<ObstacleBatch> }
+
+// Layouts of results files. Merged files contain results from several runs
+// and older files contain only a list of batches.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResultsFile { Labelled(Results)
+                 , Merged(Vec<Results>)
+                 , Unlabelled(Vec<ObstacleBatch>) }
+
+// Returns the label used for missing labels.
+fn unlabelled() -> String {
+    UNLABELLED.to_string()
+}
+
+// Returns the contents of the given file, if it can be read.
+fn read_file(filepath: &str) -> Option<String> {
+    let mut contents = String::new();
+    let mut file = match File::open(filepath) { Ok(x) => x, _ => return None };
+    if file.read_to_string(&mut contents).is_err() { return None; }
+    Some(contents)
+}
+
+// Returns a description of the current machine composed of its host name,
+// operating system, architecture and processor model. Results produced on the
+// same machine have the same fingerprint.
+pub fn machine_fingerprint() -> String {
+    let host = env::var("HOSTNAME").ok()
+        .or_else(|| read_file("/etc/hostname"))
+        .map(|x| x.trim().to_string())
+        .unwrap_or(unlabelled());
+    let cpu = read_file("/proc/cpuinfo").and_then(|x| {
+        x.lines()
+         .filter(|line| line.starts_with("model name"))
+         .filter_map(|line| line.splitn(2, ':').nth(1))
+         .map(|model| model.trim().to_string())
+         .next()
+    }).unwrap_or(unlabelled());
+    format!("{}/{}-{}/{}", host, env::consts::OS, env::consts::ARCH, cpu)
+}
+
+// Reasons why results could not be read.
+#[derive(Debug)]
+pub enum ReadError { Io(io::Error)
+                   , Parse(serde_json::Error) }
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReadError::Io(ref error) => write!(f, "{}", error),
+            ReadError::Parse(ref error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl Results {
+    // Creates results produced on the current machine from the given values.
+    pub fn new(algorithm: &str, variant: &str, batches: Vec<ObstacleBatch>)
+        -> Results
+    {
+        Results { algorithm: algorithm.to_string()
+                , variant:   variant.to_string()
+                , machine:   machine_fingerprint()
+                , batches:   batches }
+    }
+
+    // Parses results from JSON. Unlabelled results are given the label
+    // `UNLABELLED` for algorithm, variant and machine.
+    pub fn from_json(json: &str) -> Result<Vec<Results>, ReadError> {
+        match serde_json::from_str(json) {
+            Ok(ResultsFile::Labelled(results)) => Ok(vec!(results)),
+            Ok(ResultsFile::Merged(results)) => Ok(results),
+            Ok(ResultsFile::Unlabelled(batches)) => {
+                let results = Results { algorithm: unlabelled()
+                                      , variant:   unlabelled()
+                                      , machine:   unlabelled()
+                                      , batches:   batches };
+                Ok(vec!(results))
+            },
+            Err(error) => Err(ReadError::Parse(error))
+        }
+    }
+
+    // Reads results from the specified file. Merged files yield the results
+    // of each run they contain.
+    pub fn read(filepath: &Path) -> Result<Vec<Results>, ReadError> {
+        let mut json = String::new();
+        let mut file = File::open(filepath).map_err(ReadError::Io)?;
+        file.read_to_string(&mut json).map_err(ReadError::Io)?;
+        Results::from_json(&json)
+    }
+}
+
+// Summary statistics of the run times of a batch, in nanoseconds. Values
+// derived from histograms are accurate to the histogram's precision.
+#[derive(Copy, Clone)]
+pub struct Summary { pub count:    usize
+                   , pub mean:     f64
+                   , pub std_dev:  f64
+                   , pub min:      i64
+                   , pub median:   f64
+                   , pub p99:      i64
+                   , pub p99_9:    i64
+                   , pub p99_99:   i64
+                   , pub max:      i64
+                   , pub outliers: u64 }
+
+// Returns the value below which the given fraction of the sorted values fall.
+fn quantile(sorted: &Vec<i64>, q: f64) -> i64 {
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[if rank == 0 { 0 } else { rank - 1 }]
+}
+
+impl Summary {
+    // Returns the summary of the given batch's run times, or none if the
+    // batch contains no run times.
+    pub fn of(batch: &Batch) -> Option<Summary> {
+        if let Some(h) = batch.histogram() {
+            return Summary::of_histogram(h);
+        }
+
+        let run_times = batch.run_times();
+        let count = run_times.len();
+        if count == 0 { return None; }
+
+        let mut sorted = run_times.clone();
+        sorted.sort();
+        let median = if count % 2 == 0 {
+            0.5f64 * (sorted[count / 2 - 1] + sorted[count / 2]) as f64
+        } else {
+            sorted[count / 2] as f64
+        };
+
+        let mean = sorted.iter().fold(0f64, |a, &x| a + x as f64)
+                 / count as f64;
+        let sum_sq = sorted.iter().fold(0f64, |a, &x| {
+            a + (x as f64 - mean) * (x as f64 - mean)
+        });
+        let std_dev = if count > 1 {
+            (sum_sq / (count - 1) as f64).sqrt()
+        } else {
+            0f64
+        };
+
+        let threshold = median * OUTLIER_FACTOR;
+        let outliers = sorted.iter().filter(|&&x| x as f64 > threshold).count();
+
+        Some(Summary { count:    count
+                     , mean:     mean
+                     , std_dev:  std_dev
+                     , min:      sorted[0]
+                     , median:   median
+                     , p99:      quantile(&sorted, 0.99f64)
+                     , p99_9:    quantile(&sorted, 0.999f64)
+                     , p99_99:   quantile(&sorted, 0.9999f64)
+                     , max:      sorted[count - 1]
+                     , outliers: outliers as u64 })
+    }
+
+    // Returns the summary of the run times in the given histogram, or none if
+    // it is empty.
+    fn of_histogram(h: &Histogram) -> Option<Summary> {
+        if h.count() == 0 { return None; }
+        let tail = h.tail();
+        Some(Summary { count:    h.count() as usize
+                     , mean:     h.mean()
+                     , std_dev:  h.variance().sqrt()
+                     , min:      h.min()
+                     , median:   h.value_at_quantile(0.5f64) as f64
+                     , p99:      tail.p99
+                     , p99_9:    tail.p99_9
+                     , p99_99:   tail.p99_99
+                     , max:      tail.max
+                     , outliers: tail.outliers.iter().map(|x| x.1).sum() })
+    }
+}
+
+// Criteria for selecting batches from results. Criteria that are not set
+// match every batch.
+#[derive(Clone, Default)]
+pub struct Filter { algorithm:     Option<String>
+                  , variant:       Option<String>
+                  , total:         Option<u32>
+                  , insignificant: Option<u32>
+                  , significant:   Option<u32> }
+
+impl Filter {
+    // Returns a filter that matches every batch.
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    // Restricts the filter to the given algorithm.
+    pub fn algorithm(mut self, algorithm: &str) -> Filter {
+        self.algorithm = Some(algorithm.to_string());
+        self
+    }
+
+    // Restricts the filter to the given variant.
+    pub fn variant(mut self, variant: &str) -> Filter {
+        self.variant = Some(variant.to_string());
+        self
+    }
+
+    // Restricts the filter to the given total number of obstacles.
+    pub fn total(mut self, total: u32) -> Filter {
+        self.total = Some(total);
+        self
+    }
+
+    // Restricts the filter to the given number of insignificant obstacles.
+    pub fn insignificant(mut self, insignificant: u32) -> Filter {
+        self.insignificant = Some(insignificant);
+        self
+    }
+
+    // Restricts the filter to the given number of significant obstacles.
+    pub fn significant(mut self, significant: u32) -> Filter {
+        self.significant = Some(significant);
+        self
+    }
+
+    // Returns whether the batch, taken from the given results, satisfies the
+    // filter.
+    pub fn matches(&self, results: &Results, batch: &ObstacleBatch) -> bool {
+        let obstacles = batch.obstacles();
+        let same = |criterion: &Option<String>, value: &String| {
+            criterion.as_ref().map_or(true, |x| x == value)
+        };
+        let equal = |criterion: Option<u32>, value: u32| {
+            criterion.map_or(true, |x| x == value)
+        };
+        same(&self.algorithm, &results.algorithm)
+            && same(&self.variant, &results.variant)
+            && equal(self.total, obstacles.total)
+            && equal(self.insignificant, obstacles.insignificant)
+            && equal(self.significant, obstacles.significant)
+    }
+}
+
+// A batch selected from results, along with the labels of those results.
+pub struct Entry<'a> { pub algorithm: &'a str
+                     , pub variant:   &'a str
+                     , pub batch:     &'a ObstacleBatch }
+
+// Returns the batches from the given results that satisfy the filter.
+pub fn select<'a>(results: &'a [Results], filter: &Filter) -> Vec<Entry<'a>> {
+    let mut entries = vec!();
+    for result in results.iter() {
+        for batch in result.batches.iter() {
+            if filter.matches(result, batch) {
+                entries.push(Entry { algorithm: &result.algorithm
+                                   , variant:   &result.variant
+                                   , batch:     batch });
+            }
+        }
+    }
+    entries
+}
+
+// Quotes a CSV field if it contains characters that require it.
+pub fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace("\"", "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Returns the CSV fields that identify the given entry.
+fn csv_labels(entry: &Entry) -> String {
+    let obstacles = entry.batch.obstacles();
+    format!( "{},{},{},{},{}"
+           , csv_field(entry.algorithm)
+           , csv_field(entry.variant)
+           , obstacles.total
+           , obstacles.insignificant
+           , obstacles.significant )
+}
+
+// Writes the run times of the given entries as CSV, one row per sample.
+// Batches recorded in histograms have no individual samples and are omitted.
+pub fn write_sample_csv<W: Write>(writer: &mut W, entries: &[Entry])
+    -> io::Result<()>
+{
+    writeln!( writer
+            , "algorithm,variant,total,insignificant,significant,\
+               sample,nanoseconds" )?;
+    for entry in entries.iter() {
+        let labels = csv_labels(entry);
+        for (i, time) in entry.batch.batch().run_times().iter().enumerate() {
+            writeln!(writer, "{},{},{}", labels, i, time)?;
+        }
+    }
+    Ok(())
+}
+
+// Writes summaries of the run times of the given entries as CSV, one row per
+// batch. Batches without run times are omitted.
+pub fn write_summary_csv<W: Write>(writer: &mut W, entries: &[Entry])
+    -> io::Result<()>
+{
+    writeln!( writer
+            , "algorithm,variant,total,insignificant,significant,\
+               count,mean,std_dev,min,median,p99,p99_9,p99_99,max,\
+               outliers" )?;
+    for entry in entries.iter() {
+        if let Some(s) = Summary::of(entry.batch.batch()) {
+            writeln!( writer
+                    , "{},{},{},{},{},{},{},{},{},{},{}"
+                    , csv_labels(entry)
+                    , s.count, s.mean, s.std_dev, s.min, s.median
+                    , s.p99, s.p99_9, s.p99_99, s.max, s.outliers )?;
+        }
+    }
+    Ok(())
+}
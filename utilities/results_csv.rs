@@ -0,0 +1,90 @@
+extern crate getopts;
+use getopts::{Matches, Options};
+
+extern crate utilities;
+use utilities::results::*;
+
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::process::exit;
+
+// Parses an optional obstacle count from the command line.
+fn count(matches: &Matches, name: &str) -> Option<u32> {
+    matches.opt_str(name).map(|x| match x.parse() {
+        Ok(value) => value,
+        Err(_) => {
+            println!("invalid value for --{}: {}", name, x);
+            exit(1)
+        }
+    })
+}
+
+// Converts benchmark results to CSV, one row per sample or per batch.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optflag("s", "summary", "write one summary row per batch");
+    opts.optopt("a", "algorithm", "only include the given algorithm", "NAME");
+    opts.optopt("v", "variant", "only include the given variant", "NAME");
+    opts.optopt("", "total", "only include this many obstacles", "N");
+    opts.optopt("", "insignificant", "only include this many insignificant \
+                                     obstacles", "N");
+    opts.optopt("", "significant", "only include this many significant \
+                                   obstacles", "N");
+
+    let matches = opts.parse(&args[1..]).unwrap();
+    if matches.opt_present("h") || matches.free.len() < 2 {
+        let brief = format!("Usage: {} [options] OUTPUT INPUT...", program);
+        print!("{}", opts.usage(&brief));
+        return;
+    }
+
+    // Build filter from options.
+    let mut filter = Filter::new();
+    if let Some(x) = matches.opt_str("algorithm") {
+        filter = filter.algorithm(&x);
+    }
+    if let Some(x) = matches.opt_str("variant") { filter = filter.variant(&x); }
+    if let Some(x) = count(&matches, "total") { filter = filter.total(x); }
+    if let Some(x) = count(&matches, "insignificant") {
+        filter = filter.insignificant(x);
+    }
+    if let Some(x) = count(&matches, "significant") {
+        filter = filter.significant(x);
+    }
+
+    // Read all input files.
+    let mut results = vec!();
+    for input in matches.free[1..].iter() {
+        match Results::read(Path::new(input)) {
+            Ok(x) => results.extend(x),
+            Err(error) => {
+                println!("couldn't read {}: {}", input, error);
+                exit(1);
+            }
+        }
+    }
+
+    // Write selected batches.
+    let output = Path::new(&matches.free[0]);
+    let mut writer = match File::create(&output) {
+        Ok(file) => BufWriter::new(file),
+        Err(error) => panic!( "couldn't create {}: {}"
+                            , output.display()
+                            , error )
+    };
+    let entries = select(&results, &filter);
+    let written = if matches.opt_present("summary") {
+        write_summary_csv(&mut writer, &entries)
+    } else {
+        write_sample_csv(&mut writer, &entries)
+    };
+    if let Err(error) = written {
+        panic!("couldn't write to {}: {}", output.display(), error);
+    }
+}
@@ -1,3 +1,4 @@
+use super::linalg::vector2d::Vec2D;
 use super::rand::distributions::{IndependentSample, Range};
 use super::rand::thread_rng;
 
@@ -21,3 +22,35 @@ pub fn random_tau() -> f64 {
 pub fn random_margin() -> f64 {
     Range::new(0.1f64, 0.9f64).ind_sample(&mut thread_rng())
 }
+
+// Offset from a decision boundary used by boundary scenarios. A power of two,
+// so that offset geometry remains exactly representable.
+pub const BOUNDARY_MARGIN: f64 = 1f64 / 1024f64;
+
+// Returns a random index less than `n` using the thread's random number
+// generator.
+pub fn random_index(n: usize) -> usize {
+    Range::new(0usize, n).ind_sample(&mut thread_rng())
+}
+
+// Returns a random integer between `low` and `high` inclusive, as a float.
+// Integer coordinates keep boundary geometry exactly representable.
+pub fn random_integer(low: i32, high: i32) -> f64 {
+    Range::new(low, high + 1).ind_sample(&mut thread_rng()) as f64
+}
+
+// Returns one of the four axis-aligned unit vectors at random. Rotating by
+// multiples of a right angle in this way introduces no rounding error.
+pub fn random_axis() -> Vec2D {
+    match random_index(4) {
+        0 => Vec2D::unitx(),
+        1 => Vec2D::unity(),
+        2 => -Vec2D::unitx(),
+        _ => -Vec2D::unity()
+    }
+}
+
+// Returns a random position with integer coordinates.
+pub fn random_grid_point() -> Vec2D {
+    Vec2D::new(random_integer(-100, 100), random_integer(-100, 100))
+}
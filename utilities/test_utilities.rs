@@ -1,35 +1,248 @@
+use super::bench_utilities::configurations;
+use super::drift::{offset, offset_scenarios, Drift};
+use super::linalg::batch::BLOCK;
+use super::linalg::vector2d::Vec2D;
+use super::record::Record;
 use super::types::{HasScenario, Obstacles};
+use super::world::{Kind, World, Worlds};
+
+use std::fmt;
 
 // Number of each test to execute.
 pub const NUM_RUNS: u32 = 1_000;
 
+// Details of a scenario in which an algorithm did not behave as expected:
+// the iteration at which it occurred, the expected (if checked) and actual
+// interactions and avoidance result, and the serialised scenario, if one was
+// created.
+pub struct Failure { pub reason:                &'static str
+                   , pub iteration:             u32
+                   , pub expected_interactions: Option<u32>
+                   , pub interactions:          u32
+                   , pub expected_avoidance:    Option<bool>
+                   , pub avoidance:             Option<Vec2D>
+                   , pub scenario:              Option<String> }
+
+impl Failure {
+    // Creates a report of a failure, evaluating the scenario it occurred in.
+    pub fn new( reason:                &'static str
+              , iteration:             u32
+              , expected_interactions: Option<u32>
+              , expected_avoidance:    Option<bool>
+              , scenario:              Option<&mut Box<HasScenario>> )
+        -> Failure
+    {
+        let mut failure = Failure { reason:                reason
+                                  , iteration:             iteration
+                                  , expected_interactions: expected_interactions
+                                  , interactions:          0u32
+                                  , expected_avoidance:    expected_avoidance
+                                  , avoidance:             None
+                                  , scenario:              None };
+        if let Some(scenario) = scenario {
+            failure.interactions = scenario.interactions();
+            failure.avoidance = scenario.avoidance();
+            failure.scenario = Some(scenario.record().to_json());
+        }
+        failure
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} at iteration {}", self.reason, self.iteration)?;
+
+        let expected = match self.expected_interactions {
+            Some(x) => x.to_string(),
+            None => "unchecked".to_string()
+        };
+        writeln!( f, "interactions: expected {}, actual {}"
+                , expected, self.interactions )?;
+
+        let expected = match self.expected_avoidance {
+            Some(true) => "some",
+            Some(false) => "none",
+            None => "unchecked"
+        };
+        match self.avoidance {
+            Some(x) => writeln!( f, "avoidance: expected {}, actual {}"
+                               , expected, x )?,
+            None => writeln!( f, "avoidance: expected {}, actual none"
+                            , expected )?
+        }
+
+        match self.scenario {
+            Some(ref x) => write!(f, "scenario: {}", x),
+            None => write!(f, "scenario: none")
+        }
+    }
+}
+
+// Failures are reported in full when unwrapped.
+impl fmt::Debug for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\n{}", self)
+    }
+}
+
 // Tests whether the scenario produces the expected number of interactions and
-// avoidance force.
-pub fn test_scenarios<F>(obstacles: &Obstacles, creator: F) -> bool
+// avoidance force, returning a report of the first scenario that does not.
+pub fn test_scenarios<F>(obstacles: &Obstacles, creator: F)
+    -> Result<(), Failure>
     where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
 {
-    let significance = obstacles.significant > 0;
-    for _ in 0..NUM_RUNS {
+    let interactions = Some(obstacles.significant);
+    let significance = Some(obstacles.significant > 0);
+    for i in 0..NUM_RUNS {
         let mut scenario = match creator(obstacles) {
             Some(scenario) => scenario,
-            None => {
-                println!("Creation function failed to return scenario");
-                return false
-            }
+            None => return Err(Failure::new( "Creation function failed to \
+                                              return scenario"
+                                           , i, interactions, significance
+                                           , None ))
         };
 
         // Check whether the expected number of interactions occurred.
         if scenario.interactions() != obstacles.significant {
-            println!("Unexpected number of interactions");
-            return false;
+            return Err(Failure::new( "Unexpected number of interactions"
+                                   , i, interactions, significance
+                                   , Some(&mut scenario) ));
         }
 
         // Check whether an avoidance force was generated.
         let avoidance = scenario.avoidance().is_some();
-        if (avoidance && !significance) || (!avoidance && significance) {
-            println!("Unexpected avoidance result");
-            return false;
+        if Some(avoidance) != significance {
+            return Err(Failure::new( "Unexpected avoidance result"
+                                   , i, interactions, significance
+                                   , Some(&mut scenario) ));
+        }
+    }
+    Ok(())
+}
+
+// Tests scenarios, as `test_scenarios` does, both as generated by `creator`
+// and as rebuilt from their records by `lower`, which evaluates in a lower
+// precision, such as single precision or fixed point.
+pub fn test_precisions<F, G>(obstacles: &Obstacles, creator: F, lower: G)
+    -> Result<(), Failure>
+    where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    test_scenarios(obstacles, &creator)?;
+    test_scenarios(obstacles, |x| lower(&creator(x)?.record()))
+}
+
+// Returns whether two avoidance results are identical, including the bits of
+// any non-finite components.
+fn identical(a: Option<Vec2D>, b: Option<Vec2D>) -> bool {
+    let bits = |v: Option<Vec2D>| v.map(|v| (v.x.to_bits(), v.y.to_bits()));
+    bits(a) == bits(b)
+}
+
+// Checks that `NUM_RUNS` scenarios of each obstacle configuration, and of
+// configurations spanning several blocks of lanes, generated by `creator`,
+// make exactly the same decisions and produce exactly the same forces when
+// rebuilt from their records by `rebuild` and by `batched`, which evaluates
+// obstacles in batches.
+pub fn test_batches<F, G, H>(creator: F, rebuild: G, batched: H)
+    -> Result<(), Failure>
+    where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+        , H: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let spanning = 2 * BLOCK as u32 + 3;
+    let mut obstacles = configurations();
+    obstacles.push(Obstacles::new(spanning, 0u32));
+    obstacles.push(Obstacles::new(0u32, spanning));
+    for obstacles in obstacles.iter() {
+        for i in 0..NUM_RUNS {
+            let record = creator(obstacles).unwrap().record();
+            let rebuilt = rebuild(&record).and_then(|x| {
+                Some((x, batched(&record)?))
+            });
+            let (mut scenario, mut batch) = match rebuilt {
+                Some(x) => x,
+                None => return Err(Failure::new( "Scenario could not be \
+                                                  rebuilt"
+                                               , i, None, None, None ))
+            };
+
+            let interactions = scenario.interactions();
+            let avoidance = scenario.avoidance();
+            if batch.interactions() != interactions
+                || !identical(batch.avoidance(), avoidance)
+            {
+                return Err(Failure::new( "Batched scenario differs from the \
+                                          original"
+                                       , i, Some(interactions)
+                                       , Some(avoidance.is_some())
+                                       , Some(&mut batch) ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Relative tolerance of comparisons between worlds and the scenarios built
+// from them.
+const WORLD_TOLERANCE: f64 = 1e-9;
+
+// Checks that scenarios built by `from_world` from `NUM_RUNS` shared worlds
+// of each obstacle configuration describe exactly those worlds.
+pub fn test_worlds<F>(kind: Kind, from_world: F) -> Result<(), Failure>
+    where F: Fn(&World) -> Box<HasScenario>
+{
+    let worlds = Worlds::new(kind);
+    for obstacles in configurations().iter() {
+        for i in 0..NUM_RUNS {
+            let world = worlds.next(obstacles);
+            let mut scenario = from_world(&world);
+            if !world.matches(&scenario.record(), WORLD_TOLERANCE) {
+                return Err(Failure::new( "Scenario does not match its world"
+                                       , i, None, None
+                                       , Some(&mut scenario) ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Largest distance between the forces of a scenario and the same scenario
+// translated far from the origin accepted by `test_drift`.
+const DRIFT_TOLERANCE: f64 = 1e-9;
+
+// Checks that `NUM_RUNS` scenarios of each obstacle configuration, generated
+// by `creator` and rebuilt from records by `rebuild`, make the same decisions
+// and produce the same forces, to within the tolerance, when translated by
+// the given distance along each axis.
+pub fn test_drift<F, G>(distance: f64, creator: F, rebuild: G)
+    -> Result<(), Failure>
+    where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
+        , G: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    let generate = |o: &Obstacles| creator(o).unwrap();
+    for obstacles in configurations().iter() {
+        for i in 0..NUM_RUNS {
+            let scenarios = offset_scenarios( obstacles, offset(distance)
+                                            , &generate, &rebuild );
+            let (mut centred, mut translated) = match scenarios {
+                Some(x) => x,
+                None => return Err(Failure::new( "Scenario could not be \
+                                                  rebuilt"
+                                               , i, None, None, None ))
+            };
+
+            let mut drift = Drift::default();
+            drift.add(&mut centred, &mut translated);
+            if drift.decisions > 0 || drift.max_drift > DRIFT_TOLERANCE {
+                let interactions = Some(centred.interactions());
+                let avoidance = Some(centred.avoidance().is_some());
+                return Err(Failure::new( "Translated scenario drifts from \
+                                          the original"
+                                       , i, interactions, avoidance
+                                       , Some(&mut translated) ));
+            }
         }
     }
-    return true;
+    Ok(())
 }
@@ -0,0 +1,167 @@
+#![cfg(test)]
+
+use super::bench_utilities::{Batch, ObstacleBatch, Recording};
+use super::drift::translate_record;
+use super::histogram::Histogram;
+use super::invariants::{Evaluation, Invariant};
+use super::linalg::vector2d::Vec2D;
+use super::merge::{group, pool};
+use super::record::Record;
+use super::results::*;
+use super::serde_json::{from_str, to_string};
+use super::types::Obstacles;
+
+// Returns labelled results containing two small batches.
+fn results() -> Results {
+    let batch1 = ObstacleBatch::new( Obstacles::new(1u32, 0u32)
+                                   , Batch::new(3u32, vec!(4, 1, 2)) );
+    let batch2 = ObstacleBatch::new( Obstacles::new(0u32, 1u32)
+                                   , Batch::new(3u32, vec!(5, 6, 7)) );
+    Results::new("algorithm", DEFAULT_VARIANT, vec!(batch1, batch2))
+}
+
+#[test]
+fn read_results() {
+    let labelled = Results::from_json(&to_string(&results()).unwrap());
+    assert_eq!(labelled.unwrap()[0].algorithm, "algorithm");
+
+    let merged = to_string(&vec!(results(), results())).unwrap();
+    assert_eq!(Results::from_json(&merged).unwrap().len(), 2);
+
+    let batches = to_string(&results().batches).unwrap();
+    let unlabelled = Results::from_json(&batches).unwrap();
+    assert_eq!(unlabelled[0].machine, UNLABELLED);
+    assert_eq!(unlabelled[0].batches.len(), 2);
+}
+
+#[test]
+fn filter_and_export() {
+    let all = vec!(results());
+    assert_eq!(select(&all, &Filter::new()).len(), 2);
+    assert_eq!(select(&all, &Filter::new().variant("other")).len(), 0);
+
+    let entries = select(&all, &Filter::new().significant(0u32));
+    assert_eq!(entries.len(), 1);
+    let summary = Summary::of(entries[0].batch.batch()).unwrap();
+    assert_eq!((summary.min, summary.median, summary.max), (1, 2f64, 4));
+
+    let mut samples = vec!();
+    write_sample_csv(&mut samples, &entries).unwrap();
+    let samples = String::from_utf8(samples).unwrap();
+    assert_eq!(samples.lines().count(), 4);
+    assert_eq!(samples.lines().nth(1), Some("algorithm,default,1,1,0,0,4"));
+}
+
+#[test]
+fn merge_runs() {
+    let runs = vec!(results(), results());
+    let groups = group(&runs);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].runs.len(), 2);
+
+    let variance = groups[1].variance().unwrap();
+    assert_eq!((variance.runs, variance.samples), (2, 6));
+    assert_eq!((variance.within_run, variance.between_run), (1f64, 0f64));
+
+    let pooled = pool(&runs);
+    assert_eq!(pooled.len(), 1);
+    assert_eq!(pooled[0].batches[0].batch().run_times().len(), 6);
+}
+
+// Runs recorded to different precisions pool at the lower precision.
+#[test]
+fn pool_mixed_precisions() {
+    let mut fine = Histogram::new(3u32);
+    let mut coarse = Histogram::new(1u32);
+    for i in 1..1001 {
+        fine.record(i);
+        coarse.record(i + 1000);
+    }
+    let run = |h: Histogram| {
+        let batch = ObstacleBatch::new( Obstacles::new(1u32, 0u32)
+                                      , Batch::with_histogram(1000u32, h) );
+        Results::new("algorithm", DEFAULT_VARIANT, vec!(batch))
+    };
+    let runs = vec!(run(fine), run(coarse));
+    let pooled = pool(&runs);
+    let histogram = pooled[0].batches[0].batch().histogram().unwrap();
+    assert_eq!(histogram.significant_digits(), 1u32);
+    assert_eq!((histogram.count(), histogram.min(), histogram.max()),
+               (2000u64, 1i64, 2000i64));
+    let median = histogram.value_at_quantile(0.5f64) as f64;
+    assert!((median - 1000f64).abs() / 1000f64 < 0.1f64);
+}
+
+// Batches read back from a checkpoint report how their run times were
+// recorded, so that a run only resumes batches recorded the same way.
+#[test]
+fn batch_recording() {
+    let samples = Batch::new(3u32, vec!(4, 1, 2));
+    let histogram = Batch::with_histogram(3u32, Histogram::new(2u32));
+    let read = |b: &Batch| from_str::<Batch>(&to_string(b).unwrap()).unwrap();
+    assert_eq!(read(&samples).recording(), Recording::Samples);
+    assert_eq!(read(&histogram).recording(), Recording::Histogram(2u32));
+}
+
+#[test]
+fn histogram_precision() {
+    let mut histogram = Histogram::new(2u32);
+    for i in 1..100_001 { histogram.record(i); }
+    histogram.record(50_000_000);
+
+    for &q in [0.5f64, 0.99f64, 0.999f64].iter() {
+        let exact = (q * 100_001f64).ceil();
+        let value = histogram.value_at_quantile(q) as f64;
+        assert!((value - exact).abs() / exact < 0.01f64);
+    }
+    assert_eq!(histogram.max(), 50_000_000);
+    assert_eq!(histogram.outliers(), vec!((50_000_000, 1u64)));
+
+    let json = to_string(&histogram).unwrap();
+    let read: Histogram = from_str(&json).unwrap();
+    assert_eq!(read.value_at_quantile(0.999f64),
+               histogram.value_at_quantile(0.999f64));
+}
+
+#[test]
+fn invariant_violations() {
+    let evaluation = Evaluation { force:  Some(Vec2D::new(-3f64, 0f64))
+                                , away:   Some(Vec2D::unitx())
+                                , bound:  2f64
+                                , budget: Some(0f64) };
+    let invariants: Vec<Invariant> = evaluation.violations()
+                                               .iter()
+                                               .map(|x| x.0)
+                                               .collect();
+    assert!(invariants == vec!(Invariant::Bounded, Invariant::Away));
+
+    let nan = Evaluation { force: Some(Vec2D::new(0f64 / 0f64, 0f64))
+                         , .. evaluation };
+    assert!(nan.violations()[0].0 == Invariant::Finite);
+
+    let overspent = Evaluation { force: None, budget: Some(-1f64), .. nan };
+    assert!(overspent.violations()[0].0 == Invariant::Budget);
+
+    let refunded = Evaluation { budget: Some(2f64), .. overspent };
+    assert!(refunded.violations()[0].0 == Invariant::Budget);
+}
+
+#[test]
+fn translated_records() {
+    let mut record = Record::new(&[ ("x", 1f64), ("y", 2f64)
+                                  , ("vx", 3f64), ("vy", 4f64) ]);
+    record.obstacle(&[("x1", 1f64), ("y1", 2f64), ("x2", 3f64), ("y2", 4f64)]);
+    record.obstacle(&[("x", 5f64), ("y", 6f64), ("radius", 7f64)]);
+
+    let offset = Vec2D::new(1e6f64, -1e6f64);
+    let translated = translate_record(&record, offset);
+    let vehicle = &translated.vehicle;
+    assert_eq!(vehicle.get("x"), Some(1e6f64 + 1f64));
+    assert_eq!(vehicle.get("y"), Some(2f64 - 1e6f64));
+    assert_eq!(vehicle.get("vx"), Some(3f64));
+    assert_eq!(translated.obstacles[0].get("y2"), Some(4f64 - 1e6f64));
+    assert_eq!(translated.obstacles[1].get("radius"), Some(7f64));
+
+    let restored = translate_record(&translated, -offset);
+    assert_eq!(restored.to_json(), record.to_json());
+}
@@ -1,13 +1,28 @@
+use super::explain::Trace;
+use super::invariants::Evaluation;
 use super::linalg::vector2d::Vec2D;
+use super::oracle::Risk;
+use super::record::Record;
+
+pub use super::linalg::origin::Origin;
 
 // For scenarios that are testable without needing to access internals.
 pub trait HasScenario {
     fn interactions(&self) -> u32;
     fn avoidance(&mut self) -> Option<Vec2D>;
+    fn explain(&mut self) -> Trace;
+    fn risks(&self) -> Vec<Risk>;
+    fn evaluation(&mut self) -> Evaluation;
+    fn record(&self) -> Record;
+
+    // Returns the point in world space that avoidance forces are given
+    // relative to. Forces are directions unless an algorithm returns them
+    // as points, offset by its vehicle's position.
+    fn force_origin(&self) -> Vec2D { Vec2D::zero() }
 }
 
 // Contains details about obstacle interactions.
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Obstacles { pub total:         u32
                      , pub insignificant: u32
                      , pub significant:   u32 }
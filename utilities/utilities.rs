@@ -1,8 +1,14 @@
-use super::bench_utilities::ObstacleBatch;
-use super::getopts::Options;
+use super::bench_utilities::Recording;
+use super::drift::DEFAULT_OFFSET;
+use super::explain::Format;
+use super::precision::{FIXED_VARIANT, SINGLE_VARIANT};
+use super::getopts::{Matches, Options};
+use super::results::Results;
+use super::serde::Serialize;
 use super::serde_json::to_string_pretty;
 use super::test::black_box;
 use super::time::PreciseTime;
+use super::types::Obstacles;
 
 use std::env;
 use std::error::Error;
@@ -37,9 +43,185 @@ pub fn get_filepath() -> Option<String> {
     None
 }
 
+// Tasks performed by benchmark programs.
+pub enum Command { Benchmark(String, Recording)
+                 , Explain(Obstacles, Format)
+                 , Risk
+                 , Invariants
+                 , Golden(String)
+                 , FreshGolden(String)
+                 , Drift(f64)
+                 , Precision(String) }
+
+// Parses an obstacle count option, returning the default if it is absent.
+fn count_option(matches: &Matches, name: &str, default: u32) -> Option<u32> {
+    match matches.opt_str(name) {
+        Some(x) => x.parse().ok(),
+        None => Some(default)
+    }
+}
+
+// Returns the options accepted by benchmark programs.
+fn command_options() -> Options {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optopt( "d", "histogram"
+               , "record run times in a histogram with DIGITS significant \
+                  digits instead of individually"
+               , "DIGITS" );
+    opts.optopt( "e", "explain"
+               , "print a trace of a single scenario instead of benchmarking \
+                  (text or json)"
+               , "FORMAT" );
+    opts.optflag( "r", "risk"
+                , "report how often obstacles are wrongly assessed as risks \
+                   or as harmless instead of benchmarking" );
+    opts.optflag( "n", "invariants"
+                , "report violations of avoidance force invariants instead \
+                   of benchmarking" );
+    opts.optopt( "g", "golden"
+               , "re-evaluate the scenarios of the golden corpus in FILE and \
+                  rewrite their expected results instead of benchmarking"
+               , "FILE" );
+    opts.optopt( "", "fresh-golden"
+               , "replace the golden corpus in FILE with newly generated \
+                  scenarios and their results instead of benchmarking"
+               , "FILE" );
+    opts.optflag( "t", "drift"
+                , "report how interaction decisions and forces drift when \
+                   scenarios are translated far from the origin instead of \
+                   benchmarking" );
+    opts.optflagopt( "p", "precision"
+                   , "report how interaction decisions and forces change when \
+                      scenarios are evaluated in TYPE (f32 or q16.16, f32 by \
+                      default) instead of benchmarking"
+                   , "TYPE" );
+    opts.optopt( "o", "offset"
+               , "translate scenarios by DISTANCE along each axis (10^6 when \
+                  reporting drift)"
+               , "DISTANCE" );
+    opts.optopt( "i", "insignificant"
+               , "insignificant obstacles in the explained scenario (0)"
+               , "N" );
+    opts.optopt( "s", "significant"
+               , "significant obstacles in the explained scenario (1)"
+               , "N" );
+    opts.optopt( "v", "variant"
+               , "run the named variant of the algorithm, for programs that \
+                  have variants"
+               , "NAME" );
+    opts
+}
+
+// Returns the algorithm variant named on the command line of a benchmark
+// program, if any.
+pub fn get_variant() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    match command_options().parse(&args[1..]) {
+        Ok(matches) => matches.opt_str("variant"),
+        Err(_) => None
+    }
+}
+
+// Parses the distance by which scenarios are translated, returning none if
+// it is absent and an error if it is not a finite number.
+fn offset_option(matches: &Matches) -> Result<Option<f64>, ()> {
+    match matches.opt_str("offset") {
+        Some(x) => match x.parse::<f64>() {
+            Ok(distance) if distance.is_finite() => Ok(Some(distance)),
+            _ => Err(())
+        },
+        None => Ok(None)
+    }
+}
+
+// Returns the distance by which the command line of a benchmark program
+// translates scenarios, if any.
+pub fn get_offset() -> Option<f64> {
+    let args: Vec<String> = env::args().collect();
+    match command_options().parse(&args[1..]) {
+        Ok(matches) => offset_option(&matches).unwrap_or(None),
+        Err(_) => None
+    }
+}
+
+// Parses the command line of a benchmark program. Returns none, after
+// printing usage, if no valid task was specified.
+pub fn get_command() -> Option<Command> {
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+    let opts = command_options();
+
+    let brief = format!("Usage: {} [options] FILE", program);
+    let matches = opts.parse(&args[1..]).unwrap();
+    if matches.opt_present("h") {
+        print!("{}", opts.usage(&brief));
+        return None;
+    }
+
+    let offset = match offset_option(&matches) {
+        Ok(offset) => offset,
+        Err(_) => {
+            println!("DISTANCE must be a finite number.");
+            print!("{}", opts.usage(&brief));
+            return None;
+        }
+    };
+
+    if matches.opt_present("drift") {
+        return Some(Command::Drift(offset.unwrap_or(DEFAULT_OFFSET)));
+    } else if matches.opt_present("precision") {
+        let name = matches.opt_str("precision")
+                          .unwrap_or(SINGLE_VARIANT.to_string());
+        if name != SINGLE_VARIANT && name != FIXED_VARIANT {
+            println!("TYPE must be {} or {}.", SINGLE_VARIANT, FIXED_VARIANT);
+            print!("{}", opts.usage(&brief));
+            return None;
+        }
+        return Some(Command::Precision(name));
+    } else if matches.opt_present("risk") {
+        return Some(Command::Risk);
+    } else if matches.opt_present("invariants") {
+        return Some(Command::Invariants);
+    } else if let Some(filestring) = matches.opt_str("golden") {
+        return Some(Command::Golden(filestring));
+    } else if let Some(filestring) = matches.opt_str("fresh-golden") {
+        return Some(Command::FreshGolden(filestring));
+    } else if let Some(name) = matches.opt_str("explain") {
+        let format = Format::from_name(&name);
+        let insignificant = count_option(&matches, "insignificant", 0u32);
+        let significant = count_option(&matches, "significant", 1u32);
+        match (format, insignificant, significant) {
+            (Some(f), Some(i), Some(s)) if i == 0 || s == 0 => {
+                return Some(Command::Explain(Obstacles::new(i, s), f));
+            },
+            _ => println!( "Explained scenarios contain only insignificant or \
+                            only significant obstacles, and FORMAT is text \
+                            or json." )
+        }
+    } else if !matches.free.is_empty() {
+        let recording = match matches.opt_str("histogram") {
+            Some(x) => match x.parse() {
+                Ok(digits) if digits >= 1 && digits <= 5 =>
+                    Recording::Histogram(digits),
+                _ => {
+                    println!("DIGITS must be between 1 and 5.");
+                    print!("{}", opts.usage(&brief));
+                    return None;
+                }
+            },
+            None => Recording::Samples
+        };
+        return Some(Command::Benchmark(matches.free[0].clone(), recording));
+    }
+
+    print!("{}", opts.usage(&brief));
+    None
+}
+
 // Writes test information to the specified file.
-pub fn write_batches(filepath: &Path, batches: &Vec<ObstacleBatch>) {
-    let json = to_string_pretty(&batches).unwrap();
+pub fn write_batches<T: Serialize>(filepath: &Path, results: &T) {
+    let json = to_string_pretty(&results).unwrap();
 
     let mut file = match File::create(&filepath) {
         Err(error) =>  panic!( "couldn't create {}: {}"
@@ -55,8 +237,8 @@ pub fn write_batches(filepath: &Path, batches: &Vec<ObstacleBatch>) {
     }
 }
 
-// Convenience function for writing ObstacleBatch data to user-specified file.
-pub fn write_results(results: &Vec<ObstacleBatch>) {
+// Convenience function for writing labelled results to user-specified file.
+pub fn write_results(results: &Results) {
     if let Some(filestring) = get_filepath() {
         let filepath = Path::new(&filestring);
         write_batches(&filepath, results);
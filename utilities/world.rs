@@ -0,0 +1,214 @@
+use super::linalg::vector2d::Vec2D;
+use super::oracle::Geometry;
+use super::rand::distributions::{IndependentSample, Range};
+use super::rand::{SeedableRng, XorShiftRng};
+use super::record::Record;
+use super::types::Obstacles;
+
+use std::cell::RefCell;
+use std::f64::consts::PI;
+
+// Label of the variant that runs an algorithm on shared worlds.
+pub const WORLD_VARIANT: &'static str = "world";
+
+// Speed of vehicles in shared worlds.
+pub const SPEED: f64 = 10f64;
+
+// Body radius of vehicles in shared worlds.
+pub const BODY_RADIUS: f64 = 2f64;
+
+// Distance ahead of the vehicle within which obstacles are significant.
+pub const REACH: f64 = 10f64;
+
+// Half width of the corridor along the vehicle's path within which obstacles
+// are significant.
+pub const CORRIDOR: f64 = 2f64;
+
+// Time within which other vehicles are significant if they pass closer than
+// the sum of the vehicles' radii.
+pub const HORIZON: f64 = 1f64;
+
+// Kinds of obstacle that populate shared worlds.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Kind { Disks
+              , Segments
+              , Vehicles }
+
+// Pose, velocity and body radius of a vehicle, and the exact geometry of the
+// obstacles around it. Both approaches build their scenarios from worlds, so
+// they can be timed and checked on identical inputs.
+#[derive(Clone)]
+pub struct World { pub position:  Vec2D
+                 , pub velocity:  Vec2D
+                 , pub radius:    f64
+                 , pub obstacles: Vec<Geometry> }
+
+impl World {
+    // Returns the orientation of the vehicle, which faces along its velocity.
+    pub fn orientation(&self) -> f64 {
+        self.velocity.angle()
+    }
+
+    // Returns a record of the vehicle and obstacles in the world.
+    pub fn record(&self) -> Record {
+        let mut record = Record::new(&[ ("x", self.position.x)
+                                      , ("y", self.position.y)
+                                      , ("vx", self.velocity.x)
+                                      , ("vy", self.velocity.y)
+                                      , ("radius", self.radius) ]);
+        for obstacle in self.obstacles.iter() {
+            record.obstacle(&obstacle.parameters());
+        }
+        record
+    }
+
+    // Returns whether a scenario's record has the world's vehicle position
+    // and obstacles, to within the given relative tolerance.
+    pub fn matches(&self, record: &Record, tolerance: f64) -> bool {
+        let world = self.record();
+        let position = Record::new(&[ ("x", self.position.x)
+                                    , ("y", self.position.y) ]);
+        position.vehicle.close(&record.vehicle, tolerance)
+            && world.obstacles.len() == record.obstacles.len()
+            && world.obstacles.iter().zip(record.obstacles.iter())
+                    .all(|(a, b)| a.close(b, tolerance))
+    }
+}
+
+// Deterministic sequence of worlds of a single kind. Each obstacle
+// configuration has its own sequence, restarted whenever the configuration
+// changes, so every program that draws worlds for the configurations in the
+// same order draws identical worlds.
+pub struct Worlds { kind:  Kind
+                  , state: RefCell<Option<(Obstacles, XorShiftRng)>> }
+
+impl Worlds {
+    // Creates a sequence of worlds of the given kind.
+    pub fn new(kind: Kind) -> Worlds {
+        Worlds { kind: kind, state: RefCell::new(None) }
+    }
+
+    // Returns the next world with the given obstacles.
+    pub fn next(&self, obstacles: &Obstacles) -> World {
+        let mut state = self.state.borrow_mut();
+        let restart = match *state {
+            Some((ref current, _)) => current != obstacles,
+            None => true
+        };
+        if restart {
+            *state = Some((*obstacles, seeded_rng(self.kind, obstacles)));
+        }
+        let rng = &mut state.as_mut().unwrap().1;
+        generate_world(self.kind, obstacles, rng)
+    }
+}
+
+// Returns a random number generator seeded by the kind of world and the
+// obstacle configuration.
+fn seeded_rng(kind: Kind, obstacles: &Obstacles) -> XorShiftRng {
+    let kind = match kind {
+        Kind::Disks => 1u32,
+        Kind::Segments => 2u32,
+        Kind::Vehicles => 3u32
+    };
+    XorShiftRng::from_seed([ 0x5eed_u32
+                           , kind
+                           , obstacles.insignificant + 1
+                           , obstacles.significant + 1 ])
+}
+
+// Returns a random value in the given range.
+fn sample(rng: &mut XorShiftRng, low: f64, high: f64) -> f64 {
+    Range::new(low, high).ind_sample(rng)
+}
+
+// Returns a random value between 0.1 and 0.9, away from the edges of the
+// unit range.
+fn margin(rng: &mut XorShiftRng) -> f64 {
+    sample(rng, 0.1f64, 0.9f64)
+}
+
+// Returns either 1 or -1 at random.
+fn side(rng: &mut XorShiftRng) -> f64 {
+    if sample(rng, 0f64, 1f64) < 0.5f64 { 1f64 } else { -1f64 }
+}
+
+// Returns a disk beside the vehicle's path within reach. Significant disks
+// overlap the corridor along the path, others lie clear of it.
+fn disk(significant: bool, rng: &mut XorShiftRng) -> (Vec2D, f64) {
+    let radius = CORRIDOR * margin(rng);
+    let mut offset = margin(rng);
+    if !significant { offset += 1f64; }
+    let x = REACH * sample(rng, 0f64, 1f64);
+    let y = side(rng) * (radius + CORRIDOR * offset);
+    (Vec2D::new(x, y), radius)
+}
+
+// Returns the endpoints of a unit segment crossing the line of the vehicle's
+// path at a steep angle. Significant segments cross the path within reach,
+// others beyond it.
+fn segment(significant: bool, rng: &mut XorShiftRng) -> (Vec2D, Vec2D) {
+    let mut offset = margin(rng);
+    if !significant { offset += 1f64; }
+    let crossing = Vec2D::new(REACH * offset, 0f64);
+    let angle = (0.25f64 + 0.5f64 * sample(rng, 0f64, 1f64)) * PI;
+    ( crossing + Vec2D::polar(angle, 0.5f64)
+    , crossing + Vec2D::polar(angle + PI, 0.5f64) )
+}
+
+// Returns the position and velocity, relative to the vehicle, of another
+// vehicle that passes closest to it within the horizon. Significant vehicles
+// pass closer than the sum of the vehicles' radii, others further away.
+fn vehicle(significant: bool, rng: &mut XorShiftRng) -> (Vec2D, Vec2D) {
+    let mut offset = margin(rng);
+    if !significant { offset += 1f64; }
+    let closest = 2f64 * BODY_RADIUS * offset;
+
+    let relative_velocity = Vec2D::polar(sample(rng, 0f64, 2f64 * PI), SPEED);
+    let time = HORIZON * margin(rng);
+    let across = relative_velocity.perp() * (side(rng) * closest / SPEED);
+    let position = across - relative_velocity * time;
+    (position, relative_velocity)
+}
+
+// Returns a world of the given kind with the given obstacles. The vehicle has
+// a semi-random position and heading, and obstacles are placed in its space.
+fn generate_world(kind: Kind, obstacles: &Obstacles, rng: &mut XorShiftRng)
+    -> World
+{
+    let angle = sample(rng, 0f64, 2f64 * PI);
+    let position = Vec2D::polar( sample(rng, 0f64, 2f64 * PI)
+                               , 100f64 * sample(rng, 0f64, 1f64) );
+    let velocity = Vec2D::polar(angle, SPEED);
+    let heading = Vec2D::polar(angle, 1f64);
+    let rotate = |v: Vec2D| heading * v.x + heading.perp() * v.y;
+    let to_world = |v: Vec2D| position + rotate(v);
+
+    let mut geometry = vec!();
+    let (insignificant, significant) = obstacles.details();
+    for i in 0..insignificant + significant {
+        let significant = i >= insignificant;
+        geometry.push(match kind {
+            Kind::Disks => {
+                let (centre, radius) = disk(significant, rng);
+                Geometry::Disk { centre: to_world(centre), radius: radius }
+            },
+            Kind::Segments => {
+                let (point1, point2) = segment(significant, rng);
+                Geometry::Segment { point1: to_world(point1)
+                                  , point2: to_world(point2) }
+            },
+            Kind::Vehicles => {
+                let (offset, relative_velocity) = vehicle(significant, rng);
+                Geometry::Vehicle { position: to_world(offset)
+                                  , velocity: velocity
+                                          + rotate(relative_velocity)
+                                  , radius:   BODY_RADIUS }
+            }
+        });
+    }
+    World { position:  position
+          , velocity:  velocity
+          , radius:    BODY_RADIUS
+          , obstacles: geometry }
+}
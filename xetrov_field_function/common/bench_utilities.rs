@@ -1,28 +1,146 @@
 extern crate utilities;
-use utilities::bench_utilities::time_batch;
-use utilities::types::{HasScenario, Obstacles};
-use utilities::utilities::write_results;
+use utilities::bench_utilities::{run, run_batched, run_fixed, run_single};
+use utilities::bench_utilities::run_worlds;
+use utilities::bench_utilities::BATCH_VARIANT;
+use utilities::record::Record;
+use utilities::results::DEFAULT_VARIANT;
+use utilities::types::{HasScenario, Obstacles, Origin};
+use utilities::precision::{FIXED_VARIANT, SINGLE_VARIANT};
+use utilities::utilities::get_variant;
+use utilities::world::{Kind, World, WORLD_VARIANT};
 
-extern crate time;
-use self::time::PreciseTime;
+use super::profile::{Builtin, BUILTIN_NAMES};
 
-// Runs benchmarks and saves results to a file specified on the command line.
-pub fn run_benchmarks<F>(scenario: F)
+// Runs benchmarks and saves results, labelled with the given algorithm, to a
+// file specified on the command line, or explains a single scenario. The
+// `world` variant draws scenarios of the given kind from shared worlds, and
+// the `relative` variant finds sources about the vehicle's position, the
+// `f32` variant evaluates in single precision and the `q16.16` variant
+// evaluates in fixed point. A variant named after a built-in profile shapes
+// potential with that profile. Scenarios are rebuilt from records, about the
+// given origin, by `record`, in single precision by `single`, in fixed point
+// by `fixed` and with a built-in profile by `profiled`.
+pub fn run_benchmarks<F, G, H, I, J, K>( algorithm: &str
+                                       , scenario:  F
+                                       , kind:      Kind
+                                       , world:     G
+                                       , record:    H
+                                       , single:    I
+                                       , fixed:     J
+                                       , profiled:  K )
     where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
+        , G: Fn(&World) -> Box<HasScenario>
+        , H: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , I: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , J: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , K: Fn(&Record, Builtin) -> Option<Box<HasScenario>>
 {
-    let start = PreciseTime::now();
+    benchmark( algorithm, scenario, kind, world, record, single, fixed
+             , profiled, None );
+}
 
-    let creator = |o: &Obstacles| -> Box<HasScenario> { scenario(o).unwrap() };
+// As `run_benchmarks`, but the `batch` variant also evaluates obstacles in
+// batches, in scenarios rebuilt from records by `batched`.
+pub fn run_batched_benchmarks<F, G, H, I, J, K, L>( algorithm: &str
+                                                  , scenario:  F
+                                                  , kind:      Kind
+                                                  , world:     G
+                                                  , record:    H
+                                                  , single:    I
+                                                  , fixed:     J
+                                                  , profiled:  K
+                                                  , batched:   L )
+    where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
+        , G: Fn(&World) -> Box<HasScenario>
+        , H: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , I: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , J: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , K: Fn(&Record, Builtin) -> Option<Box<HasScenario>>
+        , L: Fn(&Record) -> Option<Box<HasScenario>>
+{
+    benchmark( algorithm, scenario, kind, world, record, single, fixed
+             , profiled, Some(&batched) );
+}
+
+// Runs the variant named on the command line, as `run_benchmarks` describes.
+// The `batch` variant is only available given `batched`.
+fn benchmark<F, G, H, I, J, K>( algorithm: &str
+                              , scenario:  F
+                              , kind:      Kind
+                              , world:     G
+                              , record:    H
+                              , single:    I
+                              , fixed:     J
+                              , profiled:  K
+                              , batched:   Option<&Fn(&Record) -> Option<
+                                                         Box<HasScenario>>> )
+    where F: Fn(&Obstacles) -> Option<Box<HasScenario>>
+        , G: Fn(&World) -> Box<HasScenario>
+        , H: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , I: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , J: Fn(&Record, Origin) -> Option<Box<HasScenario>>
+        , K: Fn(&Record, Builtin) -> Option<Box<HasScenario>>
+{
+    let single = |r: &Record| single(r, Origin::Absolute);
+    let fixed = |r: &Record| fixed(r, Origin::Absolute);
+    let variant = get_variant();
+    let origin = match variant {
+        Some(ref name) if name == WORLD_VARIANT => {
+            let rebuild = |r: &Record| record(r, Origin::Absolute);
+            run_worlds(algorithm, kind, world, rebuild, single, fixed);
+            return;
+        },
+        Some(ref name) if name == SINGLE_VARIANT => {
+            let creator = |o: &Obstacles| scenario(o).unwrap();
+            run_single(algorithm, creator, single);
+            return;
+        },
+        Some(ref name) if name == FIXED_VARIANT => {
+            let creator = |o: &Obstacles| scenario(o).unwrap();
+            run_fixed(algorithm, creator, fixed);
+            return;
+        },
+        Some(ref name) if name == BATCH_VARIANT && batched.is_some() => {
+            let creator = |o: &Obstacles| scenario(o).unwrap();
+            run_batched( algorithm, creator, batched.unwrap(), single
+                       , fixed );
+            return;
+        },
+        Some(ref name) if Builtin::<f64>::named(name).is_some() => {
+            let profile = Builtin::named(name).unwrap();
+            let rebuild = |r: &Record| profiled(r, profile);
+            let creator = |o: &Obstacles| {
+                rebuild(&scenario(o).unwrap().record()).unwrap()
+            };
+            run(algorithm, name, creator, rebuild, single, fixed);
+            return;
+        },
+        Some(ref name) if name == Origin::Relative.name() => Origin::Relative,
+        Some(_) => {
+            let batch = match batched {
+                Some(_) => ", batch",
+                None => ""
+            };
+            println!( "Variants are world, relative, f32, q16.16{}, or a \
+                       profile: {}."
+                    , batch, BUILTIN_NAMES.join(", ") );
+            return;
+        },
+        None => Origin::Absolute
+    };
+    let label = match variant {
+        Some(_) => origin.name(),
+        None => DEFAULT_VARIANT
+    };
 
-    let mut results = vec!();
-    for i in 1..6 {
-        let interaction1 = Obstacles::new(i, 0u32);
-        results.push(time_batch(&interaction1, |x| creator(x)));
-        let interaction2 = Obstacles::new(0u32, i);
-        results.push(time_batch(&interaction2, |x| creator(x)));
-    }
-    write_results(&results);
+    let rebuild = |r: &Record| record(r, origin);
+    let creator = |o: &Obstacles| -> Box<HasScenario> {
+        let scenario = scenario(o).unwrap();
+        match origin {
+            Origin::Absolute => scenario,
+            Origin::Relative => rebuild(&scenario.record()).unwrap()
+        }
+    };
 
-    let runtime = start.to(PreciseTime::now()).num_seconds();
-    println!("Total time: {} seconds", runtime);
+    run(algorithm, label, creator, rebuild, single, fixed);
 }
@@ -0,0 +1,42 @@
+extern crate common;
+use common::profile::{Builtin, Profile, BUILTIN_NAMES};
+
+// Number of intervals between the ratios at which profiles are tabulated.
+const STEPS: u32 = 10;
+
+// Number of intervals over which the mean falloff is found.
+const MEAN_STEPS: u32 = 1_000;
+
+// Returns the mean falloff of the profile over ratios between zero and one:
+// the share of the budget an obstacle at a uniformly random distance uses.
+fn mean_falloff(profile: &Builtin) -> f64 {
+    let total: f64 = (0..MEAN_STEPS).map(|i| {
+        profile.falloff((i as f64 + 0.5) / MEAN_STEPS as f64)
+    }).sum();
+    total / MEAN_STEPS as f64
+}
+
+// Prints the shape of the potential of each built-in profile: its magnitude,
+// radial and tangential components, and its angle from the direction away
+// from the obstacle in degrees, at ratios of the distance to the potential
+// scale between zero and one.
+fn main() {
+    println!( "{:<12} {:>5} {:>8} {:>8} {:>10} {:>6}"
+            , "profile", "ratio", "falloff", "radial", "tangential", "angle" );
+    for name in BUILTIN_NAMES.iter() {
+        let profile = Builtin::named(name).unwrap();
+        for i in 0..(STEPS + 1) {
+            let ratio = i as f64 / STEPS as f64;
+            let (rd, td, gd) = profile.terms(ratio);
+            let (radial, tangential) = profile.rotation(ratio);
+            let angle = tangential.atan2(radial).to_degrees();
+            println!( "{:<12} {:>5.2} {:>8.4} {:>8.4} {:>10.4} {:>6.1}"
+                    , name, ratio, gd, rd, td, angle );
+        }
+    }
+    println!();
+    for name in BUILTIN_NAMES.iter() {
+        let profile = Builtin::named(name).unwrap();
+        println!("{:<12} mean falloff {:.4}", name, mean_falloff(&profile));
+    }
+}
@@ -1,5 +1,25 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Without `std`, `core` stands in for it, and vectors and boxes come from
+// `alloc`.
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
 extern crate linalg;
+#[cfg(feature = "std")]
 extern crate utilities;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
 
+#[cfg(feature = "std")]
 pub mod bench_utilities;
+pub mod profile;
+#[cfg(feature = "std")]
+pub mod scenarios;
 pub mod types;
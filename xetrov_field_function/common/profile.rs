@@ -0,0 +1,241 @@
+use super::linalg::scalar::Scalar;
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use super::serde::de::Error;
+#[cfg(feature = "serde")]
+use super::serde::ser::SerializeStruct;
+
+// Decay rate of the built-in exponential profile.
+pub const EXPONENTIAL_RATE: f64 = 4f64;
+
+// Tangential bias of the built-in tangential profile.
+pub const TANGENTIAL_BIAS: f64 = 2f64;
+
+// Implementers of Profile shape the potential that obstacles generate, as
+// functions of the ratio of the distance between a source and the look-ahead
+// point to the potential scale, which is between zero and one. `falloff` is
+// the magnitude of the potential, which is charged against the budget, and
+// so must be between zero and one. `rotation` returns the weights of the
+// directions away from the obstacle and along its tangent, which are not
+// both zero. `terms` normalises the weights to the magnitude.
+pub trait Profile<T: Scalar> {
+    fn falloff(&self, ratio: T) -> T;
+
+    // Turns towards the tangent as the square root of the ratio.
+    fn rotation(&self, ratio: T) -> (T, T) {
+        let hd = ratio.sqrt();
+        (T::ONE - hd, hd)
+    }
+
+    // Returns the radial and tangential components of the potential
+    // corresponding to the given distance ratio, and its magnitude.
+    fn terms(&self, ratio: T) -> (T, T, T) {
+        let gd = self.falloff(ratio);
+        let (radial, tangential) = self.rotation(ratio);
+        let zd = (radial * radial + tangential * tangential).sqrt();
+        let rd = gd * radial / zd;
+        let td = gd * tangential / zd;
+        (rd, td, gd)
+    }
+}
+
+// The original profile, whose potential falls off as the square of one less
+// the ratio.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quadratic;
+
+impl<T: Scalar> Profile<T> for Quadratic {
+    fn falloff(&self, ratio: T) -> T {
+        (T::ONE - ratio) * (T::ONE - ratio)
+    }
+}
+
+// Profile whose potential falls off linearly, so is stronger at a distance
+// than the quadratic profile.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Linear;
+
+impl<T: Scalar> Profile<T> for Linear {
+    fn falloff(&self, ratio: T) -> T { T::ONE - ratio }
+}
+
+// Profile whose potential falls off as the cube of one less the ratio, so is
+// weaker at a distance than the quadratic profile.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cubic;
+
+impl<T: Scalar> Profile<T> for Cubic {
+    fn falloff(&self, ratio: T) -> T {
+        let remaining = T::ONE - ratio;
+        remaining * remaining * remaining
+    }
+}
+
+// Profile whose potential decays exponentially at the given rate, shifted and
+// scaled so that it is one at no distance and zero at the potential scale.
+// Higher rates concentrate the potential nearer obstacles. The rate must be
+// positive, as the profile cannot be scaled otherwise.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Exponential<T = f64> { rate:  T
+                                , floor: T
+                                , scale: T }
+
+impl<T: Scalar> Exponential<T> {
+    // Creates a profile with the given decay rate, or none if the rate is not
+    // positive or is too small for the floor to differ from one in `T`.
+    pub fn new(rate: T) -> Option<Exponential<T>> {
+        let floor = (-rate).exp();
+        if !(rate > T::ZERO && floor < T::ONE) { return None; }
+        Some(Exponential { rate:  rate
+                         , floor: floor
+                         , scale: T::ONE / (T::ONE - floor) })
+    }
+
+    pub fn rate(&self) -> T { self.rate }
+}
+
+impl<T: Scalar> Default for Exponential<T> {
+    fn default() -> Exponential<T> {
+        Exponential::new(T::from_f64(EXPONENTIAL_RATE)).unwrap()
+    }
+}
+
+impl<T: Scalar> Profile<T> for Exponential<T> {
+    fn falloff(&self, ratio: T) -> T {
+        ((-self.rate * ratio).exp() - self.floor) * self.scale
+    }
+}
+
+// Serialises the decay rate, from which the other values follow.
+#[cfg(feature = "serde")]
+impl<T: Scalar + Serialize> Serialize for Exponential<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        let mut state = serializer.serialize_struct("Exponential", 1)?;
+        state.serialize_field("rate", &self.rate)?;
+        state.end()
+    }
+}
+
+// Defining parameters of an exponential profile, as which it is serialised.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct ExponentialParameters<T> { rate: T }
+
+#[cfg(feature = "serde")]
+impl<'de, T: Scalar + Deserialize<'de>> Deserialize<'de> for Exponential<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Exponential<T>, D::Error>
+    {
+        let p = ExponentialParameters::deserialize(deserializer)?;
+        Exponential::new(p.rate)
+            .ok_or_else(|| D::Error::custom("non-positive exponential rate"))
+    }
+}
+
+// Profile with the falloff of the given profile, whose tangential weight is
+// multiplied by the given non-negative bias. A bias above one steers around
+// obstacles sooner, below one steers away from them for longer, and zero
+// steers straight away from them.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Biased<P = Quadratic, T = f64> { pub profile: P
+                                          , pub bias:    T }
+
+impl<P, T: Scalar> Biased<P, T> {
+    pub fn new(profile: P, bias: T) -> Biased<P, T> {
+        Biased { profile: profile, bias: bias }
+    }
+}
+
+impl<P: Default, T: Scalar> Default for Biased<P, T> {
+    fn default() -> Biased<P, T> {
+        Biased::new(P::default(), T::from_f64(TANGENTIAL_BIAS))
+    }
+}
+
+impl<P: Profile<T>, T: Scalar> Profile<T> for Biased<P, T> {
+    fn falloff(&self, ratio: T) -> T { self.profile.falloff(ratio) }
+
+    fn rotation(&self, ratio: T) -> (T, T) {
+        let (radial, tangential) = self.profile.rotation(ratio);
+        (radial, tangential * self.bias)
+    }
+}
+
+// The built-in profiles, chosen by name at run time. Each evaluates as the
+// profile it holds, after matching on it, and is serialised as that profile
+// under its name. The default is the quadratic profile, as for vehicles.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr( feature = "serde"
+          , serde(bound( serialize = "T: Scalar + Serialize"
+                       , deserialize = "T: Scalar + Deserialize<'de>" )) )]
+pub enum Builtin<T = f64> { Quadratic(Quadratic)
+                          , Linear(Linear)
+                          , Cubic(Cubic)
+                          , Exponential(Exponential<T>)
+                          , Tangential(Biased<Quadratic, T>) }
+
+// Names of the built-in profiles.
+pub const BUILTIN_NAMES: [&'static str; 5] =
+    ["quadratic", "linear", "cubic", "exponential", "tangential"];
+
+impl<T: Scalar> Builtin<T> {
+    // Returns the built-in profile with the given name, with the built-in
+    // rate and bias, if there is one.
+    pub fn named(name: &str) -> Option<Builtin<T>> {
+        match name {
+            "quadratic" => Some(Builtin::Quadratic(Quadratic)),
+            "linear" => Some(Builtin::Linear(Linear)),
+            "cubic" => Some(Builtin::Cubic(Cubic)),
+            "exponential" => Some(Builtin::Exponential(Exponential::default())),
+            "tangential" => Some(Builtin::Tangential(Biased::default())),
+            _ => None
+        }
+    }
+
+    // Returns the name of the profile, which labels the variant of the
+    // algorithm that evaluates with it.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Builtin::Quadratic(_) => BUILTIN_NAMES[0],
+            Builtin::Linear(_) => BUILTIN_NAMES[1],
+            Builtin::Cubic(_) => BUILTIN_NAMES[2],
+            Builtin::Exponential(_) => BUILTIN_NAMES[3],
+            Builtin::Tangential(_) => BUILTIN_NAMES[4]
+        }
+    }
+}
+
+impl<T> Default for Builtin<T> {
+    fn default() -> Builtin<T> { Builtin::Quadratic(Quadratic) }
+}
+
+impl<T: Scalar> Profile<T> for Builtin<T> {
+    fn falloff(&self, ratio: T) -> T {
+        match *self {
+            Builtin::Quadratic(ref x) => x.falloff(ratio),
+            Builtin::Linear(ref x) => x.falloff(ratio),
+            Builtin::Cubic(ref x) => x.falloff(ratio),
+            Builtin::Exponential(ref x) => x.falloff(ratio),
+            Builtin::Tangential(ref x) => x.falloff(ratio)
+        }
+    }
+
+    fn rotation(&self, ratio: T) -> (T, T) {
+        match *self {
+            Builtin::Quadratic(ref x) => x.rotation(ratio),
+            Builtin::Linear(ref x) => x.rotation(ratio),
+            Builtin::Cubic(ref x) => x.rotation(ratio),
+            Builtin::Exponential(ref x) => x.rotation(ratio),
+            Builtin::Tangential(ref x) => x.rotation(ratio)
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::linalg::batch::Lanes;
+use super::linalg::scalar::Scalar;
+use super::linalg::vector2d::Vec2D;
+use super::profile::{Profile, Quadratic};
+use super::types::*;
+use super::utilities::explain::Trace;
+use super::utilities::invariants::Evaluation;
+use super::utilities::oracle::Risk;
+use super::utilities::record::Record;
+use super::utilities::types::{HasScenario, Origin};
+use super::utilities::rng_utilities::{random_axis, random_grid_point};
+use super::utilities::rng_utilities::{random_index, random_tau, random_unity};
+use super::utilities::rng_utilities::BOUNDARY_MARGIN;
+
+// Arrangement of vehicle and obstacles to be used in benchmarks, in which the
+// vehicle evaluates potential with the profile `P`.
+pub struct Scenario<T: Scalar = f64, P = Quadratic>
+    { pub vehicle:   Vehicle<T, P>
+    , pub obstacles: Vec<Box<HasSource<T>>> }
+
+impl<T: Scalar, P: Profile<T> + 'static> HasScenario for Scenario<T, P> {
+    // Returns the interactions between the vehicle and obstacles in the
+    // scenario.
+    fn interactions(&self) -> u32 {
+        let mut count = 0;
+        let point = self.vehicle.look_ahead_point();
+        for obstacle in self.obstacles.iter() {
+            if self.vehicle.repulsor(point, obstacle).is_some() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // Returns the avoidance force to be applied to the vehicle according to
+    // the steering scenario.
+    fn avoidance(&mut self) -> Option<Vec2D> {
+        self.vehicle.total_potential(&self.obstacles).map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.vehicle.explain_potential(&self.obstacles)
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.vehicle.evaluate_potential(&self.obstacles)
+    }
+
+    // Returns the parameters defining the vehicle and obstacles.
+    fn record(&self) -> Record {
+        let v = &self.vehicle;
+        let scale = v.potential_scale.to_f64();
+        let mut record = Record::new(&[ ("x", v.position.x.to_f64())
+                                      , ("y", v.position.y.to_f64())
+                                      , ("vx", v.velocity.x.to_f64())
+                                      , ("vy", v.velocity.y.to_f64())
+                                      , ("scale", scale) ]);
+        for obstacle in self.obstacles.iter() {
+            record.obstacle(&obstacle.geometry().parameters());
+        }
+        record
+    }
+
+    // Returns whether each obstacle generates a repulsor, and whether it
+    // would collide with the vehicle within the horizon.
+    fn risks(&self) -> Vec<Risk> {
+        let point = self.vehicle.look_ahead_point();
+        let body = self.vehicle.swept_body();
+        self.obstacles.iter().map(|obstacle| {
+            let predicted = self.vehicle.repulsor(point, obstacle).is_some();
+            Risk::new(predicted, &body, &obstacle.geometry())
+        }).collect()
+    }
+}
+
+impl<T: Scalar, P: Profile<T>> Scenario<T, P> {
+    // Creates a scenario from the given vehicle and obstacles.
+    pub fn new(vehicle: Vehicle<T, P>, obstacles: Vec<Box<HasSource<T>>>)
+        -> Scenario<T, P>
+    {
+        Scenario { vehicle: vehicle, obstacles: obstacles }
+    }
+}
+
+// Scenario whose obstacles are also held as a batch, with which the
+// interactions and avoidance force are evaluated. Other evaluations use the
+// obstacles one at a time. Batches find sources in world space, so the
+// vehicle is absolute.
+pub struct BatchScenario<T: Lanes = f64> { pub scenario: Scenario<T>
+                                          , pub batch:    Box<HasSources<T>> }
+
+impl<T: Lanes> HasScenario for BatchScenario<T> {
+    // Returns the interactions between the vehicle and obstacles in the
+    // scenario.
+    fn interactions(&self) -> u32 {
+        let mut count = 0;
+        self.scenario.vehicle.batch_repulsors(&self.batch, |_, _| {
+            count += 1;
+        });
+        count
+    }
+
+    // Returns the avoidance force to be applied to the vehicle according to
+    // the steering scenario.
+    fn avoidance(&mut self) -> Option<Vec2D> {
+        self.scenario.vehicle.batch_potential(&self.batch).map(|x| x.cast())
+    }
+
+    // Returns a trace of the evaluation of the avoidance force.
+    fn explain(&mut self) -> Trace {
+        self.scenario.explain()
+    }
+
+    // Returns the quantities of the evaluation of the avoidance force that
+    // are subject to invariants.
+    fn evaluation(&mut self) -> Evaluation {
+        self.scenario.evaluation()
+    }
+
+    // Returns the parameters defining the vehicle and obstacles.
+    fn record(&self) -> Record {
+        self.scenario.record()
+    }
+
+    // Returns whether each obstacle generates a repulsor, and whether it
+    // would collide with the vehicle within the horizon.
+    fn risks(&self) -> Vec<Risk> {
+        self.scenario.risks()
+    }
+}
+
+impl<T: Lanes> BatchScenario<T> {
+    // Creates a scenario that evaluates the given batch of obstacles, and
+    // otherwise the obstacles of the given scenario, for an absolute vehicle.
+    pub fn new(mut scenario: Scenario<T>, batch: Box<HasSources<T>>)
+        -> BatchScenario<T>
+    {
+        scenario.vehicle.origin = Origin::Absolute;
+        BatchScenario { scenario: scenario, batch: batch }
+    }
+}
+
+// Returns a vehicle with semi-random position and velocity and a fixed
+// potential scale.
+#[warn(dead_code)]
+pub fn random_vehicle() -> Vehicle {
+    let angle = random_tau();
+    let position = Vec2D::polar(angle, 100f64 * random_unity());
+    let velocity = Vec2D::polar(angle, 10f64);
+    Vehicle::new(position, velocity, POTENTIAL_SCALE)
+}
+
+// Returns a vehicle with an integer position and a velocity along an axis, so
+// that its look-ahead point is exactly representable.
+pub fn boundary_vehicle() -> Vehicle {
+    let velocity = random_axis() * 10f64;
+    Vehicle::new(random_grid_point(), velocity, POTENTIAL_SCALE)
+}
+
+// Returns a distance between a look-ahead point and source on the boundary of
+// the repulsor check. Sources at no distance, just nearer than `NEAREST` or at
+// the potential scale generate no repulsor, whilst those just inside either
+// bound do.
+pub fn boundary_distance(significant: bool, potential_scale: f64) -> f64 {
+    if significant {
+        let distances = [NEAREST, potential_scale - BOUNDARY_MARGIN];
+        distances[random_index(distances.len())]
+    } else {
+        let distances = [0f64, 0.5f64 * NEAREST, potential_scale];
+        distances[random_index(distances.len())]
+    }
+}
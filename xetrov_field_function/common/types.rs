@@ -1,7 +1,25 @@
-use super::linalg::vector2d::{EPSILON, Vec2D};
-use super::utilities::types::HasScenario;
-use super::utilities::rng_utilities::{random_tau, random_unity};
+use super::linalg::batch::{self, Lanes, BLOCK};
+use super::linalg::origin::Origin;
+use super::linalg::scalar::Scalar;
+use super::linalg::vector2d::Vec2D;
+use super::profile::{Profile, Quadratic};
+#[cfg(feature = "std")]
+use super::utilities::explain::Trace;
+#[cfg(feature = "std")]
+use super::utilities::invariants::Evaluation;
+#[cfg(feature = "std")]
+use super::utilities::oracle::{Geometry, SweptBody};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+#[cfg(feature = "std")]
+use super::utilities::world::World;
 
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use std::cmp;
 use std::cmp::Ordering::Equal;
 
 // Distance to which potential spreads from obstacles.
@@ -10,82 +28,199 @@ pub const POTENTIAL_SCALE: f64 = 10f64;
 // Look ahead time for vehicles.
 pub const LOOK_AHEAD: f64 = 0.5f64;
 
-// Implementers of HasSource can return the nearest point on their geometry.
-pub trait HasSource {
-    fn source(&self, v: Vec2D) -> Vec2D;
+// Smallest power of two distance at which sources generate a repulsor.
+// `Vec2D::mag` returns the squared magnitude of vectors whose squared
+// magnitude is below `EPSILON`, so sources nearer than the square root of
+// `EPSILON` generate none. This is the distance in `f64`; in `f32`, whose
+// `EPSILON` is larger, it is 1/64.
+pub const NEAREST: f64 = 1f64 / 8192f64;
+
+// Radius of the body of vehicles when checking for collisions.
+pub const BODY_RADIUS: f64 = 2f64;
+
+// Time over which vehicles are checked for collisions.
+pub const HORIZON: f64 = 1f64;
+
+// Implementers of HasSource can return the nearest point on their geometry,
+// and, with `std`, their exact geometry. `relative_source` returns the source
+// of the point `origin + v` relative to `origin`, which implementers compute
+// without losing precision when the origin is far from the world origin.
+pub trait HasSource<T: Scalar = f64> {
+    fn source(&self, v: Vec2D<T>) -> Vec2D<T>;
+    #[cfg(feature = "std")]
+    fn geometry(&self) -> Geometry;
+
+    fn relative_source(&self, origin: Vec2D<T>, v: Vec2D<T>) -> Vec2D<T> {
+        self.source(origin + v) - origin
+    }
 }
 
-// Arrangement of vehicle and obstacles to be used in benchmarks.
-pub struct Scenario { pub vehicle: Vehicle
-                    , pub obstacles: Vec<Box<HasSource>> }
-
-impl HasScenario for Scenario {
-    // Returns the interactions between the vehicle and obstacles in the
-    // scenario.
-    fn interactions(&self) -> u32 {
-        let mut count = 0;
-        let point = self.vehicle.look_ahead();
-        for obstacle in self.obstacles.iter() {
-            if self.vehicle.repulsor(point, obstacle).is_some() {
-                count += 1;
-            }
-        }
-        count
+// Boxed sources, such as the obstacles of scenarios, are sources.
+impl<T: Scalar, S: HasSource<T> + ?Sized> HasSource<T> for Box<S> {
+    fn source(&self, v: Vec2D<T>) -> Vec2D<T> {
+        (**self).source(v)
     }
 
-    // Returns the avoidance force to be applied to the vehicle according to
-    // the steering scenario.
-    fn avoidance(&mut self) -> Option<Vec2D> {
-        self.vehicle.total_potential(&self.obstacles)
+    #[cfg(feature = "std")]
+    fn geometry(&self) -> Geometry {
+        (**self).geometry()
     }
-}
 
-impl Scenario {
-    // Creates a scenario from the given vehicle and obstacles.
-    pub fn new(vehicle: Vehicle, obstacles: Vec<Box<HasSource>>) -> Scenario {
-        Scenario { vehicle: vehicle, obstacles: obstacles }
+    fn relative_source(&self, origin: Vec2D<T>, v: Vec2D<T>) -> Vec2D<T> {
+        (**self).relative_source(origin, v)
     }
 }
 
-// Vehicle that steers using goal-aligned xetrov field.
-pub struct Vehicle { pub position:        Vec2D
-                   , pub velocity:        Vec2D
-                   , pub potential_scale: f64 }
+// Implementers of HasSources hold obstacles as a batch, whose sources they
+// find in world space for lanes of obstacles at a time. `sources` writes the
+// sources of the `xs.len()` obstacles from `start`, at most `BLOCK`, for the
+// point `v`.
+pub trait HasSources<T: Lanes = f64> {
+    fn len(&self) -> usize;
+    fn sources(&self, start: usize, v: Vec2D<T>, xs: &mut [T], ys: &mut [T]);
+}
+
+// Potential of an obstacle as it is accumulated: the obstacle's index, its
+// potential and the radial and tangential weights and magnitude of it, the
+// share of it that is within the budget, and the budget remaining after it.
+pub struct Step<T = f64> { pub index:     usize
+                         , pub potential: Vec2D<T>
+                         , pub rd:        T
+                         , pub td:        T
+                         , pub gd:        T
+                         , pub scale:     T
+                         , pub remaining: T }
+
+
+// Vehicle that steers using goal-aligned xetrov field. Absolute vehicles find
+// the sources of obstacles in world space, whilst relative vehicles find them
+// relative to their position, so do not lose precision far from the world
+// origin. The profile shapes the potential of each obstacle. Vehicles read
+// without one have the default profile.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Vehicle<T = f64, P = Quadratic>
+    { pub position:        Vec2D<T>
+    , pub velocity:        Vec2D<T>
+    , pub potential_scale: T
+    , pub origin:          Origin
+    , #[cfg_attr(feature = "serde", serde(default))]
+      pub profile:         P }
 
-impl HasSource for Vehicle {
+impl<T: Scalar, P: Profile<T>> HasSource<T> for Vehicle<T, P> {
     // Returns the future position of this vehicle.
     #[allow(unused_variables)]
-    fn source(&self, v: Vec2D) -> Vec2D {
+    fn source(&self, v: Vec2D<T>) -> Vec2D<T> {
         self.look_ahead()
     }
+
+    // Returns the body of this vehicle and its velocity.
+    #[cfg(feature = "std")]
+    fn geometry(&self) -> Geometry {
+        Geometry::Vehicle { position: self.position.cast()
+                          , velocity: self.velocity.cast()
+                          , radius:   BODY_RADIUS }
+    }
+
+    // Returns the future position of this vehicle relative to the origin.
+    #[allow(unused_variables)]
+    fn relative_source(&self, origin: Vec2D<T>, v: Vec2D<T>) -> Vec2D<T> {
+        self.position - origin + self.velocity * T::from_f64(LOOK_AHEAD)
+    }
 }
 
-impl Vehicle {
-    // Creates a vehicle from the given values.
-    pub fn new(pos: Vec2D, vel: Vec2D, potential_scale: f64) -> Vehicle {
+impl<T: Scalar> Vehicle<T> {
+    // Creates a vehicle from the given values, with the default profile.
+    pub fn new(pos: Vec2D<T>, vel: Vec2D<T>, potential_scale: T) -> Vehicle<T> {
         Vehicle { position:        pos
                 , velocity:        vel
-                , potential_scale: potential_scale }
+                , potential_scale: potential_scale
+                , origin:          Origin::Absolute
+                , profile:         Quadratic }
+    }
+
+    // Creates the vehicle of the given shared world.
+    #[cfg(feature = "std")]
+    pub fn from_world(world: &World) -> Vehicle<T> {
+        Vehicle::new( world.position.cast()
+                    , world.velocity.cast()
+                    , T::from_f64(POTENTIAL_SCALE) )
+    }
+
+    // Creates the vehicle described by the given record of a scenario, or
+    // none if the record is incomplete.
+    #[cfg(feature = "std")]
+    pub fn from_record(record: &Record) -> Option<Vehicle<T>> {
+        let v = &record.vehicle;
+        Some(Vehicle::new( v.vec_as("x", "y")?
+                         , v.vec_as("vx", "vy")?
+                         , v.get_as("scale")? ))
+    }
+}
+
+impl<T: Scalar, P: Profile<T>> Vehicle<T, P> {
+    // Returns the vehicle with its profile replaced by the given profile.
+    pub fn with_profile<Q: Profile<T>>(self, profile: Q) -> Vehicle<T, Q> {
+        Vehicle { position:        self.position
+                , velocity:        self.velocity
+                , potential_scale: self.potential_scale
+                , origin:          self.origin
+                , profile:         profile }
     }
 
     // Returns the 'look ahead' position of the vehicle.
-    pub fn look_ahead(&self) -> Vec2D {
-        self.position.add(self.velocity.mul(LOOK_AHEAD))
+    pub fn look_ahead(&self) -> Vec2D<T> {
+        self.position + self.velocity * T::from_f64(LOOK_AHEAD)
+    }
+
+    // Returns the look-ahead point from which repulsors are evaluated: in
+    // world space for absolute vehicles, and relative to the vehicle's
+    // position for relative vehicles.
+    pub fn look_ahead_point(&self) -> Vec2D<T> {
+        match self.origin {
+            Origin::Absolute => self.look_ahead(),
+            Origin::Relative => self.velocity * T::from_f64(LOOK_AHEAD)
+        }
+    }
+
+    // Returns the source of the given obstacle for the given look-ahead
+    // point, in the same space as the point.
+    fn source_of<S>(&self, point: Vec2D<T>, geometry: &S) -> Vec2D<T>
+        where S: HasSource<T> + ?Sized
+    {
+        match self.origin {
+            Origin::Absolute => geometry.source(point),
+            Origin::Relative => geometry.relative_source(self.position, point)
+        }
+    }
+
+    // Returns the body of the vehicle swept over the collision horizon.
+    #[cfg(feature = "std")]
+    pub fn swept_body(&self) -> SweptBody {
+        SweptBody { position: self.position.cast()
+                  , velocity: self.velocity.cast()
+                  , radius:   BODY_RADIUS
+                  , horizon:  HORIZON }
     }
 
     // Returns the repulsor defined by the given point and geometry and the
     // distance between the point and corresponding source.
-    fn repulsor(&self, point: Vec2D, geometry: &Box<HasSource>)
-        -> Option<(Vec2D, f64)>
+    pub fn repulsor<S>(&self, point: Vec2D<T>, geometry: &S)
+        -> Option<(Vec2D<T>, T)>
+        where S: HasSource<T> + ?Sized
     {
-        let source = geometry.source(point);
-        let repulsor = source.sub(point);
-        let distance = repulsor.mag();
+        let source = self.source_of(point, geometry);
+        let repulsor = source - point;
+        self.repulsion(repulsor, repulsor.mag())
+    }
 
-        // Determine nature of interaction.
-        if distance < EPSILON { return None; }
+    // Returns the given repulsor, normalised, and the ratio of its distance
+    // to the potential scale, or none if it does not repel the vehicle.
+    pub fn repulsion(&self, repulsor: Vec2D<T>, distance: T)
+        -> Option<(Vec2D<T>, T)>
+    {
+        if distance < T::EPSILON { return None; }
         if distance >= self.potential_scale { return None; }
-        Some((repulsor.mul(1f64 / distance), distance / self.potential_scale))
+        Some((repulsor * (T::ONE / distance), distance / self.potential_scale))
     }
 
     // Returns the potential corresponding to the given repulsor and the ratio
@@ -93,65 +228,203 @@ impl Vehicle {
     // distance that potential emanates from the obstacle (according to the
     // perception of the vehicle). Assumes that the repulsor is a unit vector
     // and ratio is between zero and one.
-    pub fn potential(&self, repulsor: Vec2D, ratio: f64) -> (Vec2D, f64) {
-        // Calculate components of potential.
-        let gd = (1f64 - ratio) * (1f64 - ratio);
-        let hd = ratio.sqrt();
-        let zd = ((1f64 - hd) * (1f64 - hd) + hd * hd).sqrt();
-        let rd = gd * (1f64 - hd) / zd;
-        let td = gd * hd / zd;
+    pub fn potential(&self, repulsor: Vec2D<T>, ratio: T) -> (Vec2D<T>, T) {
+        let (rd, td, gd) = self.profile.terms(ratio);
+        (self.combine(repulsor, rd, td), gd)
+    }
 
+    // Returns the potential of the given unit repulsor with the given radial
+    // and tangential weights, turning towards the vehicle's velocity.
+    fn combine(&self, repulsor: Vec2D<T>, rd: T, td: T) -> Vec2D<T> {
         // Determine basis and calculate potential.
         let mut tangent = repulsor.perp();
-        if tangent.dot(self.velocity) < 0f64 { tangent = tangent.neg(); }
-        (repulsor.mul(rd).add(tangent.mul(td)), gd)
+        if tangent.dot(self.velocity) < T::ZERO { tangent = -tangent; }
+        repulsor * rd + tangent * td
     }
 
     // Returns the potential generated by the given obstacles.
-    pub fn total_potential(&self, obstacles: &Vec<Box<HasSource>>)
-        -> Option<Vec2D>
+    pub fn total_potential<S>(&self, obstacles: &[S]) -> Option<Vec2D<T>>
+        where S: HasSource<T>
+    {
+        self.accumulate_potential(obstacles).map(|x| x.0)
+    }
+
+    // Returns the potential generated by the given obstacles and the budget
+    // remaining after accumulating it.
+    fn accumulate_potential<S>(&self, obstacles: &[S])
+        -> Option<(Vec2D<T>, T)>
+        where S: HasSource<T>
     {
         // Evaluate potential for all obstacles.
-        let point = self.look_ahead();
+        let point = self.look_ahead_point();
         let mut interactions = vec!();
-        for obstacle in obstacles.iter() {
+        for (i, obstacle) in obstacles.iter().enumerate() {
             if let Some(x) = self.repulsor(point, obstacle) {
-                interactions.push(x);
+                interactions.push((i, x));
             }
         }
+        self.accumulate(interactions, |_| {})
+    }
 
+    // Returns the potential generated by the given interactions, each the
+    // index of an obstacle and its repulsor and distance ratio, and the
+    // budget remaining after accumulating it. `step` is called with the
+    // potential of each obstacle as it is accumulated, in order of distance.
+    pub fn accumulate<F>( &self
+                        , mut interactions: Vec<(usize, (Vec2D<T>, T))>
+                        , mut step: F ) -> Option<(Vec2D<T>, T)>
+        where F: FnMut(&Step<T>)
+    {
         // Sort interactions by distance.
         if interactions.len() == 0 { return None; }
         interactions.sort_by(|a, b| {
-            (a.1).partial_cmp(&(b.1)).unwrap_or(Equal)
+            ((a.1).1).partial_cmp(&((b.1).1)).unwrap_or(Equal)
         });
 
         // Accumulate potentials.
         let mut result = Vec2D::zero();
-        let mut remaining = 1f64;
+        let mut remaining = T::ONE;
 
-        for interaction in interactions.iter() {
-            let potential = self.potential(interaction.0, interaction.1);
+        for &(index, (repulsor, ratio)) in interactions.iter() {
+            let (rd, td, gd) = self.profile.terms(ratio);
+            let potential = self.combine(repulsor, rd, td);
 
-            if potential.1 > remaining {
-                let scale = remaining / potential.1;
-                let result = result.add(potential.0.mul(scale));
-                return Some(result);
+            // A falloff within the budget's range spends what remains of it;
+            // a larger one is reported as an overdraft, and one less than zero
+            // as a budget above one, so that profiles breaking the contract
+            // of `Profile::falloff` violate the budget invariant.
+            if gd > remaining {
+                let scale = remaining / gd;
+                let remaining = if gd > T::ONE { remaining - gd }
+                                else { T::ZERO };
+                step(&Step { index:     index
+                           , potential: potential
+                           , rd:        rd
+                           , td:        td
+                           , gd:        gd
+                           , scale:     scale
+                           , remaining: remaining });
+                let result = result + potential * scale;
+                return Some((result, remaining));
+            }
+
+            result += potential;
+            remaining -= gd;
+            step(&Step { index:     index
+                       , potential: potential
+                       , rd:        rd
+                       , td:        td
+                       , gd:        gd
+                       , scale:     T::ONE
+                       , remaining: remaining });
+        }
+        Some((result * self.potential_scale, remaining))
+    }
+
+    // Returns the quantities of the evaluation of `total_potential` that are
+    // subject to invariants. The potential of each obstacle is no larger than
+    // its share of the budget, so the force is bounded by the potential
+    // scale. The most threatening obstacle is the nearest. The radial term of
+    // each potential follows the sign of its repulsor, so the force is
+    // checked against the nearest repulsor itself.
+    #[cfg(feature = "std")]
+    pub fn evaluate_potential<S>(&self, obstacles: &[S]) -> Evaluation
+        where S: HasSource<T>
+    {
+        let point = self.look_ahead_point();
+        let mut nearest: Option<(Vec2D<T>, T)> = None;
+        for obstacle in obstacles.iter() {
+            if let Some(x) = self.repulsor(point, obstacle) {
+                if nearest.map_or(true, |n| x.1 < n.1) { nearest = Some(x); }
             }
+        }
+        let accumulated = self.accumulate_potential(obstacles);
+        Evaluation { force:  accumulated.map(|x| x.0.cast())
+                   , away:   nearest.map(|x| x.0.cast())
+                   , bound:  self.potential_scale.to_f64()
+                   , budget: accumulated.map(|x| x.1.to_f64()) }
+    }
 
-            result = result.add(potential.0);
-            remaining -= potential.1;
+    // Returns a trace of the evaluation of `total_potential` for the given
+    // obstacles.
+    #[cfg(feature = "std")]
+    pub fn explain_potential<S>(&self, obstacles: &[S]) -> Trace
+        where S: HasSource<T>
+    {
+        let mut trace = Trace::new();
+
+        // Record repulsors of all obstacles.
+        let point = self.look_ahead_point();
+        let mut interactions = vec!();
+        for (i, obstacle) in obstacles.iter().enumerate() {
+            let distance = (self.source_of(point, obstacle) - point).mag()
+                                                                   .to_f64();
+            match self.repulsor(point, obstacle) {
+                Some(x) => {
+                    trace.record( Some(i), "repulsor"
+                                , &[ ("distance", distance)
+                                   , ("ratio", x.1.to_f64()) ] );
+                    interactions.push((i, x));
+                },
+                None => trace.record( Some(i), "no repulsor"
+                                    , &[("distance", distance)] )
+            }
         }
-        Some(result.mul(self.potential_scale))
+
+        // Record potentials as they are accumulated, in order of distance.
+        let mut winner = None;
+        let accumulated = self.accumulate(interactions, |x| {
+            if winner.is_none() { winner = Some(x.index); }
+            trace.record( Some(x.index), "potential"
+                        , &[ ("rd", x.rd.to_f64()), ("td", x.td.to_f64())
+                           , ("gd", x.gd.to_f64()), ("scale", x.scale.to_f64())
+                           , ("remaining", x.remaining.to_f64())
+                           , ("x", x.potential.x.to_f64())
+                           , ("y", x.potential.y.to_f64()) ] );
+        });
+
+        let force = accumulated.map(|x| x.0.cast());
+        trace.conclude(winner, force);
+        trace
     }
 }
 
-// Returns a vehicle with semi-random position and velocity and a fixed
-// potential scale.
-#[warn(dead_code)]
-pub fn random_vehicle() -> Vehicle {
-    let angle = random_tau();
-    let position = Vec2D::polar(angle, 100f64 * random_unity());
-    let velocity = Vec2D::polar(angle, 10f64);
-    Vehicle::new(position, velocity, POTENTIAL_SCALE)
+impl<T: Lanes, P: Profile<T>> Vehicle<T, P> {
+    // Calls `found` with the index of each obstacle of the batch that
+    // generates a repulsor, and the repulsor and its distance ratio, in
+    // order. Sources, repulsors and distances are found `BLOCK` obstacles at
+    // a time.
+    pub fn batch_repulsors<F>( &self
+                             , obstacles: &Box<HasSources<T>>
+                             , mut found: F )
+        where F: FnMut(usize, (Vec2D<T>, T))
+    {
+        let point = self.look_ahead_point();
+        let mut sources = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let mut repulsors = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let mut distances = [T::ZERO; BLOCK];
+        for start in (0..obstacles.len()).step_by(BLOCK) {
+            let n = cmp::min(BLOCK, obstacles.len() - start);
+            let (xs, ys) = (&mut sources.0[..n], &mut sources.1[..n]);
+            obstacles.sources(start, point, xs, ys);
+            let (rxs, rys) = (&mut repulsors.0[..n], &mut repulsors.1[..n]);
+            batch::sub(xs, ys, point, rxs, rys);
+            batch::mag(rxs, rys, &mut distances[..n]);
+            for i in 0..n {
+                let repulsor = Vec2D::new(rxs[i], rys[i]);
+                if let Some(x) = self.repulsion(repulsor, distances[i]) {
+                    found(start + i, x);
+                }
+            }
+        }
+    }
+
+    // As `total_potential`, but evaluates a batch of obstacles.
+    pub fn batch_potential(&self, obstacles: &Box<HasSources<T>>)
+        -> Option<Vec2D<T>>
+    {
+        let mut interactions = vec!();
+        self.batch_repulsors(obstacles, |i, x| interactions.push((i, x)));
+        self.accumulate(interactions, |_| {}).map(|x| x.0)
+    }
 }
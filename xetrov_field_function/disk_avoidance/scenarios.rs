@@ -1,14 +1,20 @@
 use types::*;
 
+use super::common::profile::Builtin;
+use super::common::scenarios::*;
 use super::common::types::*;
-use super::linalg::matrix2d::Mat2D;
+use super::linalg::scalar::Scalar;
+use super::linalg::transform2d::Transform2D;
 use super::linalg::vector2d::Vec2D;
 use super::utilities::rng_utilities::*;
-use super::utilities::types::{HasScenario, Obstacles};
+use super::utilities::oracle::Geometry;
+use super::utilities::record::Record;
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+use super::utilities::world::World;
 
 // Returns a disk positioned semi-randomly with respect to `potential_scale`
 // transformed by `to_world`.
-fn near_disk(significant: bool, potential_scale: f64, transform: &Mat2D)
+fn near_disk(significant: bool, potential_scale: f64, transform: &Transform2D)
     -> Box<HasSource>
 {
     let radius = potential_scale * random_margin();
@@ -25,12 +31,52 @@ fn scenario(num_obstacles: u32, significant: bool) -> Box<Scenario> {
     let vehicle = random_vehicle();
     let position = vehicle.look_ahead();
     let orientation = vehicle.velocity.angle();
-    let to_world = Mat2D::rotation(orientation).shift(position);
+    let to_world = Transform2D::new(orientation, position);
 
     let f = |_| near_disk(significant, POTENTIAL_SCALE, &to_world);
     Box::new(Scenario::new(vehicle, (0..num_obstacles).map(f).collect()))
 }
 
+// Returns a disk whose source for `point` lies exactly on the boundary of the
+// repulsor check. Sources at no distance lie on the disk's circumference.
+fn boundary_disk(point: Vec2D, significant: bool, potential_scale: f64)
+    -> Box<HasSource>
+{
+    let direction = random_axis();
+    let distance = boundary_distance(significant, potential_scale);
+    if distance == 0f64 {
+        let radius = random_integer(1, 10);
+        return Box::new(Disk::new(point + direction * radius, radius));
+    }
+
+    // A disk as large as its distance places its source exactly half way to
+    // its centre.
+    let centre = point + direction * (2f64 * distance);
+    Box::new(Disk::new(centre, distance))
+}
+
+// Helper function for creating arrangements of disks on the boundary of the
+// repulsor check.
+fn boundary_scenario(num_obstacles: u32, significant: bool) -> Box<Scenario> {
+    let vehicle = boundary_vehicle();
+    let point = vehicle.look_ahead();
+    let f = |_| boundary_disk(point, significant, POTENTIAL_SCALE);
+    Box::new(Scenario::new(vehicle, (0..num_obstacles).map(f).collect()))
+}
+
+// Returns a scenario with the given configuration of obstacles placed exactly
+// on the boundary of the repulsor check. Returns none if it is not possible to
+// create the given scenario.
+pub fn boundary_scenario_with_obstacles(obstacles: &Obstacles)
+    -> Option<Box<HasScenario>>
+{
+    match obstacles.details() {
+        (num_obs, 0u32) => Some(boundary_scenario(num_obs, false)),
+        (0u32, num_obs) => Some(boundary_scenario(num_obs, true)),
+        _ => None
+    }
+}
+
 // Returns a scenario with the given configuration of obstacles. Returns none
 // if it is not possible to create the given scenario.
 pub fn scenario_with_obstacles(obstacles: &Obstacles)
@@ -42,3 +88,82 @@ pub fn scenario_with_obstacles(obstacles: &Obstacles)
         _ => None
     }
 }
+
+// Returns the scenario described by the given record, or none if the record
+// is incomplete.
+pub fn scenario_from_record(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_with_origin(record, Origin::Absolute)
+}
+
+// As `scenario_from_record`, but the vehicle finds sources about the given
+// origin.
+pub fn scenario_from_record_with_origin(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    scenario_from_record_as::<f64>(record, origin)
+}
+
+// As `scenario_from_record_with_origin`, but the scenario is evaluated in the
+// scalar type `T`.
+pub fn scenario_from_record_as<T: Scalar>(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    let mut obstacles: Vec<Box<HasSource<T>>> = vec!();
+    for disk in disks_from_record(record)? {
+        obstacles.push(Box::new(disk));
+    }
+    let mut vehicle = Vehicle::from_record(record)?;
+    vehicle.origin = origin;
+    Some(Box::new(Scenario::new(vehicle, obstacles)))
+}
+
+// As `scenario_from_record`, but the vehicle shapes potential with the given
+// built-in profile.
+pub fn scenario_from_record_with_profile(record: &Record, profile: Builtin)
+    -> Option<Box<HasScenario>>
+{
+    let mut obstacles: Vec<Box<HasSource>> = vec!();
+    for disk in disks_from_record(record)? {
+        obstacles.push(Box::new(disk));
+    }
+    let vehicle = Vehicle::from_record(record)?.with_profile(profile);
+    Some(Box::new(Scenario::new(vehicle, obstacles)))
+}
+
+// As `scenario_from_record`, but the scenario evaluates its disks in
+// batches.
+pub fn scenario_from_record_batched(record: &Record)
+    -> Option<Box<HasScenario>>
+{
+    let disks = disks_from_record(record)?;
+    let batch = DiskBatch::new(&disks);
+    let mut obstacles: Vec<Box<HasSource>> = vec!();
+    for disk in disks {
+        obstacles.push(Box::new(disk));
+    }
+    let scenario = Scenario::new(Vehicle::from_record(record)?, obstacles);
+    Some(Box::new(BatchScenario::new(scenario, Box::new(batch))))
+}
+
+// Returns the disks described by the given record, or none if the record is
+// incomplete.
+fn disks_from_record<T: Scalar>(record: &Record) -> Option<Vec<Disk<T>>> {
+    let mut disks = vec!();
+    for obstacle in record.obstacles.iter() {
+        let centre = obstacle.vec_as("x", "y")?;
+        let radius = obstacle.get_as("radius")?;
+        disks.push(Disk::new(centre, radius));
+    }
+    Some(disks)
+}
+
+// Returns the scenario of the given shared world. Obstacles other than disks
+// are ignored.
+pub fn scenario_from_world(world: &World) -> Box<HasScenario> {
+    let obstacles = world.obstacles.iter().filter_map(|x| match *x {
+        Geometry::Disk { centre, radius } =>
+            Some(Box::new(Disk::new(centre, radius)) as Box<HasSource>),
+        _ => None
+    }).collect();
+    Box::new(Scenario::new(Vehicle::from_world(world), obstacles))
+}
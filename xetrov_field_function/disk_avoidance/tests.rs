@@ -1,15 +1,256 @@
 #![cfg(test)]
 
+#[cfg(feature = "std")]
+use super::common::profile::{Builtin, Profile, BUILTIN_NAMES};
+use super::common::types::Vehicle;
+use super::linalg::fixed::ArrayVec;
+use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "std")]
+use super::scenarios::boundary_scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record, scenario_from_world};
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_origin;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_as;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_profile;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_batched;
+#[cfg(feature = "std")]
 use super::scenarios::scenario_with_obstacles;
+use super::types::Disk;
+#[cfg(feature = "std")]
+use super::utilities::bench_utilities::configurations;
+#[cfg(feature = "std")]
+use super::utilities::golden::check_corpus;
+#[cfg(all(feature = "std", feature = "deterministic"))]
+use super::utilities::golden::digest_corpus;
+#[cfg(feature = "std")]
+use super::utilities::invariants::{check_scenarios, Invariant, Violation};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::{test_batches, test_drift};
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_precisions;
+#[cfg(feature = "std")]
 use super::utilities::test_utilities::test_scenarios;
-use super::utilities::types::Obstacles;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_worlds;
+#[cfg(feature = "std")]
+use super::utilities::precision::Q16;
+#[cfg(feature = "std")]
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+#[cfg(feature = "std")]
+use super::utilities::world::Kind;
 
+// Distance along each axis by which scenarios are translated when testing
+// drift, far enough from the origin that absolute evaluation drifts.
+#[cfg(feature = "std")]
+const OFFSET: f64 = 1e12;
+
+// Rebuilds scenarios from their records in single precision.
+#[cfg(feature = "std")]
+fn single(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<f32>(record, Origin::Absolute)
+}
+
+// Rebuilds scenarios from their records in fixed point.
+#[cfg(feature = "std")]
+fn fixed(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<Q16>(record, Origin::Absolute)
+}
+
+#[cfg(feature = "std")]
 #[test]
 fn test() {
     for i in 1..6 {
         let obstacles1 = Obstacles::new(i, 0u32);
-        assert!(test_scenarios(&obstacles1, scenario_with_obstacles));
+        test_precisions(&obstacles1, scenario_with_obstacles, single).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, scenario_with_obstacles, single).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn fixed_point() {
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_precisions(&obstacles1, scenario_with_obstacles, fixed).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, scenario_with_obstacles, fixed).unwrap();
+    }
+}
+
+// Boundary scenarios are only checked in double precision: sources at
+// `NEAREST` are nearer than single precision can distinguish from zero.
+#[cfg(feature = "std")]
+#[test]
+fn boundary() {
+    let creator = boundary_scenario_with_obstacles;
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_scenarios(&obstacles1, creator).unwrap();
         let obstacles2 = Obstacles::new(0u32, i);
-        assert!(test_scenarios(&obstacles2, scenario_with_obstacles));
+        test_scenarios(&obstacles2, creator).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn golden() {
+    check_corpus(include_str!("golden.json"), scenario_from_record).unwrap();
+}
+
+// Digest of the golden corpus as evaluated in deterministic mode, which every
+// platform must reproduce.
+#[cfg(all(feature = "std", feature = "deterministic"))]
+const DIGEST: u64 = 0x6d01a648030ad58b;
+
+#[cfg(all(feature = "std", feature = "deterministic"))]
+#[test]
+fn deterministic() {
+    let corpus = include_str!("golden.json");
+    let digest = digest_corpus(corpus, scenario_from_record);
+    assert!(digest == DIGEST, "corpus digest {:#018x}", digest);
+}
+
+// Scenarios break no invariant. The tangential term of a potential is
+// perpendicular to its repulsor, so the force of a single obstacle never
+// opposes the nearest repulsor. With several significant obstacles, the
+// potentials of the others can outweigh the nearest obstacle's radial term,
+// so only there may the force point against it.
+#[cfg(feature = "std")]
+#[test]
+fn invariants() {
+    for obstacles in configurations().iter() {
+        let violations = check_scenarios(obstacles, |o| {
+            scenario_with_obstacles(o).unwrap()
+        });
+        let allowed = |x: &Violation| {
+            x.invariant == Invariant::Away && obstacles.significant > 1
+        };
+        assert!( violations.iter().all(allowed)
+               , "{} / {} obstacles violate invariants"
+               , obstacles.insignificant, obstacles.significant );
+    }
+}
+
+// Scenarios evaluated with each built-in profile find the same interactions
+// and break no invariant, with the allowance `invariants` makes for several
+// significant obstacles. The quadratic profile evaluates the golden corpus
+// as the default profile does.
+#[cfg(feature = "std")]
+#[test]
+fn profiles() {
+    for name in BUILTIN_NAMES.iter() {
+        let profile = Builtin::named(name).unwrap();
+        let creator = |o: &Obstacles| {
+            let record = scenario_with_obstacles(o)?.record();
+            scenario_from_record_with_profile(&record, profile)
+        };
+        for obstacles in configurations().iter() {
+            test_scenarios(obstacles, creator).unwrap();
+            let violations = check_scenarios(obstacles, |o| {
+                creator(o).unwrap()
+            });
+            let allowed = |x: &Violation| {
+                x.invariant == Invariant::Away && obstacles.significant > 1
+            };
+            assert!( violations.iter().all(allowed)
+                   , "{} violates invariants", name );
+        }
+    }
+    let quadratic = Builtin::named("quadratic").unwrap();
+    check_corpus(include_str!("golden.json"), |r| {
+        scenario_from_record_with_profile(r, quadratic)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn world() {
+    test_worlds(Kind::Disks, scenario_from_world).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drift() {
+    test_drift(OFFSET, scenario_with_obstacles, |r| {
+        scenario_from_record_with_origin(r, Origin::Relative)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn batch() {
+    let rebuild = scenario_from_record;
+    let batched = scenario_from_record_batched;
+    test_batches(scenario_with_obstacles, rebuild, batched).unwrap();
+    test_batches(boundary_scenario_with_obstacles, rebuild, batched).unwrap();
+}
+
+// Disks held in a fixed-capacity collection repel the vehicle as they do in
+// an array.
+#[test]
+fn fixed_capacity() {
+    let vehicle = Vehicle::new(Vec2D::zero(), Vec2D::new(10f64, 0f64), 10f64);
+    let centres = [(8f64, 1f64), (3f64, -2f64), (40f64, 0f64)];
+    let disk = |&(x, y): &(f64, f64)| Disk::new(Vec2D::new(x, y), 1f64);
+    let disks = [disk(&centres[0]), disk(&centres[1]), disk(&centres[2])];
+    let mut fixed: ArrayVec<Disk, 3> = ArrayVec::new();
+    for centre in centres.iter() { fixed.push(disk(centre)); }
+    assert!(fixed.try_push(disk(&centres[0])).is_err());
+    let force = vehicle.total_potential(&fixed);
+    assert!(force.is_some());
+    assert_eq!(force, vehicle.total_potential(&disks));
+}
+
+// Profile whose falloff is a multiple of the quadratic profile's.
+#[cfg(feature = "std")]
+struct Scaled(f64);
+
+#[cfg(feature = "std")]
+impl Profile<f64> for Scaled {
+    fn falloff(&self, ratio: f64) -> f64 {
+        self.0 * (1f64 - ratio) * (1f64 - ratio)
     }
 }
+
+// Disks beside the look-ahead point exhaust the budget of the quadratic
+// profile without violating the budget invariant, which flags profiles whose
+// falloff exceeds one or is negative.
+#[cfg(feature = "std")]
+#[test]
+fn budget() {
+    let vehicle = || {
+        Vehicle::new(Vec2D::zero(), Vec2D::new(10f64, 0f64), 10f64)
+    };
+    let point = vehicle().look_ahead_point();
+    let disk = |y: f64| Disk::new(point + Vec2D::new(0f64, y), 1f64);
+    let disks = [disk(2f64), disk(-2f64)];
+    let budget = |scale: f64| {
+        let scaled = vehicle().with_profile(Scaled(scale));
+        let evaluation = scaled.evaluate_potential(&disks);
+        let violations = evaluation.violations();
+        let flagged = violations.iter().any(|x| x.0 == Invariant::Budget);
+        (evaluation.budget.unwrap(), flagged)
+    };
+    assert_eq!(budget(1f64), (0f64, false));
+    let overdrawn = budget(2f64);
+    assert!(overdrawn.0 < 0f64 && overdrawn.1);
+    let raised = budget(-1f64);
+    assert!(raised.0 > 1f64 && raised.1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialisation() {
+    let disk = Disk::new(Vec2D::new(1f64, 2f64), 0.5f64);
+    let json = ::serde_json::to_string(&disk).unwrap();
+    assert_eq!(json, r#"{"centre":{"x":1.0,"y":2.0},"radius":0.5}"#);
+    let rebuilt: Disk = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.to_circle(), disk.to_circle());
+}
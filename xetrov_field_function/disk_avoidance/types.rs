@@ -1,28 +1,137 @@
-use super::common::types::HasSource;
-use super::linalg::matrix2d::Mat2D;
+use super::common::types::{HasSource, HasSources};
+use super::linalg::batch::{self, Lanes, Transform2DBatch, BLOCK};
+use super::linalg::geometry::Circle;
+use super::linalg::scalar::Scalar;
+use super::linalg::transform2d::Transform2D;
 use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "std")]
+use super::utilities::oracle::Geometry;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 // Defines transforms in and out of a space containing a disk.
-pub struct Disk { pub to_world: Mat2D
-                , pub to_local: Mat2D
-                , pub radius:   f64 }
+pub struct Disk<T = f64> { pub to_world: Transform2D<T>
+                         , pub to_local: Transform2D<T>
+                         , pub radius:   T }
 
-impl HasSource for Disk {
+impl<T: Scalar> HasSource<T> for Disk<T> {
     // Returns the source corresponding to the given point.
-    fn source(&self, v: Vec2D) -> Vec2D {
+    fn source(&self, v: Vec2D<T>) -> Vec2D<T> {
         let local = self.to_local.transform(v);
         let mag_sq = local.mag_sq();
         if mag_sq <= self.radius * self.radius { return v; }
-        let source = local.mul(self.radius / mag_sq.sqrt());
+        let source = local * (self.radius / mag_sq.sqrt());
         self.to_world.transform(source)
     }
+
+    // Returns the centre and radius of the disk.
+    #[cfg(feature = "std")]
+    fn geometry(&self) -> Geometry {
+        Geometry::Disk { centre: self.to_world.transform(Vec2D::zero()).cast()
+                       , radius: self.radius.to_f64() }
+    }
+
+    // Returns the source corresponding to the given point relative to the
+    // origin, using only differences from the disk's centre.
+    fn relative_source(&self, origin: Vec2D<T>, v: Vec2D<T>) -> Vec2D<T> {
+        let centre = self.to_world.transform(Vec2D::zero()) - origin;
+        let local = v - centre;
+        let mag_sq = local.mag_sq();
+        if mag_sq <= self.radius * self.radius { return v; }
+        centre + local * (self.radius / mag_sq.sqrt())
+    }
 }
 
-impl Disk {
+impl<T: Scalar> Disk<T> {
     // Creates a disk from a position and radius.
-    pub fn new(pos: Vec2D, radius: f64) -> Disk {
-        let to_world = Mat2D::translation(pos);
-        let to_local = Mat2D::translation(pos.neg());
+    pub fn new(pos: Vec2D<T>, radius: T) -> Disk<T> {
+        let to_world = Transform2D::translation(pos);
+        let to_local = to_world.inverse();
         Disk { to_world: to_world, to_local: to_local, radius: radius }
     }
+
+    // Creates a disk covering the given circle.
+    pub fn from_circle(circle: &Circle<T>) -> Disk<T> {
+        Disk::new(circle.centre, circle.radius)
+    }
+
+    // Returns the circle the disk covers.
+    pub fn to_circle(&self) -> Circle<T> {
+        Circle::new(self.to_world.transform(Vec2D::zero()), self.radius)
+    }
+}
+
+// Serialises the circle the disk covers, from which its transforms follow.
+#[cfg(feature = "serde")]
+impl<T: Scalar + Serialize> Serialize for Disk<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        self.to_circle().serialize(serializer)
+    }
+}
+
+// Deserialises a disk from the circle it covers.
+#[cfg(feature = "serde")]
+impl<'de, T: Scalar + Deserialize<'de>> Deserialize<'de> for Disk<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Disk<T>, D::Error>
+    {
+        Circle::deserialize(deserializer).map(|x| Disk::from_circle(&x))
+    }
+}
+
+// Disks held as a batch of the transforms in and out of their spaces and
+// their radii.
+pub struct DiskBatch<T = f64> { pub to_world: Transform2DBatch<T>
+                              , pub to_local: Transform2DBatch<T>
+                              , pub radii:    Vec<T> }
+
+impl<T: Lanes> HasSources<T> for DiskBatch<T> {
+    // Returns the number of disks in the batch.
+    fn len(&self) -> usize {
+        self.radii.len()
+    }
+
+    // Writes the sources of the disks from `start` corresponding to the
+    // given point.
+    fn sources(&self, start: usize, v: Vec2D<T>, xs: &mut [T], ys: &mut [T]) {
+        let n = xs.len();
+        let mut local = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let mut mags_sq = [T::ZERO; BLOCK];
+        let (lxs, lys) = (&mut local.0[..n], &mut local.1[..n]);
+        batch::transform_each(&self.to_local, start, v, lxs, lys);
+        batch::mag_sq(lxs, lys, &mut mags_sq[..n]);
+        for i in 0..n {
+            let scale = self.radii[start + i] / mags_sq[i].sqrt();
+            lxs[i] = lxs[i] * scale;
+            lys[i] = lys[i] * scale;
+        }
+        batch::transform_lanes(&self.to_world, start, lxs, lys, xs, ys);
+        for i in 0..n {
+            let radius = self.radii[start + i];
+            if mags_sq[i] <= radius * radius {
+                xs[i] = v.x;
+                ys[i] = v.y;
+            }
+        }
+    }
+}
+
+impl<T: Lanes> DiskBatch<T> {
+    // Creates a batch of the given disks.
+    pub fn new(disks: &[Disk<T>]) -> DiskBatch<T> {
+        let mut batch = DiskBatch { to_world: Transform2DBatch::new()
+                                  , to_local: Transform2DBatch::new()
+                                  , radii:    vec!() };
+        for disk in disks.iter() {
+            batch.to_world.push(&disk.to_world);
+            batch.to_local.push(&disk.to_local);
+            batch.radii.push(disk.radius);
+        }
+        batch
+    }
 }
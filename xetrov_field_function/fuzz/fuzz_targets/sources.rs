@@ -0,0 +1,54 @@
+#![no_main]
+#[macro_use]
+extern crate arbitrary;
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate common;
+extern crate disk_avoidance;
+extern crate utilities;
+extern crate wall_avoidance;
+
+use common::types::{HasSource, Vehicle, POTENTIAL_SCALE};
+use disk_avoidance::types::Disk;
+use utilities::fuzz::{check_finite, positive, Point};
+use wall_avoidance::types::Segment;
+
+// Arbitrary geometry of each obstacle type. Only disks require a positive
+// radius; segments are not checked for degeneracy when created.
+#[derive(Arbitrary, Debug)]
+enum Geometry { Disk(Point, f64)
+              , Segment(Point, Point)
+              , Vehicle(Point, Point) }
+
+impl Geometry {
+    // Returns the obstacle with this geometry, or none if it is out of range.
+    fn obstacle(&self) -> Option<Box<HasSource>> {
+        match *self {
+            Geometry::Disk(centre, radius) => {
+                let disk = (centre.vec(), positive(radius));
+                if let (Some(c), Some(r)) = disk {
+                    return Some(Box::new(Disk::new(c, r)));
+                }
+            },
+            Geometry::Segment(point1, point2) => {
+                if let (Some(p1), Some(p2)) = (point1.vec(), point2.vec()) {
+                    return Some(Box::new(Segment::new(p1, p2)));
+                }
+            },
+            Geometry::Vehicle(position, velocity) => {
+                if let (Some(p), Some(v)) = (position.vec(), velocity.vec()) {
+                    let vehicle = Vehicle::new(p, v, POTENTIAL_SCALE);
+                    return Some(Box::new(vehicle));
+                }
+            }
+        }
+        None
+    }
+}
+
+// Finds the source of an arbitrary point on an arbitrary obstacle.
+fuzz_target!(|input: (Geometry, Point)| {
+    if let (Some(obstacle), Some(v)) = (input.0.obstacle(), input.1.vec()) {
+        check_finite(Some(obstacle.source(v)), &input);
+    }
+});
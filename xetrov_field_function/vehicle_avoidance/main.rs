@@ -1,9 +1,23 @@
 extern crate vehicle_avoidance;
-use vehicle_avoidance::scenarios::scenario_with_obstacles;
+use vehicle_avoidance::scenarios::*;
 
 extern crate common;
 use common::bench_utilities::run_benchmarks;
 
+extern crate utilities;
+use utilities::precision::Q16;
+use utilities::world::Kind;
+
+// Label of the benchmarked algorithm in results.
+const ALGORITHM: &'static str = "xetrov_field_function/vehicle_avoidance";
+
 fn main() {
-    run_benchmarks(scenario_with_obstacles);
+    run_benchmarks( ALGORITHM
+                  , scenario_with_obstacles
+                  , Kind::Vehicles
+                  , scenario_from_world
+                  , scenario_from_record_with_origin
+                  , scenario_from_record_as::<f32>
+                  , scenario_from_record_as::<Q16>
+                  , scenario_from_record_with_profile );
 }
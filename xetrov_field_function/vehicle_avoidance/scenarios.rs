@@ -1,7 +1,13 @@
+use super::common::profile::Builtin;
+use super::common::scenarios::*;
 use super::common::types::*;
+use super::linalg::scalar::Scalar;
 use super::linalg::vector2d::Vec2D;
 use super::utilities::rng_utilities::*;
-use super::utilities::types::{HasScenario, Obstacles};
+use super::utilities::oracle::Geometry;
+use super::utilities::record::Record;
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+use super::utilities::world::World;
 
 // Returns a semi-random vehicle near to the given position.
 fn near_vehicle(position: Vec2D, significant: bool, potential_scale: f64)
@@ -9,13 +15,12 @@ fn near_vehicle(position: Vec2D, significant: bool, potential_scale: f64)
 {
     let mut offset1 = random_margin();
     if !significant { offset1 += 1f64; }
-    let future_position = position.add(
-        Vec2D::polar( random_tau(), offset1 * POTENTIAL_SCALE )
-    );
+    let future_position =
+        position + Vec2D::polar(random_tau(), offset1 * POTENTIAL_SCALE);
 
     let offset2 = 0.25f64 * potential_scale * random_unity();
     let velocity = Vec2D::polar(random_tau(), offset2);
-    let position = future_position.sub(velocity.mul(LOOK_AHEAD));
+    let position = future_position - velocity * LOOK_AHEAD;
 
     Box::new(Vehicle::new(position, velocity, potential_scale))
 }
@@ -30,6 +35,41 @@ fn scenario(num_obstacles: u32, significant: bool) -> Box<Scenario> {
     Box::new(Scenario::new(vehicle, obstacles))
 }
 
+// Returns a vehicle whose look-ahead point lies exactly on the boundary of the
+// repulsor check of `point`. At no distance, the look-ahead points coincide.
+fn boundary_near_vehicle(point: Vec2D, significant: bool, potential_scale: f64)
+    -> Box<HasSource>
+{
+    let distance = boundary_distance(significant, potential_scale);
+    let future_position = point + random_axis() * distance;
+    let velocity = random_axis() * random_integer(0, 10);
+    let position = future_position - velocity * LOOK_AHEAD;
+    Box::new(Vehicle::new(position, velocity, potential_scale))
+}
+
+// Returns a scenario involving a single vehicle and other vehicles whose
+// look-ahead points lie on the boundary of its repulsor check.
+fn boundary_scenario(num_obstacles: u32, significant: bool) -> Box<Scenario> {
+    let vehicle = boundary_vehicle();
+    let point = vehicle.look_ahead();
+    let f = |_| boundary_near_vehicle(point, significant, POTENTIAL_SCALE);
+    let obstacles = (0..num_obstacles).map(f).collect();
+    Box::new(Scenario::new(vehicle, obstacles))
+}
+
+// Returns a scenario with the given configuration of obstacles placed exactly
+// on the boundary of the repulsor check. Returns none if it is not possible to
+// create the given scenario.
+pub fn boundary_scenario_with_obstacles(obstacles: &Obstacles)
+    -> Option<Box<HasScenario>>
+{
+    match obstacles.details() {
+        (num_obs, 0u32) => Some(boundary_scenario(num_obs, false)),
+        (0u32, num_obs) => Some(boundary_scenario(num_obs, true)),
+        _ => None
+    }
+}
+
 // Returns a scenario with the given configuration of obstacles. Returns none
 // if it is not possible to create the given scenario.
 pub fn scenario_with_obstacles(obstacles: &Obstacles)
@@ -41,3 +81,65 @@ pub fn scenario_with_obstacles(obstacles: &Obstacles)
         _ => None
     }
 }
+
+// Returns the scenario described by the given record, or none if the record
+// is incomplete. Only the look-ahead points of other vehicles are sources, so
+// they share the vehicle's potential scale.
+pub fn scenario_from_record(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_with_origin(record, Origin::Absolute)
+}
+
+// As `scenario_from_record`, but the vehicle finds sources about the given
+// origin.
+pub fn scenario_from_record_with_origin(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    scenario_from_record_as::<f64>(record, origin)
+}
+
+// As `scenario_from_record_with_origin`, but the scenario is evaluated in the
+// scalar type `T`.
+pub fn scenario_from_record_as<T: Scalar>(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    let mut vehicle = Vehicle::<T>::from_record(record)?;
+    vehicle.origin = origin;
+    let mut obstacles: Vec<Box<HasSource<T>>> = vec!();
+    for obstacle in record.obstacles.iter() {
+        obstacles.push(Box::new(Vehicle::new( obstacle.vec_as("x", "y")?
+                                            , obstacle.vec_as("vx", "vy")?
+                                            , vehicle.potential_scale )));
+    }
+    Some(Box::new(Scenario::new(vehicle, obstacles)))
+}
+
+// As `scenario_from_record`, but the vehicle shapes potential with the given
+// built-in profile. Other vehicles are only sources, so keep the default.
+pub fn scenario_from_record_with_profile(record: &Record, profile: Builtin)
+    -> Option<Box<HasScenario>>
+{
+    let vehicle = Vehicle::from_record(record)?;
+    let mut obstacles: Vec<Box<HasSource>> = vec!();
+    for obstacle in record.obstacles.iter() {
+        obstacles.push(Box::new(Vehicle::new( obstacle.vec_as("x", "y")?
+                                            , obstacle.vec_as("vx", "vy")?
+                                            , vehicle.potential_scale )));
+    }
+    let vehicle = vehicle.with_profile(profile);
+    Some(Box::new(Scenario::new(vehicle, obstacles)))
+}
+
+// Returns the scenario of the given shared world. Only the look-ahead points
+// of other vehicles are sources, so they share the vehicle's potential scale.
+// Obstacles other than vehicles are ignored.
+pub fn scenario_from_world(world: &World) -> Box<HasScenario> {
+    let vehicle = Vehicle::from_world(world);
+    let scale = vehicle.potential_scale;
+    let obstacles = world.obstacles.iter().filter_map(|x| match *x {
+        Geometry::Vehicle { position, velocity, .. } =>
+            Some(Box::new(Vehicle::new(position, velocity, scale))
+                 as Box<HasSource>),
+        _ => None
+    }).collect();
+    Box::new(Scenario::new(vehicle, obstacles))
+}
@@ -1,15 +1,258 @@
 #![cfg(test)]
 
+#[cfg(feature = "std")]
+use super::common::profile::{Builtin, BUILTIN_NAMES};
+#[cfg(all(feature = "std", feature = "serde"))]
+use super::common::profile::{Biased, Quadratic};
+use super::common::profile::{Exponential, Profile};
+use super::common::types::Vehicle;
+use super::linalg::fixed::ArrayVec;
+use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "std")]
+use super::scenarios::boundary_scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record, scenario_from_world};
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_origin;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_as;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_profile;
+#[cfg(feature = "std")]
 use super::scenarios::scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::utilities::bench_utilities::configurations;
+#[cfg(feature = "std")]
+use super::utilities::golden::check_corpus;
+#[cfg(all(feature = "std", feature = "deterministic"))]
+use super::utilities::golden::digest_corpus;
+#[cfg(feature = "std")]
+use super::utilities::invariants::{check_scenarios, Invariant, Violation};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::{test_drift, test_precisions};
+#[cfg(feature = "std")]
 use super::utilities::test_utilities::test_scenarios;
-use super::utilities::types::Obstacles;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_worlds;
+#[cfg(feature = "std")]
+use super::utilities::precision::Q16;
+#[cfg(feature = "std")]
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+#[cfg(feature = "std")]
+use super::utilities::world::Kind;
 
+// Distance along each axis by which scenarios are translated when testing
+// drift, far enough from the origin that absolute evaluation drifts.
+#[cfg(feature = "std")]
+const OFFSET: f64 = 1e12;
+
+// Rebuilds scenarios from their records in single precision.
+#[cfg(feature = "std")]
+fn single(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<f32>(record, Origin::Absolute)
+}
+
+// Rebuilds scenarios from their records in fixed point.
+#[cfg(feature = "std")]
+fn fixed(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<Q16>(record, Origin::Absolute)
+}
+
+#[cfg(feature = "std")]
 #[test]
 fn test() {
     for i in 1..6 {
         let obstacles1 = Obstacles::new(i, 0u32);
-        assert!(test_scenarios(&obstacles1, scenario_with_obstacles));
+        test_precisions(&obstacles1, scenario_with_obstacles, single).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, scenario_with_obstacles, single).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn fixed_point() {
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_precisions(&obstacles1, scenario_with_obstacles, fixed).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, scenario_with_obstacles, fixed).unwrap();
+    }
+}
+
+// Boundary scenarios are only checked in double precision: sources at
+// `NEAREST` are nearer than single precision can distinguish from zero.
+#[cfg(feature = "std")]
+#[test]
+fn boundary() {
+    let creator = boundary_scenario_with_obstacles;
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_scenarios(&obstacles1, creator).unwrap();
         let obstacles2 = Obstacles::new(0u32, i);
-        assert!(test_scenarios(&obstacles2, scenario_with_obstacles));
+        test_scenarios(&obstacles2, creator).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn golden() {
+    check_corpus(include_str!("golden.json"), scenario_from_record).unwrap();
+}
+
+// Digest of the golden corpus as evaluated in deterministic mode, which every
+// platform must reproduce.
+#[cfg(all(feature = "std", feature = "deterministic"))]
+const DIGEST: u64 = 0x1a0648dacda02745;
+
+#[cfg(all(feature = "std", feature = "deterministic"))]
+#[test]
+fn deterministic() {
+    let corpus = include_str!("golden.json");
+    let digest = digest_corpus(corpus, scenario_from_record);
+    assert!(digest == DIGEST, "corpus digest {:#018x}", digest);
+}
+
+// Scenarios break no invariant. The tangential term of a potential is
+// perpendicular to its repulsor, so the force of a single obstacle never
+// opposes the nearest repulsor. With several significant obstacles, the
+// potentials of the others can outweigh the nearest obstacle's radial term,
+// so only there may the force point against it.
+#[cfg(feature = "std")]
+#[test]
+fn invariants() {
+    for obstacles in configurations().iter() {
+        let violations = check_scenarios(obstacles, |o| {
+            scenario_with_obstacles(o).unwrap()
+        });
+        let allowed = |x: &Violation| {
+            x.invariant == Invariant::Away && obstacles.significant > 1
+        };
+        assert!( violations.iter().all(allowed)
+               , "{} / {} obstacles violate invariants"
+               , obstacles.insignificant, obstacles.significant );
+    }
+}
+
+// Scenarios evaluated with each built-in profile find the same interactions
+// and break no invariant, with the allowance `invariants` makes for several
+// significant obstacles. The quadratic profile evaluates the golden corpus
+// as the default profile does.
+#[cfg(feature = "std")]
+#[test]
+fn profiles() {
+    for name in BUILTIN_NAMES.iter() {
+        let profile = Builtin::named(name).unwrap();
+        let creator = |o: &Obstacles| {
+            let record = scenario_with_obstacles(o)?.record();
+            scenario_from_record_with_profile(&record, profile)
+        };
+        for obstacles in configurations().iter() {
+            test_scenarios(obstacles, creator).unwrap();
+            let violations = check_scenarios(obstacles, |o| {
+                creator(o).unwrap()
+            });
+            let allowed = |x: &Violation| {
+                x.invariant == Invariant::Away && obstacles.significant > 1
+            };
+            assert!( violations.iter().all(allowed)
+                   , "{} violates invariants", name );
+        }
+    }
+    let quadratic = Builtin::named("quadratic").unwrap();
+    check_corpus(include_str!("golden.json"), |r| {
+        scenario_from_record_with_profile(r, quadratic)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn world() {
+    test_worlds(Kind::Vehicles, scenario_from_world).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drift() {
+    test_drift(OFFSET, scenario_with_obstacles, |r| {
+        scenario_from_record_with_origin(r, Origin::Relative)
+    }).unwrap();
+}
+
+// Vehicles held in a fixed-capacity collection repel the vehicle as they do
+// in an array.
+#[test]
+fn fixed_capacity() {
+    let vehicle = Vehicle::new(Vec2D::zero(), Vec2D::new(10f64, 0f64), 10f64);
+    let others = [ (8f64, 1f64, -2f64), (2f64, -3f64, 4f64)
+                 , (40f64, 0f64, 0f64) ];
+    let other = |&(x, y, vx): &(f64, f64, f64)| {
+        Vehicle::new(Vec2D::new(x, y), Vec2D::new(vx, 0f64), 10f64)
+    };
+    let vehicles = [other(&others[0]), other(&others[1]), other(&others[2])];
+    let mut fixed: ArrayVec<Vehicle, 3> = ArrayVec::new();
+    for x in others.iter() { fixed.push(other(x)); }
+    assert!(fixed.try_push(other(&others[0])).is_err());
+    let force = vehicle.total_potential(&fixed);
+    assert!(force.is_some());
+    assert_eq!(force, vehicle.total_potential(&vehicles));
+}
+
+// Exponential profiles need a positive rate that leaves a floor below one,
+// without which they cannot be scaled to fall from one to zero.
+#[test]
+fn exponential_rates() {
+    for &rate in [0f64, -1f64, f64::NAN, 1e-20f64].iter() {
+        assert!(Exponential::new(rate).is_none(), "rate {}", rate);
     }
+    let profile = Exponential::new(2f64).unwrap();
+    assert!((profile.falloff(0f64) - 1f64).abs() < 1e-15f64);
+    assert!(profile.falloff(1f64).abs() < 1e-15f64);
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+#[test]
+fn serialisation() {
+    let vehicle = Vehicle { position:        Vec2D::new(1f64, 2f64)
+                          , velocity:        Vec2D::new(0f64, -1f64)
+                          , potential_scale: 0.5f64
+                          , origin:          Origin::Relative
+                          , profile:         Quadratic };
+    let json = ::serde_json::to_string(&vehicle).unwrap();
+    let fields = concat!( r#"{"position":{"x":1.0,"y":2.0},"#
+                        , r#""velocity":{"x":0.0,"y":-1.0},"#
+                        , r#""potential_scale":0.5,"#
+                        , r#""origin":"relative""# );
+    assert_eq!(json, format!(r#"{},"profile":null}}"#, fields));
+    let rebuilt: Vehicle = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(::serde_json::to_string(&rebuilt).unwrap(), json);
+    let defaulted: Vehicle = ::serde_json::from_str(&format!("{}}}", fields))
+        .unwrap();
+    assert_eq!(::serde_json::to_string(&defaulted).unwrap(), json);
+
+    let profile = Biased::new(Exponential::new(2f64).unwrap(), 1.5f64);
+    let json = ::serde_json::to_string(&vehicle.with_profile(profile)).unwrap();
+    let expected = r#""profile":{"profile":{"rate":2.0},"bias":1.5}}"#;
+    assert!(json.ends_with(expected));
+    let rebuilt: Vehicle<f64, Biased<Exponential>> =
+        ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.profile, profile);
+    let zero = json.replace(r#""rate":2.0"#, r#""rate":0.0"#);
+    let read: Result<Vehicle<f64, Biased<Exponential>>, _> =
+        ::serde_json::from_str(&zero);
+    assert!(read.is_err());
+
+    let builtin: Vehicle<f64, Builtin> =
+        ::serde_json::from_str(&format!("{}}}", fields)).unwrap();
+    assert_eq!(builtin.profile, Builtin::default());
+    let json = ::serde_json::to_string(&builtin).unwrap();
+    assert!(json.ends_with(r#""profile":{"quadratic":null}}"#));
+    let exponential = Builtin::named("exponential").unwrap();
+    let json = ::serde_json::to_string(&builtin.with_profile(exponential))
+        .unwrap();
+    assert!(json.ends_with(r#""profile":{"exponential":{"rate":4.0}}}"#));
+    let rebuilt: Vehicle<f64, Builtin> = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.profile, exponential);
 }
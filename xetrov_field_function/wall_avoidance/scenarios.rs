@@ -1,15 +1,21 @@
 use types::*;
 
+use super::common::profile::Builtin;
+use super::common::scenarios::*;
 use super::common::types::*;
+use super::linalg::scalar::Scalar;
 use super::linalg::vector2d::Vec2D;
-use super::linalg::matrix2d::Mat2D;
+use super::linalg::transform2d::Transform2D;
 use super::utilities::rng_utilities::*;
-use super::utilities::types::{HasScenario, Obstacles};
+use super::utilities::oracle::Geometry;
+use super::utilities::record::Record;
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+use super::utilities::world::World;
 
 use std::f64::consts::PI;
 
 // Returns a line segment positioned semi-randomly.
-fn near_wall(significant: bool, potential_scale: f64, to_world: &Mat2D)
+fn near_wall(significant: bool, potential_scale: f64, to_world: &Transform2D)
     -> Box<HasSource>
 {
     let angle = random_tau();
@@ -21,8 +27,8 @@ fn near_wall(significant: bool, potential_scale: f64, to_world: &Mat2D)
     let local_point1 = Vec2D::polar(angle + 0.5f64 * PI, 0.5f64);
     let local_point2 = Vec2D::polar(angle - 0.5f64 * PI, 0.5f64);
 
-    let point1 = to_world.transform(point.add(local_point1));
-    let point2 = to_world.transform(point.add(local_point2));
+    let point1 = to_world.transform(point + local_point1);
+    let point2 = to_world.transform(point + local_point2);
     Box::new(Segment::new(point1, point2))
 }
 
@@ -31,12 +37,58 @@ fn scenario(num_obstacles: u32, significant: bool) -> Box<Scenario> {
     let vehicle = random_vehicle();
     let position = vehicle.look_ahead();
     let orientation = vehicle.velocity.angle();
-    let to_world = Mat2D::rotation(orientation).shift(position);
+    let to_world = Transform2D::new(orientation, position);
 
     let f = |_| near_wall(significant, POTENTIAL_SCALE, &to_world);
     Box::new(Scenario::new(vehicle, (0..num_obstacles).map(f).collect()))
 }
 
+// Length of walls in boundary scenarios, a power of two so that transforms
+// into and out of their space are exact.
+const BOUNDARY_WALL_LENGTH: f64 = 4f64;
+
+// Returns a wall parallel to the x-axis whose source for `point` lies exactly
+// on the boundary of the repulsor check. The source is either perpendicular
+// to the wall or one of its endpoints, and at no distance lies on the wall.
+fn boundary_wall(point: Vec2D, significant: bool, potential_scale: f64)
+    -> Box<HasSource>
+{
+    let direction = random_axis();
+    let distance = boundary_distance(significant, potential_scale);
+    let source = point + direction * distance;
+    let length = Vec2D::unitx() * BOUNDARY_WALL_LENGTH;
+    let (point1, point2) = if direction.x == 0f64 {
+        (source - length * 0.5f64, source + length * 0.5f64)
+    } else if direction.x > 0f64 {
+        (source, source + length)
+    } else {
+        (source - length, source)
+    };
+    Box::new(Segment::new(point1, point2))
+}
+
+// Helper function for creating arrangements of walls on the boundary of the
+// repulsor check.
+fn boundary_scenario(num_obstacles: u32, significant: bool) -> Box<Scenario> {
+    let vehicle = boundary_vehicle();
+    let point = vehicle.look_ahead();
+    let f = |_| boundary_wall(point, significant, POTENTIAL_SCALE);
+    Box::new(Scenario::new(vehicle, (0..num_obstacles).map(f).collect()))
+}
+
+// Returns a scenario with the given configuration of obstacles placed exactly
+// on the boundary of the repulsor check. Returns none if it is not possible to
+// create the given scenario.
+pub fn boundary_scenario_with_obstacles(obstacles: &Obstacles)
+    -> Option<Box<HasScenario>>
+{
+    match obstacles.details() {
+        (num_obs, 0u32) => Some(boundary_scenario(num_obs, false)),
+        (0u32, num_obs) => Some(boundary_scenario(num_obs, true)),
+        _ => None
+    }
+}
+
 // Returns a scenario with the given configuration of obstacles. Returns none
 // if it is not possible to create the given scenario.
 pub fn scenario_with_obstacles(obstacles: &Obstacles)
@@ -48,3 +100,84 @@ pub fn scenario_with_obstacles(obstacles: &Obstacles)
         _ => None
     }
 }
+
+// Returns the scenario described by the given record, or none if the record
+// is incomplete.
+pub fn scenario_from_record(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_with_origin(record, Origin::Absolute)
+}
+
+// As `scenario_from_record`, but the vehicle finds sources about the given
+// origin.
+pub fn scenario_from_record_with_origin(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    scenario_from_record_as::<f64>(record, origin)
+}
+
+// As `scenario_from_record_with_origin`, but the scenario is evaluated in the
+// scalar type `T`.
+pub fn scenario_from_record_as<T: Scalar>(record: &Record, origin: Origin)
+    -> Option<Box<HasScenario>>
+{
+    let mut obstacles: Vec<Box<HasSource<T>>> = vec!();
+    for segment in segments_from_record(record)? {
+        obstacles.push(Box::new(segment));
+    }
+    let mut vehicle = Vehicle::from_record(record)?;
+    vehicle.origin = origin;
+    Some(Box::new(Scenario::new(vehicle, obstacles)))
+}
+
+// As `scenario_from_record`, but the vehicle shapes potential with the given
+// built-in profile.
+pub fn scenario_from_record_with_profile(record: &Record, profile: Builtin)
+    -> Option<Box<HasScenario>>
+{
+    let mut obstacles: Vec<Box<HasSource>> = vec!();
+    for segment in segments_from_record(record)? {
+        obstacles.push(Box::new(segment));
+    }
+    let vehicle = Vehicle::from_record(record)?.with_profile(profile);
+    Some(Box::new(Scenario::new(vehicle, obstacles)))
+}
+
+// As `scenario_from_record`, but the scenario evaluates its segments in
+// batches.
+pub fn scenario_from_record_batched(record: &Record)
+    -> Option<Box<HasScenario>>
+{
+    let segments = segments_from_record(record)?;
+    let batch = SegmentBatch::new(&segments);
+    let mut obstacles: Vec<Box<HasSource>> = vec!();
+    for segment in segments {
+        obstacles.push(Box::new(segment));
+    }
+    let scenario = Scenario::new(Vehicle::from_record(record)?, obstacles);
+    Some(Box::new(BatchScenario::new(scenario, Box::new(batch))))
+}
+
+// Returns the segments described by the given record, or none if the record
+// is incomplete.
+fn segments_from_record<T: Scalar>(record: &Record)
+    -> Option<Vec<Segment<T>>>
+{
+    let mut segments = vec!();
+    for obstacle in record.obstacles.iter() {
+        let point1 = obstacle.vec_as("x1", "y1")?;
+        let point2 = obstacle.vec_as("x2", "y2")?;
+        segments.push(Segment::new(point1, point2));
+    }
+    Some(segments)
+}
+
+// Returns the scenario of the given shared world. Obstacles other than
+// segments are ignored.
+pub fn scenario_from_world(world: &World) -> Box<HasScenario> {
+    let obstacles = world.obstacles.iter().filter_map(|x| match *x {
+        Geometry::Segment { point1, point2 } =>
+            Some(Box::new(Segment::new(point1, point2)) as Box<HasSource>),
+        _ => None
+    }).collect();
+    Box::new(Scenario::new(Vehicle::from_world(world), obstacles))
+}
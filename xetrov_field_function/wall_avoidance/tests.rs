@@ -1,15 +1,266 @@
 #![cfg(test)]
 
+#[cfg(feature = "std")]
+use super::common::profile::{Builtin, BUILTIN_NAMES};
+use super::common::types::Vehicle;
+use super::linalg::fixed::ArrayVec;
+use super::linalg::geometry;
+use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "std")]
+use super::scenarios::boundary_scenario_with_obstacles;
+#[cfg(feature = "std")]
+use super::scenarios::{scenario_from_record, scenario_from_world};
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_origin;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_as;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_with_profile;
+#[cfg(feature = "std")]
+use super::scenarios::scenario_from_record_batched;
+#[cfg(feature = "std")]
 use super::scenarios::scenario_with_obstacles;
+use super::types::Segment;
+#[cfg(feature = "std")]
+use super::utilities::bench_utilities::configurations;
+#[cfg(feature = "std")]
+use super::utilities::golden::check_corpus;
+#[cfg(all(feature = "std", feature = "deterministic"))]
+use super::utilities::golden::digest_corpus;
+#[cfg(feature = "std")]
+use super::utilities::invariants::{check_scenarios, Invariant, Violation};
+#[cfg(feature = "std")]
+use super::utilities::record::Record;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::{test_batches, test_drift};
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_precisions;
+#[cfg(feature = "std")]
 use super::utilities::test_utilities::test_scenarios;
-use super::utilities::types::Obstacles;
+#[cfg(feature = "std")]
+use super::utilities::test_utilities::test_worlds;
+#[cfg(feature = "std")]
+use super::utilities::types::{HasScenario, Obstacles, Origin};
+#[cfg(feature = "std")]
+use super::utilities::world::Kind;
 
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+
+// Returns the configuration of the segment property tests, whose failing
+// seeds are kept in `tests.proptest-regressions` with this crate.
+fn config() -> ProptestConfig {
+    let saved = FileFailurePersistence::WithSource("proptest-regressions");
+    ProptestConfig { failure_persistence: Some(Box::new(saved))
+                   , .. ProptestConfig::default() }
+}
+
+// Relative tolerance of comparisons between transformed points.
+const TOLERANCE: f64 = 1e-9;
+
+// Distance along each axis by which scenarios are translated when testing
+// drift, far enough from the origin that absolute evaluation drifts.
+#[cfg(feature = "std")]
+const OFFSET: f64 = 1e12;
+
+// Returns a strategy producing points.
+fn point() -> BoxedStrategy<Vec2D> {
+    (-1e3f64..1e3f64, -1e3f64..1e3f64).prop_map(|(x, y)| Vec2D::new(x, y))
+                                      .boxed()
+}
+
+// Returns a strategy producing segments at least a thousandth long.
+fn segment() -> BoxedStrategy<(Vec2D, Vec2D)> {
+    (point(), point()).prop_filter("degenerate segment", |&(a, b)| {
+        (b - a).mag() >= 1e-3f64
+    }).boxed()
+}
+
+// Rebuilds scenarios from their records in single precision.
+#[cfg(feature = "std")]
+fn single(record: &Record) -> Option<Box<HasScenario>> {
+    scenario_from_record_as::<f32>(record, Origin::Absolute)
+}
+
+#[cfg(feature = "std")]
 #[test]
 fn test() {
     for i in 1..6 {
         let obstacles1 = Obstacles::new(i, 0u32);
-        assert!(test_scenarios(&obstacles1, scenario_with_obstacles));
+        test_precisions(&obstacles1, scenario_with_obstacles, single).unwrap();
+        let obstacles2 = Obstacles::new(0u32, i);
+        test_precisions(&obstacles2, scenario_with_obstacles, single).unwrap();
+    }
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn segment_round_trips(endpoints in segment(), v in point()) {
+        let segment = Segment::new(endpoints.0, endpoints.1);
+        let tolerance = TOLERANCE * 1e3f64;
+        let local = segment.to_local.transform(v);
+        let round_trip = segment.to_world.transform(local);
+        prop_assert!((round_trip - v).mag() <= tolerance);
+    }
+
+    #[test]
+    fn segment_lies_along_local_axis(endpoints in segment()) {
+        let segment = Segment::new(endpoints.0, endpoints.1);
+        let tolerance = TOLERANCE * 1e3f64;
+        let point1 = segment.to_local.transform(endpoints.0);
+        let point2 = segment.to_local.transform(endpoints.1);
+        prop_assert!(point1.mag() <= tolerance);
+        let end = Vec2D::new(segment.length, 0f64);
+        prop_assert!((point2 - end).mag() <= tolerance);
+    }
+
+    #[test]
+    fn shared_segment_round_trips(endpoints in segment()) {
+        let shared = geometry::Segment::new(endpoints.0, endpoints.1);
+        let tolerance = TOLERANCE * 1e3f64;
+        let round_trip = Segment::from_segment(&shared).to_segment();
+        prop_assert!((round_trip.point1 - shared.point1).mag() <= tolerance);
+        prop_assert!((round_trip.point2 - shared.point2).mag() <= tolerance);
+    }
+}
+
+// Boundary scenarios are only checked in double precision: sources at
+// `NEAREST` are nearer than single precision can distinguish from zero.
+#[cfg(feature = "std")]
+#[test]
+fn boundary() {
+    let creator = boundary_scenario_with_obstacles;
+    for i in 1..6 {
+        let obstacles1 = Obstacles::new(i, 0u32);
+        test_scenarios(&obstacles1, creator).unwrap();
         let obstacles2 = Obstacles::new(0u32, i);
-        assert!(test_scenarios(&obstacles2, scenario_with_obstacles));
+        test_scenarios(&obstacles2, creator).unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn golden() {
+    check_corpus(include_str!("golden.json"), scenario_from_record).unwrap();
+}
+
+// Digest of the golden corpus as evaluated in deterministic mode, which every
+// platform must reproduce.
+#[cfg(all(feature = "std", feature = "deterministic"))]
+const DIGEST: u64 = 0xd5027d3db7ef34e2;
+
+#[cfg(all(feature = "std", feature = "deterministic"))]
+#[test]
+fn deterministic() {
+    let corpus = include_str!("golden.json");
+    let digest = digest_corpus(corpus, scenario_from_record);
+    assert!(digest == DIGEST, "corpus digest {:#018x}", digest);
+}
+
+// Scenarios break no invariant. The tangential term of a potential is
+// perpendicular to its repulsor, so the force of a single obstacle never
+// opposes the nearest repulsor. With several significant obstacles, the
+// potentials of the others can outweigh the nearest obstacle's radial term,
+// so only there may the force point against it.
+#[cfg(feature = "std")]
+#[test]
+fn invariants() {
+    for obstacles in configurations().iter() {
+        let violations = check_scenarios(obstacles, |o| {
+            scenario_with_obstacles(o).unwrap()
+        });
+        let allowed = |x: &Violation| {
+            x.invariant == Invariant::Away && obstacles.significant > 1
+        };
+        assert!( violations.iter().all(allowed)
+               , "{} / {} obstacles violate invariants"
+               , obstacles.insignificant, obstacles.significant );
     }
 }
+
+// Scenarios evaluated with each built-in profile find the same interactions
+// and break no invariant, with the allowance `invariants` makes for several
+// significant obstacles. The quadratic profile evaluates the golden corpus
+// as the default profile does.
+#[cfg(feature = "std")]
+#[test]
+fn profiles() {
+    for name in BUILTIN_NAMES.iter() {
+        let profile = Builtin::named(name).unwrap();
+        let creator = |o: &Obstacles| {
+            let record = scenario_with_obstacles(o)?.record();
+            scenario_from_record_with_profile(&record, profile)
+        };
+        for obstacles in configurations().iter() {
+            test_scenarios(obstacles, creator).unwrap();
+            let violations = check_scenarios(obstacles, |o| {
+                creator(o).unwrap()
+            });
+            let allowed = |x: &Violation| {
+                x.invariant == Invariant::Away && obstacles.significant > 1
+            };
+            assert!( violations.iter().all(allowed)
+                   , "{} violates invariants", name );
+        }
+    }
+    let quadratic = Builtin::named("quadratic").unwrap();
+    check_corpus(include_str!("golden.json"), |r| {
+        scenario_from_record_with_profile(r, quadratic)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn world() {
+    test_worlds(Kind::Segments, scenario_from_world).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn drift() {
+    test_drift(OFFSET, scenario_with_obstacles, |r| {
+        scenario_from_record_with_origin(r, Origin::Relative)
+    }).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn batch() {
+    let rebuild = scenario_from_record;
+    let batched = scenario_from_record_batched;
+    test_batches(scenario_with_obstacles, rebuild, batched).unwrap();
+    test_batches(boundary_scenario_with_obstacles, rebuild, batched).unwrap();
+}
+
+// Walls held in a fixed-capacity collection repel the vehicle as they do in
+// an array.
+#[test]
+fn fixed_capacity() {
+    let vehicle = Vehicle::new(Vec2D::zero(), Vec2D::new(10f64, 0f64), 10f64);
+    let ends = [(8f64, -1f64), (3f64, -2f64), (40f64, 0f64)];
+    let wall = |&(x, y): &(f64, f64)| {
+        Segment::new(Vec2D::new(x, y), Vec2D::new(x + 1f64, y + 4f64))
+    };
+    let walls = [wall(&ends[0]), wall(&ends[1]), wall(&ends[2])];
+    let mut fixed: ArrayVec<Segment, 3> = ArrayVec::new();
+    for end in ends.iter() { fixed.push(wall(end)); }
+    assert!(fixed.try_push(wall(&ends[0])).is_err());
+    let force = vehicle.total_potential(&fixed);
+    assert!(force.is_some());
+    assert_eq!(force, vehicle.total_potential(&walls));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialisation() {
+    let segment = Segment::new(Vec2D::new(1f64, 2f64), Vec2D::new(4f64, 6f64));
+    let json = ::serde_json::to_string(&segment).unwrap();
+    assert_eq!(json, concat!( r#"{"point1":{"x":1.0,"y":2.0},"#
+                            , r#""point2":{"x":4.0,"y":6.0}}"# ));
+    let rebuilt: Segment = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(rebuilt.to_segment(), segment.to_segment());
+}
@@ -1,37 +1,141 @@
-use super::common::types::HasSource;
-use super::linalg::matrix2d::Mat2D;
+use super::common::types::{HasSource, HasSources};
+use super::linalg::batch::{self, Lanes, Transform2DBatch, BLOCK};
+use super::linalg::geometry;
+use super::linalg::scalar::Scalar;
+use super::linalg::transform2d::Transform2D;
 use super::linalg::vector2d::Vec2D;
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "std")]
+use super::utilities::oracle::Geometry;
 
-// Defines transforms in and out of a space containing a line segment.
-pub struct Segment { pub to_world: Mat2D
-                   , pub to_local: Mat2D }
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
-impl HasSource for Segment {
+// Defines transforms in and out of a space in which a line segment of the
+// given length runs along the x-axis from the origin.
+pub struct Segment<T = f64> { pub to_world: Transform2D<T>
+                            , pub to_local: Transform2D<T>
+                            , pub length:   T }
+
+impl<T: Scalar> HasSource<T> for Segment<T> {
     // Returns the source corresponding to the given point.
-    fn source(&self, v: Vec2D) -> Vec2D {
+    fn source(&self, v: Vec2D<T>) -> Vec2D<T> {
         let mut local = self.to_local.transform(v);
-        if local.x < 0f64 { local.x = 0f64; }
-        if local.x > 1f64 { local.x = 1f64; }
-        local.y = 0f64;
+        if local.x < T::ZERO { local.x = T::ZERO; }
+        if local.x > self.length { local.x = self.length; }
+        local.y = T::ZERO;
         self.to_world.transform(local)
     }
+
+    // Returns the endpoints of the segment.
+    #[cfg(feature = "std")]
+    fn geometry(&self) -> Geometry {
+        let segment = self.to_segment();
+        Geometry::Segment { point1: segment.point1.cast()
+                          , point2: segment.point2.cast() }
+    }
+
+    // Returns the source corresponding to the given point relative to the
+    // origin, using only differences from the segment's first endpoint.
+    fn relative_source(&self, origin: Vec2D<T>, v: Vec2D<T>) -> Vec2D<T> {
+        let start = self.to_world.transform(Vec2D::zero()) - origin;
+        let mut local = self.to_local.transform_vector(v - start);
+        if local.x < T::ZERO { local.x = T::ZERO; }
+        if local.x > self.length { local.x = self.length; }
+        local.y = T::ZERO;
+        start + self.to_world.transform_vector(local)
+    }
 }
 
-impl Segment {
+impl<T: Scalar> Segment<T> {
     // Creates a segment from two endpoints.
-    pub fn new(point1: Vec2D, point2: Vec2D) -> Segment {
-        let difference = point2.sub(point1);
+    pub fn new(point1: Vec2D<T>, point2: Vec2D<T>) -> Segment<T> {
+        let difference = point2 - point1;
         let orientation = difference.angle();
         let length = difference.mag();
 
-        let scale = Vec2D::new(length, 1f64);
-        let inverse_scale = Vec2D::new(1f64 / length, 1f64);
-        let to_local = Mat2D::identity().shift(point1.neg())
-                                        .turn(-orientation)
-                                        .scale(inverse_scale);
-        let to_world = Mat2D::identity().scale(scale)
-                                        .turn(orientation)
-                                        .shift(point1);
-        Segment { to_world: to_world, to_local: to_local }
+        let to_world = Transform2D::new(orientation, point1);
+        Segment { to_world: to_world
+                , to_local: to_world.inverse()
+                , length:   length }
+    }
+
+    // Creates a segment with the endpoints of the given segment.
+    pub fn from_segment(segment: &geometry::Segment<T>) -> Segment<T> {
+        Segment::new(segment.point1, segment.point2)
+    }
+
+    // Returns the endpoints of the segment as a shared segment.
+    pub fn to_segment(&self) -> geometry::Segment<T> {
+        let end = Vec2D::new(self.length, T::ZERO);
+        geometry::Segment::new( self.to_world.transform(Vec2D::zero())
+                              , self.to_world.transform(end) )
+    }
+}
+
+// Serialises the endpoints of the segment, from which its transforms and
+// length follow.
+#[cfg(feature = "serde")]
+impl<T: Scalar + Serialize> Serialize for Segment<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        self.to_segment().serialize(serializer)
+    }
+}
+
+// Deserialises a segment from its endpoints.
+#[cfg(feature = "serde")]
+impl<'de, T: Scalar + Deserialize<'de>> Deserialize<'de> for Segment<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+        -> Result<Segment<T>, D::Error>
+    {
+        geometry::Segment::deserialize(deserializer)
+            .map(|x| Segment::from_segment(&x))
+    }
+}
+
+// Segments held as a batch of the transforms in and out of their spaces and
+// their lengths.
+pub struct SegmentBatch<T = f64> { pub to_world: Transform2DBatch<T>
+                                 , pub to_local: Transform2DBatch<T>
+                                 , pub lengths:  Vec<T> }
+
+impl<T: Lanes> HasSources<T> for SegmentBatch<T> {
+    // Returns the number of segments in the batch.
+    fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    // Writes the sources of the segments from `start` corresponding to the
+    // given point.
+    fn sources(&self, start: usize, v: Vec2D<T>, xs: &mut [T], ys: &mut [T]) {
+        let n = xs.len();
+        let mut local = ([T::ZERO; BLOCK], [T::ZERO; BLOCK]);
+        let (lxs, lys) = (&mut local.0[..n], &mut local.1[..n]);
+        batch::transform_each(&self.to_local, start, v, lxs, lys);
+        for i in 0..n {
+            let length = self.lengths[start + i];
+            if lxs[i] < T::ZERO { lxs[i] = T::ZERO; }
+            if lxs[i] > length { lxs[i] = length; }
+            lys[i] = T::ZERO;
+        }
+        batch::transform_lanes(&self.to_world, start, lxs, lys, xs, ys);
+    }
+}
+
+impl<T: Lanes> SegmentBatch<T> {
+    // Creates a batch of the given segments.
+    pub fn new(segments: &[Segment<T>]) -> SegmentBatch<T> {
+        let mut batch = SegmentBatch { to_world: Transform2DBatch::new()
+                                     , to_local: Transform2DBatch::new()
+                                     , lengths:  vec!() };
+        for segment in segments.iter() {
+            batch.to_world.push(&segment.to_world);
+            batch.to_local.push(&segment.to_local);
+            batch.lengths.push(segment.length);
+        }
+        batch
     }
 }